@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+CREATE TABLE `journal`(
+	`id` TEXT NOT NULL PRIMARY KEY,
+	`operation_id` TEXT NOT NULL,
+	`command` TEXT NOT NULL,
+	`created_at` TEXT NOT NULL,
+	`task_uuid` TEXT NOT NULL,
+	`before` TEXT,
+	`after` TEXT
+);
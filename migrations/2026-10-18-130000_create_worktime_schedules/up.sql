@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+CREATE TABLE `worktime_schedules`(
+	`id` TEXT NOT NULL PRIMARY KEY,
+	`worktime_id` TEXT,
+	`weekday` TEXT,
+	`date` TEXT,
+	`description` TEXT
+);
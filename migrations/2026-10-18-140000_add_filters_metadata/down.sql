@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE `filters` DROP COLUMN `last_used`;
+ALTER TABLE `filters` DROP COLUMN `description`;
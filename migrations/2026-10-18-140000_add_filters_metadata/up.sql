@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE `filters` ADD COLUMN `description` TEXT;
+ALTER TABLE `filters` ADD COLUMN `last_used` TEXT;
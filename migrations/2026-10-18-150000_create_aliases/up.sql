@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+CREATE TABLE `aliases`(
+	`id` TEXT NOT NULL PRIMARY KEY,
+	`name` TEXT NOT NULL,
+	`task_uuid` TEXT NOT NULL,
+	`description` TEXT
+);
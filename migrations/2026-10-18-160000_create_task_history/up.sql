@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+CREATE TABLE `task_history`(
+	`id` TEXT NOT NULL PRIMARY KEY,
+	`task_uuid` TEXT NOT NULL,
+	`started_at` TEXT NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+CREATE TABLE `instances`(
+	`id` TEXT NOT NULL PRIMARY KEY,
+	`name` TEXT NOT NULL,
+	`description` TEXT NOT NULL,
+	`taskdata` TEXT NOT NULL,
+	`timewarriordb` TEXT NOT NULL
+);
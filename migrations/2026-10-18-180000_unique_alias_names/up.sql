@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- Keep only the first alias of each name, to be able to create the index.
+DELETE FROM `aliases` WHERE `rowid` NOT IN (
+	SELECT MIN(`rowid`) FROM `aliases` GROUP BY `name`
+);
+
+CREATE UNIQUE INDEX `aliases_name` ON `aliases`(`name`);
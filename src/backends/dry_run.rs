@@ -0,0 +1,243 @@
+//#region           Crates
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use colored::Colorize;
+use serde_json::Value;
+
+//#endregion
+//#region           Modules
+use super::task::{self, TaskBackend, TaskwarriorBackend};
+use super::time::{self, TimeBackend};
+use crate::values::enums::TimewAction;
+use crate::values::err::FypmError;
+use crate::values::structs::{TaskWarriorExported, TimeWarriorExported};
+
+//#endregion
+//#region           Structs
+/// A mutation that was recorded instead of executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedMutation {
+    /// The full command, as it would be executed (ex: `["task", "rc.confirmation=0", ...]`).
+    pub command: Vec<String>,
+    /// UUIDs of the tasks that would be affected. Empty for Timewarrior mutations.
+    pub uuids: Vec<String>,
+}
+
+/// Keeps the mutations of a dry run and prints each one when it is recorded.
+#[derive(Default)]
+pub struct Recorder {
+    mutations: RefCell<Vec<RecordedMutation>>,
+}
+
+/// Reads from the wrapped backend, but only records the mutations.
+pub struct DryRunTaskBackend {
+    inner: Rc<dyn TaskBackend>,
+    recorder: Rc<Recorder>,
+}
+/// Reads from the wrapped backend, but only records the mutations.
+pub struct DryRunTimeBackend {
+    inner: Rc<dyn TimeBackend>,
+    recorder: Rc<Recorder>,
+}
+//#endregion
+//#region           Implementation
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Wrap the current backends, so every mutation until the end of the thread is only recorded.
+pub fn enable() -> Rc<Recorder> {
+    let recorder = Rc::new(Recorder::default());
+    ENABLED.with(|enabled| enabled.set(true));
+
+    task::set_current(Rc::new(DryRunTaskBackend {
+        inner: task::current(),
+        recorder: recorder.clone(),
+    }));
+    time::set_current(Rc::new(DryRunTimeBackend {
+        inner: time::current(),
+        recorder: recorder.clone(),
+    }));
+
+    recorder
+}
+/// If the current thread is in a dry run, so fypm must not save anything else either.
+pub fn is_enabled() -> bool {
+    ENABLED.with(|enabled| enabled.get())
+}
+
+impl Recorder {
+    pub fn mutations(&self) -> Vec<RecordedMutation> {
+        self.mutations.borrow().clone()
+    }
+
+    fn record(&self, program: &str, args: Vec<String>, uuids: Vec<String>) {
+        let mut command = vec![program.to_string()];
+        command.extend(args);
+
+        let mutation = RecordedMutation { command, uuids };
+
+        println!(
+            "{} {}",
+            "[dry-run]".bold().yellow(),
+            mutation
+                .command
+                .iter()
+                .map(|arg| Self::quote(arg))
+                .collect::<Vec<String>>()
+                .join(" ")
+        );
+        if !mutation.uuids.is_empty() {
+            println!("          affects: {}", mutation.uuids.join(", "));
+        }
+
+        self.mutations.borrow_mut().push(mutation);
+    }
+    /// Quote an arg to show it as it should be typed in a shell.
+    fn quote(arg: &str) -> String {
+        if arg.is_empty()
+            || arg.contains(|c: char| c.is_whitespace() || "'\"()\\$&|;<>*".contains(c))
+        {
+            format!("'{}'", arg.replace('\'', "'\\''"))
+        } else {
+            arg.to_string()
+        }
+    }
+}
+
+impl DryRunTaskBackend {
+    fn record(&self, filter: &str, command: &str, args: &[String]) -> Result<(), FypmError> {
+        let uuids = self.inner.uuids(filter)?;
+
+        self.recorder.record(
+            "task",
+            TaskwarriorBackend::mutation_args(filter, command, args),
+            uuids,
+        );
+
+        Ok(())
+    }
+}
+
+impl TaskBackend for DryRunTaskBackend {
+    fn export(
+        &self,
+        filter: &str,
+        overrides: &[String],
+    ) -> Result<Vec<TaskWarriorExported>, FypmError> {
+        self.inner.export(filter, overrides)
+    }
+    fn export_json(&self, filter: &str) -> Result<Vec<Value>, FypmError> {
+        self.inner.export_json(filter)
+    }
+    fn import(&self, tasks: &[Value]) -> Result<(), FypmError> {
+        self.recorder.record(
+            "task",
+            TaskwarriorBackend::import_args(),
+            tasks
+                .iter()
+                .filter_map(|task| task["uuid"].as_str().map(|uuid| uuid.to_string()))
+                .collect(),
+        );
+
+        Ok(())
+    }
+    /// Nothing is created, so a new UUID is returned to let the operation continue.
+    fn add(&self, args: &[String]) -> Result<String, FypmError> {
+        let uuid = uuid::Uuid::now_v7().to_string();
+
+        self.recorder.record(
+            "task",
+            TaskwarriorBackend::add_args(args),
+            vec![uuid.clone()],
+        );
+
+        Ok(uuid)
+    }
+    fn modify(&self, filter: &str, args: &[String]) -> Result<(), FypmError> {
+        self.record(filter, "modify", args)
+    }
+    fn start(&self, filter: &str) -> Result<(), FypmError> {
+        self.record(filter, "start", &[])
+    }
+    fn stop(&self, filter: &str) -> Result<(), FypmError> {
+        self.record(filter, "stop", &[])
+    }
+    fn done(&self, filter: &str) -> Result<(), FypmError> {
+        self.record(filter, "done", &[])
+    }
+    fn delete(&self, filter: &str) -> Result<(), FypmError> {
+        self.record(filter, "delete", &[])
+    }
+    fn annotate(&self, filter: &str, annotation: &str) -> Result<(), FypmError> {
+        self.record(filter, "annotate", &[annotation.to_string()])
+    }
+    fn count(&self, filter: &str) -> Result<u32, FypmError> {
+        self.inner.count(filter)
+    }
+    fn uuids(&self, filter: &str) -> Result<Vec<String>, FypmError> {
+        self.inner.uuids(filter)
+    }
+    fn show_config(&self, key: &str) -> Result<String, FypmError> {
+        self.inner.show_config(key)
+    }
+    fn report(&self, filter: &str, report: &str) -> Result<(), FypmError> {
+        self.inner.report(filter, report)
+    }
+}
+
+impl TimeBackend for DryRunTimeBackend {
+    fn export(&self, filter: &[String]) -> Result<Vec<TimeWarriorExported>, FypmError> {
+        self.inner.export(filter)
+    }
+    fn modify(&self, action: &TimewAction, id: &str, time: &str) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::modify_args(action, id, time), Vec::new());
+
+        Ok(String::new())
+    }
+    fn track(&self, start: &str, end: &str, tags: &[String]) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::track_args(start, end, tags), Vec::new());
+
+        Ok(String::new())
+    }
+    fn continue_interval(&self, id: &str, start: &str, end: &str) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::continue_args(id, start, end), Vec::new());
+
+        Ok(String::new())
+    }
+    fn annotate(&self, ids: &str, annotation: &str) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::annotate_args(ids, annotation), Vec::new());
+
+        Ok(String::new())
+    }
+    fn start(&self, tags: &[String]) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::interval_args("start", tags), Vec::new());
+
+        Ok(String::new())
+    }
+    fn stop(&self, tags: &[String]) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::interval_args("stop", tags), Vec::new());
+
+        Ok(String::new())
+    }
+    fn tag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::tag_args("tag", id, tags), Vec::new());
+
+        Ok(String::new())
+    }
+    fn untag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+        self.recorder
+            .record("timew", time::tag_args("untag", id, tags), Vec::new());
+
+        Ok(String::new())
+    }
+}
+//#endregion
@@ -0,0 +1,200 @@
+//#region           Crates
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use chrono::Local;
+use diesel::SqliteConnection;
+use serde_json::Value;
+
+//#endregion
+//#region           Modules
+use super::task::{self, TaskBackend};
+use crate::db::models::JournalEntry;
+use crate::handlers::journal::JournalHandler;
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::values::structs::TaskWarriorExported;
+
+//#endregion
+//#region           Structs
+/// Records the state of every task before and after the mutations of one fypm operation,
+/// so `fypm undo` can restore them.
+pub struct JournalTaskBackend {
+    inner: Rc<dyn TaskBackend>,
+    conn: RefCell<SqliteConnection>,
+    operation_id: String,
+    command: String,
+    created_at: String,
+    touched: RefCell<HashSet<String>>,
+}
+//#endregion
+//#region           Implementation
+/// Wrap the current backend, so every mutation until the end of the thread is journaled as `command`.
+pub fn enable(conn: SqliteConnection, command: &str) {
+    task::set_current(Rc::new(JournalTaskBackend {
+        inner: task::current(),
+        conn: RefCell::new(conn),
+        operation_id: uuid::Uuid::now_v7().to_string(),
+        command: command.to_string(),
+        created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        touched: RefCell::new(HashSet::new()),
+    }));
+}
+
+impl JournalTaskBackend {
+    fn database_error(error: diesel::result::Error) -> FypmError {
+        FypmError {
+            message: format!("Failed to write the journal: {}", error),
+            kind: FypmErrorKind::BackendError,
+        }
+    }
+    /// Export the tasks of all the uuids at once, as JSON text by uuid.
+    fn snapshots(&self, uuids: &[String]) -> Result<HashMap<String, String>, FypmError> {
+        if uuids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(self
+            .inner
+            .export_json(&uuids.join(" "))?
+            .into_iter()
+            .filter_map(|task| {
+                task["uuid"]
+                    .as_str()
+                    .map(|uuid| (uuid.to_string(), task.to_string()))
+            })
+            .collect())
+    }
+    /// Save the state of the tasks before they are touched for the first time in this operation.
+    /// Tasks that don't exist yet are saved as created by the operation.
+    fn before(&self, uuids: &[String], created: bool) -> Result<(), FypmError> {
+        let first = self.touched.borrow().is_empty();
+
+        let uuids = uuids
+            .iter()
+            .filter(|uuid| self.touched.borrow_mut().insert(uuid.to_string()))
+            .cloned()
+            .collect::<Vec<String>>();
+        let mut snapshots = if created {
+            HashMap::new()
+        } else {
+            self.snapshots(&uuids)?
+        };
+
+        for uuid in uuids {
+            JournalHandler::record(
+                &mut self.conn.borrow_mut(),
+                &JournalEntry {
+                    id: uuid::Uuid::now_v7().to_string(),
+                    operation_id: self.operation_id.clone(),
+                    command: self.command.clone(),
+                    created_at: self.created_at.clone(),
+                    before: snapshots.remove(&uuid),
+                    task_uuid: uuid,
+                    after: None,
+                },
+            )
+            .map_err(Self::database_error)?;
+        }
+
+        // Once this operation is saved, the ones before the last `JOURNAL_LIMIT` are forgotten.
+        if first && !self.touched.borrow().is_empty() {
+            JournalHandler::prune(&mut self.conn.borrow_mut()).map_err(Self::database_error)?;
+        }
+
+        Ok(())
+    }
+    fn after(&self, uuids: &[String]) -> Result<(), FypmError> {
+        let mut snapshots = self.snapshots(uuids)?;
+
+        for uuid in uuids {
+            JournalHandler::update_after(
+                &mut self.conn.borrow_mut(),
+                &self.operation_id,
+                uuid,
+                &snapshots.remove(uuid),
+            )
+            .map_err(Self::database_error)?;
+        }
+
+        Ok(())
+    }
+    fn journaled<F>(&self, filter: &str, mutation: F) -> Result<(), FypmError>
+    where
+        F: FnOnce(&dyn TaskBackend) -> Result<(), FypmError>,
+    {
+        let uuids = self.inner.uuids(filter)?;
+
+        self.before(&uuids, false)?;
+        let result = mutation(self.inner.as_ref());
+        // Even if the mutation fails, some tasks may have been changed.
+        self.after(&uuids)?;
+
+        result
+    }
+}
+
+impl TaskBackend for JournalTaskBackend {
+    fn export(
+        &self,
+        filter: &str,
+        overrides: &[String],
+    ) -> Result<Vec<TaskWarriorExported>, FypmError> {
+        self.inner.export(filter, overrides)
+    }
+    fn export_json(&self, filter: &str) -> Result<Vec<Value>, FypmError> {
+        self.inner.export_json(filter)
+    }
+    fn import(&self, tasks: &[Value]) -> Result<(), FypmError> {
+        let uuids = tasks
+            .iter()
+            .filter_map(|task| task["uuid"].as_str().map(|uuid| uuid.to_string()))
+            .collect::<Vec<String>>();
+
+        // The tasks that aren't exported yet are created by the import.
+        self.before(&uuids, false)?;
+        let result = self.inner.import(tasks);
+        self.after(&uuids)?;
+
+        result
+    }
+    fn add(&self, args: &[String]) -> Result<String, FypmError> {
+        let uuid = self.inner.add(args)?;
+
+        self.before(std::slice::from_ref(&uuid), true)?;
+        self.after(std::slice::from_ref(&uuid))?;
+
+        Ok(uuid)
+    }
+    fn modify(&self, filter: &str, args: &[String]) -> Result<(), FypmError> {
+        self.journaled(filter, |inner| inner.modify(filter, args))
+    }
+    fn start(&self, filter: &str) -> Result<(), FypmError> {
+        self.journaled(filter, |inner| inner.start(filter))
+    }
+    fn stop(&self, filter: &str) -> Result<(), FypmError> {
+        self.journaled(filter, |inner| inner.stop(filter))
+    }
+    fn done(&self, filter: &str) -> Result<(), FypmError> {
+        self.journaled(filter, |inner| inner.done(filter))
+    }
+    fn delete(&self, filter: &str) -> Result<(), FypmError> {
+        self.journaled(filter, |inner| inner.delete(filter))
+    }
+    fn annotate(&self, filter: &str, annotation: &str) -> Result<(), FypmError> {
+        self.journaled(filter, |inner| inner.annotate(filter, annotation))
+    }
+    fn count(&self, filter: &str) -> Result<u32, FypmError> {
+        self.inner.count(filter)
+    }
+    fn uuids(&self, filter: &str) -> Result<Vec<String>, FypmError> {
+        self.inner.uuids(filter)
+    }
+    fn show_config(&self, key: &str) -> Result<String, FypmError> {
+        self.inner.show_config(key)
+    }
+    fn report(&self, filter: &str, report: &str) -> Result<(), FypmError> {
+        self.inner.report(filter, report)
+    }
+}
+//#endregion
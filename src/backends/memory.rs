@@ -0,0 +1,767 @@
+//#region           Crates
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+use serde_json::{Map, Value};
+
+//#endregion
+//#region           Modules
+use super::task::TaskBackend;
+use crate::handlers::config::ConfigHandler;
+use crate::values::constants::TASKWARRIOR_DATE_FORMAT;
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::values::structs::TaskWarriorExported;
+
+//#endregion
+//#region           Constants
+const CORE_DATE_ATTRIBUTES: [&str; 8] = [
+    "due",
+    "scheduled",
+    "wait",
+    "until",
+    "start",
+    "end",
+    "entry",
+    "modified",
+];
+//#endregion
+//#region           Structs
+/// A task database that lives in memory, made for testing workflows without Taskwarrior.
+///
+/// It understands the subset of the filter language used by fypm: ids and UUIDs,
+/// `+TAG`/`-TAG` (including the common virtual tags), `attribute[.modifier]:value`,
+/// description patterns, `and`/`or` and parentheses. Recurrence is not simulated.
+pub struct MemoryTaskBackend {
+    tasks: RefCell<Vec<Map<String, Value>>>,
+    config: RefCell<BTreeMap<String, String>>,
+    reports: RefCell<Vec<(String, Vec<String>)>>,
+    defaults: BTreeMap<String, String>,
+    date_attributes: Vec<String>,
+}
+
+#[derive(Debug)]
+enum Filter {
+    All,
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Ids(Vec<String>),
+    Tag {
+        name: String,
+        present: bool,
+    },
+    Attribute {
+        name: String,
+        modifier: String,
+        value: String,
+    },
+    Pattern(String),
+}
+//#endregion
+//#region           Implementation
+impl Default for MemoryTaskBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryTaskBackend {
+    /// Create an empty database that applies the fypm UDA defaults (ex: `STATE:Time`).
+    pub fn new() -> Self {
+        let config_defaults = ConfigHandler::create_config_defaults();
+
+        let mut defaults = BTreeMap::new();
+        let mut date_attributes: Vec<String> = CORE_DATE_ATTRIBUTES
+            .iter()
+            .map(|attr| attr.to_string())
+            .collect();
+
+        for (uda, config) in &config_defaults.uda {
+            if let Some(default) = &config.default {
+                defaults.insert(uda.to_string(), default.clone());
+            }
+            if config.r#type == "date" {
+                date_attributes.push(uda.to_string());
+            }
+        }
+
+        Self {
+            tasks: RefCell::new(Vec::new()),
+            config: RefCell::new(BTreeMap::new()),
+            reports: RefCell::new(Vec::new()),
+            defaults,
+            date_attributes,
+        }
+    }
+    /// Set a config value returned by `show_config`.
+    pub fn with_config(self, key: &str, value: &str) -> Self {
+        self.config
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+
+        self
+    }
+    /// Get the reports that were shown, with the UUIDs of their tasks.
+    pub fn reports(&self) -> Vec<(String, Vec<String>)> {
+        self.reports.borrow().clone()
+    }
+    /// Insert a raw task (as exported by Taskwarrior) and return its UUID.
+    /// Missing `uuid`, `status`, `entry` and `modified` are filled.
+    pub fn insert(&self, task: Value) -> String {
+        let mut task = match task {
+            Value::Object(map) => map,
+            _ => panic!("A task must be a JSON object!"),
+        };
+        let now = Utc::now().format(TASKWARRIOR_DATE_FORMAT).to_string();
+
+        if !task.contains_key("uuid") {
+            task.insert(
+                "uuid".to_string(),
+                Value::String(uuid::Uuid::now_v7().to_string()),
+            );
+        }
+        for (key, value) in [("status", "pending"), ("entry", &now), ("modified", &now)] {
+            if !task.contains_key(key) {
+                task.insert(key.to_string(), Value::String(value.to_string()));
+            }
+        }
+
+        let uuid = task["uuid"].as_str().unwrap().to_string();
+        self.tasks.borrow_mut().push(task);
+
+        uuid
+    }
+
+    fn error(message: String) -> FypmError {
+        FypmError {
+            message,
+            kind: FypmErrorKind::InvalidInput,
+        }
+    }
+
+    //#region       Dates
+    fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+        let today = Local::now().date_naive();
+        let local_midnight = |date: NaiveDate| {
+            Local
+                .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                .earliest()
+                .map(|date| date.with_timezone(&Utc))
+        };
+
+        match value {
+            "now" => return Some(Utc::now()),
+            "today" | "sod" => return local_midnight(today),
+            "tomorrow" | "eod" => return local_midnight(today + Duration::days(1)),
+            "yesterday" => return local_midnight(today - Duration::days(1)),
+            _ => {}
+        }
+
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, TASKWARRIOR_DATE_FORMAT) {
+            return Some(Utc.from_utc_datetime(&date));
+        }
+        if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+            return Some(date.with_timezone(&Utc));
+        }
+        for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+            if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+                return Local
+                    .from_local_datetime(&date)
+                    .earliest()
+                    .map(|date| date.with_timezone(&Utc));
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return local_midnight(date);
+        }
+
+        None
+    }
+    /// If the value represents a whole day (ex: `today`, `2024-01-01`), matches are made by day.
+    fn is_day_value(value: &str) -> bool {
+        matches!(value, "today" | "tomorrow" | "yesterday")
+            || NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+    }
+    //#endregion
+    //#region       Parsing
+    fn tokenize(filter: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut current = String::new();
+        let mut quote: Option<char> = None;
+
+        for char in filter.chars() {
+            match (quote, char) {
+                (Some(open), c) if c == open => quote = None,
+                (Some(_), c) => current.push(c),
+                (None, '\'' | '"') => quote = Some(char),
+                (None, '(' | ')') => {
+                    if !current.is_empty() {
+                        tokens.push(std::mem::take(&mut current));
+                    }
+                    tokens.push(char.to_string());
+                }
+                (None, c) if c.is_whitespace() => {
+                    if !current.is_empty() {
+                        tokens.push(std::mem::take(&mut current));
+                    }
+                }
+                (None, c) => current.push(c),
+            }
+        }
+        if !current.is_empty() {
+            tokens.push(current);
+        }
+
+        tokens
+    }
+    fn parse_filter(filter: &str) -> Result<Filter, FypmError> {
+        let tokens = Self::tokenize(filter);
+        let mut position = 0;
+
+        if tokens.is_empty() {
+            return Ok(Filter::All);
+        }
+
+        let parsed = Self::parse_or(&tokens, &mut position)?;
+
+        if position < tokens.len() {
+            return Err(Self::error(format!(
+                "Unexpected token in filter: {}",
+                tokens[position]
+            )));
+        }
+
+        Ok(parsed)
+    }
+    fn parse_or(tokens: &[String], position: &mut usize) -> Result<Filter, FypmError> {
+        let mut left = Self::parse_and(tokens, position)?;
+
+        while tokens.get(*position).map(|token| token.as_str()) == Some("or") {
+            *position += 1;
+            let right = Self::parse_and(tokens, position)?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+    fn parse_and(tokens: &[String], position: &mut usize) -> Result<Filter, FypmError> {
+        let mut left = Self::parse_term(tokens, position)?;
+
+        loop {
+            match tokens.get(*position).map(|token| token.as_str()) {
+                None | Some(")") | Some("or") => break,
+                Some("and") => *position += 1,
+                _ => {}
+            }
+
+            let right = Self::parse_term(tokens, position)?;
+
+            // Like Taskwarrior, a sequence of ids is a list, not an intersection.
+            left = match (left, right) {
+                (Filter::Ids(mut ids), Filter::Ids(other)) => {
+                    ids.extend(other);
+                    Filter::Ids(ids)
+                }
+                (left, right) => Filter::And(Box::new(left), Box::new(right)),
+            };
+        }
+
+        Ok(left)
+    }
+    fn parse_term(tokens: &[String], position: &mut usize) -> Result<Filter, FypmError> {
+        let token = tokens
+            .get(*position)
+            .ok_or(Self::error("The filter ended unexpectedly!".to_string()))?;
+        *position += 1;
+
+        if token == "(" {
+            let inner = Self::parse_or(tokens, position)?;
+
+            if tokens.get(*position).map(|token| token.as_str()) != Some(")") {
+                return Err(Self::error("Unbalanced parentheses in filter!".to_string()));
+            }
+            *position += 1;
+
+            return Ok(inner);
+        }
+
+        let id_regex = Regex::new(
+            r"^(\d+(-\d+)?|[0-9a-fA-F]{8}(-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})?)(,(\d+(-\d+)?|[0-9a-fA-F]{8}(-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})?))*$",
+        )
+        .unwrap();
+        let attribute_regex = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*)(\.([a-z]+))?:(.*)$").unwrap();
+
+        if id_regex.is_match(token) {
+            Ok(Filter::Ids(
+                token.split(',').map(|id| id.to_lowercase()).collect(),
+            ))
+        } else if (token.starts_with('+') || token.starts_with('-')) && token.len() > 1 {
+            Ok(Filter::Tag {
+                name: token[1..].to_string(),
+                present: token.starts_with('+'),
+            })
+        } else if let Some(captures) = attribute_regex.captures(token) {
+            Ok(Filter::Attribute {
+                name: captures[1].to_string(),
+                modifier: captures
+                    .get(3)
+                    .map(|modifier| modifier.as_str().to_string())
+                    .unwrap_or_default(),
+                value: captures[4].to_string(),
+            })
+        } else {
+            Ok(Filter::Pattern(token.to_string()))
+        }
+    }
+    //#endregion
+    //#region       Matching
+    fn text(task: &Map<String, Value>, name: &str) -> Option<String> {
+        match task.get(name)? {
+            Value::String(value) => Some(value.clone()),
+            Value::Array(values) => Some(
+                values
+                    .iter()
+                    .map(|value| {
+                        value
+                            .as_str()
+                            .map(|v| v.to_string())
+                            .unwrap_or(value.to_string())
+                    })
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ),
+            Value::Null => None,
+            value => Some(value.to_string()),
+        }
+    }
+    fn has_tag(task: &Map<String, Value>, tag: &str) -> bool {
+        let status = Self::text(task, "status").unwrap_or_default();
+        let is_pending = status == "pending";
+        let date_before_now = |attribute: &str| {
+            Self::text(task, attribute)
+                .and_then(|date| Self::parse_date(&date))
+                .map(|date| date < Utc::now())
+        };
+
+        match tag {
+            "PENDING" => is_pending,
+            "COMPLETED" => status == "completed",
+            "DELETED" => status == "deleted",
+            "WAITING" => status == "waiting" || date_before_now("wait") == Some(false),
+            "PARENT" => status == "recurring",
+            "INSTANCE" | "CHILD" => task.contains_key("parent"),
+            "ACTIVE" => is_pending && task.contains_key("start"),
+            "SCHEDULED" => task.contains_key("scheduled"),
+            "OVERDUE" => is_pending && date_before_now("due") == Some(true),
+            "ANNOTATED" => task.contains_key("annotations"),
+            "TAGGED" => task
+                .get("tags")
+                .and_then(|tags| tags.as_array())
+                .is_some_and(|tags| !tags.is_empty()),
+            _ => task
+                .get("tags")
+                .and_then(|tags| tags.as_array())
+                .is_some_and(|tags| tags.iter().any(|item| item == tag)),
+        }
+    }
+    fn matches_attribute(
+        &self,
+        task: &Map<String, Value>,
+        name: &str,
+        modifier: &str,
+        expected: &str,
+    ) -> Result<bool, FypmError> {
+        let value = Self::text(task, name).filter(|value| !value.is_empty());
+
+        match modifier {
+            "any" => return Ok(value.is_some()),
+            "none" => return Ok(value.is_none()),
+            _ => {}
+        }
+
+        if self
+            .date_attributes
+            .iter()
+            .any(|attribute| attribute == name)
+            && !expected.is_empty()
+        {
+            let expected_date = Self::parse_date(expected)
+                .ok_or(Self::error(format!("Invalid date in filter: {}", expected)))?;
+            let Some(date) = value.and_then(|value| Self::parse_date(&value)) else {
+                return Ok(matches!(modifier, "not" | "isnt"));
+            };
+            let same = if Self::is_day_value(expected) {
+                date >= expected_date && date < expected_date + Duration::days(1)
+            } else {
+                date == expected_date
+            };
+
+            return match modifier {
+                "" | "is" | "equals" => Ok(same),
+                "not" | "isnt" => Ok(!same),
+                "before" | "below" => Ok(date < expected_date),
+                "after" | "above" => Ok(date > expected_date),
+                "by" => Ok(date <= expected_date),
+                _ => Err(Self::error(format!("Unsupported modifier: {}", modifier))),
+            };
+        }
+
+        let Some(value) = value else {
+            return Ok(match modifier {
+                "" | "is" | "equals" => expected.is_empty(),
+                "not" | "isnt" | "hasnt" => !expected.is_empty(),
+                _ => false,
+            });
+        };
+
+        match modifier {
+            "" if name == "project" => {
+                Ok(value == expected || value.starts_with(&format!("{}.", expected)))
+            }
+            "" | "is" | "equals" => Ok(value == expected),
+            "not" | "isnt" => Ok(value != expected),
+            "has" | "contains" => Ok(value.contains(expected)),
+            "hasnt" => Ok(!value.contains(expected)),
+            "startswith" | "left" => Ok(value.starts_with(expected)),
+            "endswith" | "right" => Ok(value.ends_with(expected)),
+            "before" | "below" => Ok(value.as_str() < expected),
+            "after" | "above" => Ok(value.as_str() > expected),
+            _ => Err(Self::error(format!("Unsupported modifier: {}", modifier))),
+        }
+    }
+    fn matches(
+        &self,
+        task: &Map<String, Value>,
+        id: u32,
+        filter: &Filter,
+    ) -> Result<bool, FypmError> {
+        match filter {
+            Filter::All => Ok(true),
+            Filter::And(left, right) => {
+                Ok(self.matches(task, id, left)? && self.matches(task, id, right)?)
+            }
+            Filter::Or(left, right) => {
+                Ok(self.matches(task, id, left)? || self.matches(task, id, right)?)
+            }
+            Filter::Ids(ids) => {
+                let uuid = Self::text(task, "uuid").unwrap_or_default();
+
+                Ok(ids.iter().any(|item| {
+                    if let Some((start, end)) = item.split_once('-').filter(|_| item.len() < 36) {
+                        match (start.parse::<u32>(), end.parse::<u32>()) {
+                            (Ok(start), Ok(end)) => id != 0 && id >= start && id <= end,
+                            _ => false,
+                        }
+                    } else if let Ok(number) = item.parse::<u32>() {
+                        id != 0 && id == number
+                    } else {
+                        uuid.starts_with(item.as_str())
+                    }
+                }))
+            }
+            Filter::Tag { name, present } => Ok(Self::has_tag(task, name) == *present),
+            Filter::Attribute {
+                name,
+                modifier,
+                value,
+            } => self.matches_attribute(task, name, modifier, value),
+            Filter::Pattern(pattern) => {
+                let in_description = Self::text(task, "description")
+                    .is_some_and(|description| description.contains(pattern.as_str()));
+                let in_annotations = task
+                    .get("annotations")
+                    .and_then(|annotations| annotations.as_array())
+                    .is_some_and(|annotations| {
+                        annotations.iter().any(|annotation| {
+                            annotation["description"]
+                                .as_str()
+                                .is_some_and(|description| description.contains(pattern.as_str()))
+                        })
+                    });
+
+                Ok(in_description || in_annotations)
+            }
+        }
+    }
+    /// Get the working set ids, following the tasks order.
+    fn ids(&self) -> Vec<u32> {
+        let mut next_id = 0;
+
+        self.tasks
+            .borrow()
+            .iter()
+            .map(
+                |task| match Self::text(task, "status").unwrap_or_default().as_str() {
+                    "pending" | "waiting" | "recurring" => {
+                        next_id += 1;
+                        next_id
+                    }
+                    _ => 0,
+                },
+            )
+            .collect()
+    }
+    /// Get the indexes of the tasks that match the filter.
+    fn select(&self, filter: &str) -> Result<Vec<usize>, FypmError> {
+        let parsed_filter = Self::parse_filter(filter)?;
+        let ids = self.ids();
+        let tasks = self.tasks.borrow();
+
+        let mut selected = Vec::new();
+        for (index, task) in tasks.iter().enumerate() {
+            if self.matches(task, ids[index], &parsed_filter)? {
+                selected.push(index);
+            }
+        }
+
+        Ok(selected)
+    }
+    //#endregion
+    //#region       Mutation
+    fn normalize_value(&self, name: &str, value: &str) -> Result<String, FypmError> {
+        if self
+            .date_attributes
+            .iter()
+            .any(|attribute| attribute == name)
+        {
+            Self::parse_date(value)
+                .map(|date| date.format(TASKWARRIOR_DATE_FORMAT).to_string())
+                .ok_or(Self::error(format!("Invalid date for {}: {}", name, value)))
+        } else {
+            Ok(value.to_string())
+        }
+    }
+    fn apply_modifications(
+        &self,
+        task: &mut Map<String, Value>,
+        args: &[String],
+    ) -> Result<(), FypmError> {
+        let attribute_regex = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*):(.*)$").unwrap();
+        let mut description_words = Vec::new();
+
+        for arg in args {
+            let is_single_word = !arg.contains(char::is_whitespace);
+
+            if is_single_word && (arg.starts_with('+') || arg.starts_with('-')) && arg.len() > 1 {
+                let tag = Value::String(arg[1..].to_string());
+                let tags = task
+                    .entry("tags")
+                    .or_insert(Value::Array(Vec::new()))
+                    .as_array_mut()
+                    .unwrap();
+
+                if arg.starts_with('+') {
+                    if !tags.contains(&tag) {
+                        tags.push(tag);
+                    }
+                } else {
+                    tags.retain(|item| *item != tag);
+                }
+
+                if tags.is_empty() {
+                    task.remove("tags");
+                }
+            } else if let Some(captures) = attribute_regex.captures(arg).filter(|_| is_single_word)
+            {
+                let name = captures[1].to_string();
+                let value = captures[2].trim_matches(|c| c == '\'' || c == '"');
+
+                if value.is_empty() {
+                    task.remove(&name);
+                } else {
+                    let value = self.normalize_value(&name, value)?;
+                    task.insert(name, Value::String(value));
+                }
+            } else {
+                description_words.push(arg.clone());
+            }
+        }
+
+        if !description_words.is_empty() {
+            task.insert(
+                "description".to_string(),
+                Value::String(description_words.join(" ")),
+            );
+        }
+        task.insert(
+            "modified".to_string(),
+            Value::String(Utc::now().format(TASKWARRIOR_DATE_FORMAT).to_string()),
+        );
+
+        Ok(())
+    }
+    fn update_selected<F>(&self, filter: &str, mut update: F) -> Result<(), FypmError>
+    where
+        F: FnMut(&mut Map<String, Value>) -> Result<(), FypmError>,
+    {
+        let selected = self.select(filter)?;
+
+        if selected.is_empty() {
+            return Err(FypmError {
+                message: format!("No tasks found for filter: {}", filter),
+                kind: FypmErrorKind::NoTasksFound,
+            });
+        }
+
+        let mut tasks = self.tasks.borrow_mut();
+        for index in selected {
+            update(&mut tasks[index])?;
+        }
+
+        Ok(())
+    }
+    fn now() -> Value {
+        Value::String(Utc::now().format(TASKWARRIOR_DATE_FORMAT).to_string())
+    }
+    //#endregion
+}
+
+impl TaskBackend for MemoryTaskBackend {
+    fn export(
+        &self,
+        filter: &str,
+        _overrides: &[String],
+    ) -> Result<Vec<TaskWarriorExported>, FypmError> {
+        let selected = self.select(filter)?;
+        let ids = self.ids();
+        let tasks = self.tasks.borrow();
+
+        selected
+            .into_iter()
+            .map(|index| {
+                let mut task = tasks[index].clone();
+                task.insert("id".to_string(), Value::from(ids[index]));
+                task.entry("urgency").or_insert(Value::from(0.0));
+
+                serde_json::from_value::<TaskWarriorExported>(Value::Object(task)).map_err(
+                    |error| FypmError {
+                        message: format!("Failed to parse the exported tasks: {}", error),
+                        kind: FypmErrorKind::ProblemWithStoredTask,
+                    },
+                )
+            })
+            .collect()
+    }
+    fn export_json(&self, filter: &str) -> Result<Vec<Value>, FypmError> {
+        let selected = self.select(filter)?;
+        let tasks = self.tasks.borrow();
+
+        Ok(selected
+            .into_iter()
+            .map(|index| Value::Object(tasks[index].clone()))
+            .collect())
+    }
+    fn import(&self, tasks: &[Value]) -> Result<(), FypmError> {
+        for task in tasks {
+            let mut task = task
+                .as_object()
+                .cloned()
+                .ok_or(Self::error("A task must be a JSON object!".to_string()))?;
+            task.remove("id");
+            task.remove("urgency");
+
+            let uuid = Self::text(&task, "uuid")
+                .ok_or(Self::error("An imported task must have an UUID!".to_string()))?;
+            let position = self
+                .tasks
+                .borrow()
+                .iter()
+                .position(|item| Self::text(item, "uuid").as_ref() == Some(&uuid));
+
+            match position {
+                Some(index) => self.tasks.borrow_mut()[index] = task,
+                None => {
+                    self.insert(Value::Object(task));
+                }
+            }
+        }
+
+        Ok(())
+    }
+    fn add(&self, args: &[String]) -> Result<String, FypmError> {
+        let mut task = Map::new();
+
+        for (key, value) in &self.defaults {
+            task.insert(key.clone(), Value::String(value.clone()));
+        }
+        self.apply_modifications(&mut task, args)?;
+
+        if !task.contains_key("description") {
+            return Err(Self::error("A task must have a description!".to_string()));
+        }
+
+        Ok(self.insert(Value::Object(task)))
+    }
+    fn modify(&self, filter: &str, args: &[String]) -> Result<(), FypmError> {
+        self.update_selected(filter, |task| self.apply_modifications(task, args))
+    }
+    fn start(&self, filter: &str) -> Result<(), FypmError> {
+        self.update_selected(filter, |task| {
+            task.insert("start".to_string(), Self::now());
+            Ok(())
+        })
+    }
+    fn stop(&self, filter: &str) -> Result<(), FypmError> {
+        self.update_selected(filter, |task| {
+            task.remove("start");
+            Ok(())
+        })
+    }
+    fn done(&self, filter: &str) -> Result<(), FypmError> {
+        self.update_selected(filter, |task| {
+            task.remove("start");
+            task.insert("status".to_string(), Value::from("completed"));
+            task.insert("end".to_string(), Self::now());
+            Ok(())
+        })
+    }
+    fn delete(&self, filter: &str) -> Result<(), FypmError> {
+        self.update_selected(filter, |task| {
+            task.remove("start");
+            task.insert("status".to_string(), Value::from("deleted"));
+            task.insert("end".to_string(), Self::now());
+            Ok(())
+        })
+    }
+    fn annotate(&self, filter: &str, annotation: &str) -> Result<(), FypmError> {
+        self.update_selected(filter, |task| {
+            let mut entry = Map::new();
+            entry.insert("entry".to_string(), Self::now());
+            entry.insert("description".to_string(), Value::from(annotation));
+
+            task.entry("annotations")
+                .or_insert(Value::Array(Vec::new()))
+                .as_array_mut()
+                .unwrap()
+                .push(Value::Object(entry));
+            Ok(())
+        })
+    }
+    fn count(&self, filter: &str) -> Result<u32, FypmError> {
+        Ok(self.select(filter)?.len() as u32)
+    }
+    fn uuids(&self, filter: &str) -> Result<Vec<String>, FypmError> {
+        let selected = self.select(filter)?;
+        let tasks = self.tasks.borrow();
+
+        Ok(selected
+            .into_iter()
+            .map(|index| Self::text(&tasks[index], "uuid").unwrap_or_default())
+            .collect())
+    }
+    fn show_config(&self, key: &str) -> Result<String, FypmError> {
+        Ok(self.config.borrow().get(key).cloned().unwrap_or_default())
+    }
+    fn report(&self, filter: &str, report: &str) -> Result<(), FypmError> {
+        let uuids = self.uuids(filter)?;
+
+        for uuid in &uuids {
+            println!("{}", uuid);
+        }
+        self.reports.borrow_mut().push((report.to_string(), uuids));
+
+        Ok(())
+    }
+}
+//#endregion
@@ -0,0 +1,6 @@
+pub mod dry_run;
+pub mod journal;
+#[cfg(test)]
+pub mod memory;
+pub mod task;
+pub mod time;
@@ -0,0 +1,299 @@
+//#region           Crates
+use std::cell::RefCell;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::rc::Rc;
+use std::str;
+
+use serde_json::Value;
+
+//#endregion
+//#region           Modules
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::values::structs::TaskWarriorExported;
+
+//#endregion
+//#region           Traits
+/// Every operation that fypm performs against a task database.
+///
+/// Commands must go through [`current`] instead of spawning `task` by themselves,
+/// so the same workflow can run against Taskwarrior or against a [`super::memory::MemoryTaskBackend`].
+pub trait TaskBackend {
+    /// Export tasks that match the filter. `overrides` are `rc.*` arguments for the backend.
+    fn export(
+        &self,
+        filter: &str,
+        overrides: &[String],
+    ) -> Result<Vec<TaskWarriorExported>, FypmError>;
+    /// Export tasks that match the filter as Taskwarrior JSON, keeping every attribute.
+    fn export_json(&self, filter: &str) -> Result<Vec<Value>, FypmError>;
+    /// Create or replace tasks (by UUID) with the received Taskwarrior JSON.
+    fn import(&self, tasks: &[Value]) -> Result<(), FypmError>;
+    /// Create a task with the received modifications and return its UUID.
+    fn add(&self, args: &[String]) -> Result<String, FypmError>;
+    fn modify(&self, filter: &str, args: &[String]) -> Result<(), FypmError>;
+    fn start(&self, filter: &str) -> Result<(), FypmError>;
+    fn stop(&self, filter: &str) -> Result<(), FypmError>;
+    fn done(&self, filter: &str) -> Result<(), FypmError>;
+    fn delete(&self, filter: &str) -> Result<(), FypmError>;
+    fn annotate(&self, filter: &str, annotation: &str) -> Result<(), FypmError>;
+    fn count(&self, filter: &str) -> Result<u32, FypmError>;
+    fn uuids(&self, filter: &str) -> Result<Vec<String>, FypmError>;
+    /// Get the value of a config key (ex: `report.next.filter`). Returns an empty string if it isn't set.
+    fn show_config(&self, key: &str) -> Result<String, FypmError>;
+    /// Show a report (ex: `list`) of the tasks that match the filter to the user.
+    fn report(&self, filter: &str, report: &str) -> Result<(), FypmError>;
+}
+//#endregion
+//#region           Selection
+thread_local! {
+    static TASK_BACKEND: RefCell<Rc<dyn TaskBackend>> = RefCell::new(Rc::new(TaskwarriorBackend));
+}
+
+/// Get the backend used by the current thread (Taskwarrior by default).
+pub fn current() -> Rc<dyn TaskBackend> {
+    TASK_BACKEND.with(|backend| backend.borrow().clone())
+}
+/// Replace the backend used by the current thread.
+pub fn set_current(backend: Rc<dyn TaskBackend>) {
+    TASK_BACKEND.with(|current| *current.borrow_mut() = backend);
+}
+//#endregion
+//#region           Taskwarrior
+/// The default backend, that runs the `task` binary.
+pub struct TaskwarriorBackend;
+
+impl TaskwarriorBackend {
+    fn command(&self) -> Command {
+        Command::new("task")
+    }
+
+    fn verify_output(output: Output) -> Result<Output, FypmError> {
+        if output.status.success() {
+            Ok(output)
+        } else {
+            Err(FypmError {
+                message: format!(
+                    "Taskwarrior failed to execute the command: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                kind: FypmErrorKind::BackendError,
+            })
+        }
+    }
+    fn read(&self, args: &[&str]) -> Result<String, FypmError> {
+        let output = self
+            .command()
+            .args(args)
+            .output()
+            .map_err(|error| FypmError {
+                message: format!("Failed to execute task: {}", error),
+                kind: FypmErrorKind::BackendError,
+            })?;
+
+        Ok(String::from_utf8_lossy(&Self::verify_output(output)?.stdout).to_string())
+    }
+    /// Get the args that `task` receives for a mutation.
+    /// Only `done` and `delete` keep Taskwarrior messages, to show what was affected.
+    pub fn mutation_args(filter: &str, command: &str, args: &[String]) -> Vec<String> {
+        let mut final_args = vec![
+            "rc.confirmation=0".to_string(),
+            "rc.recurrence.confirmation=0".to_string(),
+        ];
+        if !Self::is_verbose(command) {
+            final_args.push("rc.verbose=0".to_string());
+        }
+        final_args.extend([filter.to_string(), command.to_string()]);
+        final_args.extend(args.iter().cloned());
+
+        final_args
+    }
+    /// Get the args that `task` receives to import tasks from stdin.
+    pub fn import_args() -> Vec<String> {
+        vec!["rc.verbose=0".to_string(), "import".to_string(), "-".to_string()]
+    }
+    /// Get the args that `task` receives to create a task.
+    pub fn add_args(args: &[String]) -> Vec<String> {
+        let mut final_args = vec!["rc.verbose=new-id".to_string(), "add".to_string()];
+        final_args.extend(args.iter().cloned());
+
+        final_args
+    }
+    fn is_verbose(command: &str) -> bool {
+        matches!(command, "done" | "delete")
+    }
+    /// Run a mutation answering "all" to bulk confirmations, since fypm confirms by itself.
+    fn mutate(&self, filter: &str, command: &str, args: &[String]) -> Result<(), FypmError> {
+        let final_args = Self::mutation_args(filter, command, args);
+
+        let spawn_error = |error: std::io::Error| FypmError {
+            message: format!("Failed to execute task: {}", error),
+            kind: FypmErrorKind::BackendError,
+        };
+
+        let mut child = self
+            .command()
+            .args(final_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+
+        // If there is no bulk confirmation, Taskwarrior just ignores it.
+        let _ = child.stdin.take().unwrap().write_all("all\n".as_bytes());
+
+        let output = Self::verify_output(child.wait_with_output().map_err(spawn_error)?)?;
+
+        if Self::is_verbose(command) {
+            print!("{}", String::from_utf8_lossy(&output.stdout));
+        }
+
+        Ok(())
+    }
+}
+
+impl TaskBackend for TaskwarriorBackend {
+    fn export(
+        &self,
+        filter: &str,
+        overrides: &[String],
+    ) -> Result<Vec<TaskWarriorExported>, FypmError> {
+        let mut args: Vec<&str> = overrides.iter().map(|item| item.as_str()).collect();
+        args.extend([filter, "export"]);
+
+        let json = self.read(&args)?;
+
+        serde_json::from_str::<Vec<TaskWarriorExported>>(&json).map_err(|error| FypmError {
+            message: format!("Failed to parse the exported tasks: {}", error),
+            kind: FypmErrorKind::ProblemWithStoredTask,
+        })
+    }
+    fn export_json(&self, filter: &str) -> Result<Vec<Value>, FypmError> {
+        let json = self.read(&[filter, "export"])?;
+
+        serde_json::from_str::<Vec<Value>>(&json).map_err(|error| FypmError {
+            message: format!("Failed to parse the exported tasks: {}", error),
+            kind: FypmErrorKind::ProblemWithStoredTask,
+        })
+    }
+    fn import(&self, tasks: &[Value]) -> Result<(), FypmError> {
+        let spawn_error = |error: std::io::Error| FypmError {
+            message: format!("Failed to execute task: {}", error),
+            kind: FypmErrorKind::BackendError,
+        };
+
+        let mut child = self
+            .command()
+            .args(Self::import_args())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(Value::from(tasks.to_vec()).to_string().as_bytes())
+            .map_err(spawn_error)?;
+
+        Self::verify_output(child.wait_with_output().map_err(spawn_error)?)?;
+
+        Ok(())
+    }
+    fn add(&self, args: &[String]) -> Result<String, FypmError> {
+        let final_args = Self::add_args(args);
+
+        let stdout = self.read(
+            &final_args
+                .iter()
+                .map(|arg| arg.as_str())
+                .collect::<Vec<&str>>(),
+        )?;
+
+        let id = stdout
+            .trim()
+            .replace("Created task ", "")
+            .replace('.', "")
+            .to_string();
+
+        let uuids = self.uuids(&id)?;
+
+        uuids.first().cloned().ok_or(FypmError {
+            message: format!("The created task ({}) was not found!", id),
+            kind: FypmErrorKind::NoTasksFound,
+        })
+    }
+    fn modify(&self, filter: &str, args: &[String]) -> Result<(), FypmError> {
+        self.mutate(filter, "modify", args)
+    }
+    fn start(&self, filter: &str) -> Result<(), FypmError> {
+        self.mutate(filter, "start", &[])
+    }
+    fn stop(&self, filter: &str) -> Result<(), FypmError> {
+        self.mutate(filter, "stop", &[])
+    }
+    fn done(&self, filter: &str) -> Result<(), FypmError> {
+        self.mutate(filter, "done", &[])
+    }
+    fn delete(&self, filter: &str) -> Result<(), FypmError> {
+        self.mutate(filter, "delete", &[])
+    }
+    fn annotate(&self, filter: &str, annotation: &str) -> Result<(), FypmError> {
+        self.mutate(filter, "annotate", &[annotation.to_string()])
+    }
+    fn count(&self, filter: &str) -> Result<u32, FypmError> {
+        let count = self.read(&["rc.verbose=0", filter, "count"])?;
+
+        if count.trim().is_empty() {
+            return Ok(0);
+        }
+
+        count.trim().parse::<u32>().map_err(|_| FypmError {
+            message: format!("Taskwarrior returned an invalid count: {}", count.trim()),
+            kind: FypmErrorKind::BackendError,
+        })
+    }
+    fn uuids(&self, filter: &str) -> Result<Vec<String>, FypmError> {
+        Ok(self
+            .read(&[filter, "_uuids"])?
+            .lines()
+            .map(|line| line.to_string())
+            .collect())
+    }
+    fn show_config(&self, key: &str) -> Result<String, FypmError> {
+        let configs = self.read(&["show", key])?;
+
+        let lines_with_config = configs
+            .split('\n')
+            .filter(|line| line.contains(key))
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        Ok(lines_with_config.replace(key, "").trim().to_string())
+    }
+    fn report(&self, filter: &str, report: &str) -> Result<(), FypmError> {
+        let status = self
+            .command()
+            .args([filter, report])
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .map_err(|error| FypmError {
+                message: format!("Failed to execute task: {}", error),
+                kind: FypmErrorKind::BackendError,
+            })?;
+
+        if !status.success() {
+            return Err(FypmError {
+                message: format!("Taskwarrior failed to show the {} report!", report),
+                kind: FypmErrorKind::BackendError,
+            });
+        }
+
+        Ok(())
+    }
+}
+//#endregion
@@ -0,0 +1,613 @@
+//#region           Crates
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+
+//#endregion
+//#region           Modules
+use crate::values::enums::TimewAction;
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::values::structs::TimeWarriorExported;
+
+//#endregion
+//#region           Constants
+pub const TIMEWARRIOR_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// Words that Timewarrior reads as dates or ranges in a filter, besides the ones starting with a
+/// digit (ex: `2024-W01`) or a colon (hints, ex: `:week`).
+const TIMEWARRIOR_DATE_WORDS: [&str; 57] = [
+    "now",
+    "today",
+    "yesterday",
+    "tomorrow",
+    "later",
+    "someday",
+    "sod",
+    "eod",
+    "sow",
+    "eow",
+    "soww",
+    "eoww",
+    "som",
+    "eom",
+    "soq",
+    "eoq",
+    "soy",
+    "eoy",
+    "sopd",
+    "eopd",
+    "sopw",
+    "eopw",
+    "sopm",
+    "eopm",
+    "sopq",
+    "eopq",
+    "sopy",
+    "eopy",
+    "sond",
+    "eond",
+    "sonw",
+    "eonw",
+    "sonm",
+    "eonm",
+    "sonq",
+    "eonq",
+    "sony",
+    "eony",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+    "mon",
+    "tue",
+    "wed",
+    "thu",
+    "fri",
+    "sat",
+    "sun",
+    "for",
+    "before",
+    "after",
+    "since",
+    "until",
+];
+//#endregion
+//#region           Traits
+/// Every operation that fypm performs against a Timewarrior database.
+///
+/// Mutations return the output that Timewarrior shows to the user.
+pub trait TimeBackend {
+    /// Export intervals that match the filter (ex: `["@1"]`, `["2024-01-01", "-", "2024-01-02"]`).
+    fn export(&self, filter: &[String]) -> Result<Vec<TimeWarriorExported>, FypmError>;
+    /// Move the start or the end of an interval, adjusting the neighbours.
+    fn modify(&self, action: &TimewAction, id: &str, time: &str) -> Result<String, FypmError>;
+    /// Track a closed interval with the received tags.
+    fn track(&self, start: &str, end: &str, tags: &[String]) -> Result<String, FypmError>;
+    /// Track a closed interval with the same tags of the interval `id`.
+    fn continue_interval(&self, id: &str, start: &str, end: &str) -> Result<String, FypmError>;
+    fn annotate(&self, ids: &str, annotation: &str) -> Result<String, FypmError>;
+    /// Open an interval now with the received tags.
+    fn start(&self, tags: &[String]) -> Result<String, FypmError>;
+    /// Close the open interval that has the received tags.
+    fn stop(&self, tags: &[String]) -> Result<String, FypmError>;
+    fn tag(&self, id: &str, tags: &[String]) -> Result<String, FypmError>;
+    fn untag(&self, id: &str, tags: &[String]) -> Result<String, FypmError>;
+}
+//#endregion
+//#region           Selection
+thread_local! {
+    static TIME_BACKEND: RefCell<Rc<dyn TimeBackend>> = RefCell::new(default_backend());
+}
+
+/// Read the data files directly if the database exists, falling back to the `timew` binary.
+fn default_backend() -> Rc<dyn TimeBackend> {
+    let database = database_path();
+
+    if database.join("data").is_dir() {
+        Rc::new(TimewarriorDataBackend::new(database))
+    } else {
+        Rc::new(TimewarriorBackend)
+    }
+}
+/// Get the Timewarrior database path, in the same order that Timewarrior looks for it.
+pub fn database_path() -> PathBuf {
+    if let Ok(path) = env::var("TIMEWARRIORDB") {
+        return PathBuf::from(path);
+    }
+
+    let home = dirs::home_dir().unwrap();
+    let legacy_path = home.join(".timewarrior");
+
+    if legacy_path.is_dir() {
+        legacy_path
+    } else {
+        dirs::data_dir()
+            .unwrap_or(home.join(".local/share"))
+            .join("timewarrior")
+    }
+}
+/// Get the backend used by the current thread.
+pub fn current() -> Rc<dyn TimeBackend> {
+    TIME_BACKEND.with(|backend| backend.borrow().clone())
+}
+/// Replace the backend used by the current thread.
+pub fn set_current(backend: Rc<dyn TimeBackend>) {
+    TIME_BACKEND.with(|current| *current.borrow_mut() = backend);
+}
+//#endregion
+//#region           Timewarrior
+/// Run `timew` with the received args. If `database` is specified, it is used as TIMEWARRIORDB.
+fn run_timew(database: Option<&Path>, args: &[String]) -> Result<String, FypmError> {
+    let mut command = Command::new("timew");
+    command.args(args);
+
+    if let Some(database) = database {
+        command.env("TIMEWARRIORDB", database);
+    }
+
+    let output: Output = command.output().map_err(|error| FypmError {
+        message: format!("Failed to execute timew: {}", error),
+        kind: FypmErrorKind::BackendError,
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(FypmError {
+            message: format!(
+                "Timewarrior failed to execute the command: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            kind: FypmErrorKind::BackendError,
+        })
+    }
+}
+/// Get the args that `timew` receives to move the start or the end of an interval.
+pub fn modify_args(action: &TimewAction, id: &str, time: &str) -> Vec<String> {
+    let action = match action {
+        TimewAction::Start => "start",
+        TimewAction::End => "end",
+    };
+
+    ["modify", action, id, time, ":adjust"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+}
+/// Get the args that `timew` receives to track a closed interval.
+pub fn track_args(start: &str, end: &str, tags: &[String]) -> Vec<String> {
+    let mut args = vec![
+        "track".to_string(),
+        start.to_string(),
+        "-".to_string(),
+        end.to_string(),
+    ];
+    args.extend(tags.iter().cloned());
+    args.push(":adjust".to_string());
+
+    args
+}
+/// Get the args that `timew` receives to track a closed interval with the tags of `id`.
+pub fn continue_args(id: &str, start: &str, end: &str) -> Vec<String> {
+    ["continue", id, start, "-", end, ":adjust"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+}
+/// Get the args that `timew` receives to annotate intervals.
+pub fn annotate_args(ids: &str, annotation: &str) -> Vec<String> {
+    let mut args = vec!["annotate".to_string()];
+    args.extend(ids.split_whitespace().map(|id| id.to_string()));
+    args.push(annotation.to_string());
+
+    args
+}
+/// Get the args that `timew` receives to open (`start`) or close (`stop`) an interval with the tags.
+pub fn interval_args(command: &str, tags: &[String]) -> Vec<String> {
+    let mut args = vec![command.to_string()];
+    args.extend(tags.iter().cloned());
+    args.push(":yes".to_string());
+
+    args
+}
+/// Get the args that `timew` receives to add (`tag`) or remove (`untag`) tags of an interval.
+pub fn tag_args(command: &str, id: &str, tags: &[String]) -> Vec<String> {
+    let mut args = vec![command.to_string(), id.to_string()];
+    args.extend(tags.iter().cloned());
+    args.push(":yes".to_string());
+
+    args
+}
+
+/// Parse the output of `timew export`.
+fn parse_export(json: &str) -> Result<Vec<TimeWarriorExported>, FypmError> {
+    serde_json::from_str::<Vec<TimeWarriorExported>>(json).map_err(|error| FypmError {
+        message: format!("Failed to parse the exported intervals: {}", error),
+        kind: FypmErrorKind::BackendError,
+    })
+}
+/// The backend that runs the `timew` binary for everything.
+pub struct TimewarriorBackend;
+
+impl TimeBackend for TimewarriorBackend {
+    fn export(&self, filter: &[String]) -> Result<Vec<TimeWarriorExported>, FypmError> {
+        let mut args = vec!["export".to_string()];
+        args.extend(filter.iter().cloned());
+
+        parse_export(&run_timew(None, &args)?)
+    }
+    fn modify(&self, action: &TimewAction, id: &str, time: &str) -> Result<String, FypmError> {
+        run_timew(None, &modify_args(action, id, time))
+    }
+    fn track(&self, start: &str, end: &str, tags: &[String]) -> Result<String, FypmError> {
+        run_timew(None, &track_args(start, end, tags))
+    }
+    fn continue_interval(&self, id: &str, start: &str, end: &str) -> Result<String, FypmError> {
+        run_timew(None, &continue_args(id, start, end))
+    }
+    fn annotate(&self, ids: &str, annotation: &str) -> Result<String, FypmError> {
+        run_timew(None, &annotate_args(ids, annotation))
+    }
+    fn start(&self, tags: &[String]) -> Result<String, FypmError> {
+        run_timew(None, &interval_args("start", tags))
+    }
+    fn stop(&self, tags: &[String]) -> Result<String, FypmError> {
+        run_timew(None, &interval_args("stop", tags))
+    }
+    fn tag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+        run_timew(None, &tag_args("tag", id, tags))
+    }
+    fn untag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+        run_timew(None, &tag_args("untag", id, tags))
+    }
+}
+//#endregion
+//#region           Data Files
+/// An interval read from the data files, with its dates already parsed.
+struct DataInterval {
+    interval: TimeWarriorExported,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+}
+/// The files that were read (with their modification time and size) and what they had.
+struct DataCache {
+    files: Vec<(PathBuf, Option<SystemTime>, u64)>,
+    intervals: Rc<Vec<DataInterval>>,
+}
+
+/// A filter of `timew export` that the data files can answer without `timew`.
+#[derive(Debug, Default, PartialEq)]
+pub struct DataFilter {
+    pub ids: Vec<i32>,
+    pub dates: Vec<DateTime<Utc>>,
+    pub tags: Vec<String>,
+}
+
+/// A backend that reads `<database>/data/*.data` directly, without spawning `timew`.
+///
+/// The intervals are kept in memory and only read again when a data file changes.
+/// Mutations, and exports with filters it doesn't understand (like `:week` or `yesterday`),
+/// are still delegated to `timew`, using this database as TIMEWARRIORDB.
+pub struct TimewarriorDataBackend {
+    database: PathBuf,
+    cache: RefCell<Option<DataCache>>,
+}
+
+impl TimewarriorDataBackend {
+    pub fn new(database: PathBuf) -> Self {
+        Self {
+            database,
+            cache: RefCell::new(None),
+        }
+    }
+
+    fn read_error(error: std::io::Error) -> FypmError {
+        FypmError {
+            message: format!("Failed to read the Timewarrior data: {}", error),
+            kind: FypmErrorKind::BackendError,
+        }
+    }
+    /// The `YYYY-MM.data` files, sorted, with their modification time and size.
+    fn data_files(&self) -> Result<Vec<(PathBuf, Option<SystemTime>, u64)>, FypmError> {
+        let file_name_regex = Regex::new(r"^\d{4}-\d{2}\.data$").unwrap();
+
+        let mut files = fs::read_dir(self.database.join("data"))
+            .map_err(Self::read_error)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| file_name_regex.is_match(name))
+            })
+            .map(|entry| {
+                let metadata = entry.metadata().ok();
+
+                (
+                    entry.path(),
+                    metadata
+                        .as_ref()
+                        .and_then(|metadata| metadata.modified().ok()),
+                    metadata.map(|metadata| metadata.len()).unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+
+        Ok(files)
+    }
+    /// Get the intervals of the cache, reading the files again if any of them changed.
+    fn load(&self) -> Result<Rc<Vec<DataInterval>>, FypmError> {
+        let files = self.data_files()?;
+
+        if let Some(cache) = self.cache.borrow().as_ref() {
+            if cache.files == files {
+                return Ok(cache.intervals.clone());
+            }
+        }
+
+        let mut intervals = Vec::new();
+        for (file, _, _) in &files {
+            for line in fs::read_to_string(file).map_err(Self::read_error)?.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                intervals.push(Self::parse_line(line).ok_or(FypmError {
+                    message: format!("Invalid interval in {}: {}", file.display(), line),
+                    kind: FypmErrorKind::BackendError,
+                })?);
+            }
+        }
+
+        intervals.sort_by_key(|interval| interval.start);
+
+        // Like Timewarrior, @1 is the most recent interval.
+        let total = intervals.len() as i32;
+        for (index, interval) in intervals.iter_mut().enumerate() {
+            interval.interval.id = total - index as i32;
+        }
+
+        let intervals = Rc::new(intervals);
+        *self.cache.borrow_mut() = Some(DataCache {
+            files,
+            intervals: intervals.clone(),
+        });
+
+        Ok(intervals)
+    }
+    /// Run `timew` against this database, forgetting the cache (the files will change).
+    fn run(&self, args: &[String]) -> Result<String, FypmError> {
+        self.cache.borrow_mut().take();
+
+        run_timew(Some(&self.database), args)
+    }
+
+    /// Parse a line like `inc 20240101T080000Z - 20240101T090000Z # tag "other tag" # "annotation"`.
+    /// Returns `None` if the line or its dates are invalid.
+    fn parse_line(line: &str) -> Option<DataInterval> {
+        let words = Self::split_words(line.trim());
+        let mut words = words.iter();
+
+        if words.next()?.0 != "inc" {
+            return None;
+        }
+
+        let start = words.next()?.0.clone();
+        let mut end = None;
+        let mut tags = Vec::new();
+        let mut annotation = None;
+        let mut section = 0;
+
+        while let Some((word, quoted)) = words.next() {
+            match (section, word.as_str(), quoted) {
+                (0, "-", false) => end = Some(words.next()?.0.clone()),
+                (_, "#", false) => section += 1,
+                (1, _, _) => tags.push(word.clone()),
+                (2, _, _) => annotation = Some(word.clone()),
+                _ => return None,
+            }
+        }
+
+        let parse_stored_date = |value: &str| {
+            NaiveDateTime::parse_from_str(value, TIMEWARRIOR_DATE_FORMAT)
+                .ok()
+                .map(|date| Utc.from_utc_datetime(&date))
+        };
+
+        Some(DataInterval {
+            start: parse_stored_date(&start)?,
+            end: match &end {
+                Some(end) => Some(parse_stored_date(end)?),
+                None => None,
+            },
+            interval: TimeWarriorExported {
+                id: 0,
+                start,
+                end,
+                tags: if tags.is_empty() { None } else { Some(tags) },
+                annotation,
+            },
+        })
+    }
+    /// Split by whitespace, keeping quoted words together. Returns the word and if it was quoted.
+    fn split_words(line: &str) -> Vec<(String, bool)> {
+        let mut words = Vec::new();
+        let mut current = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut chars = line.chars();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '\\' if in_quotes => {
+                    if let Some(escaped) = chars.next() {
+                        current.push(escaped);
+                    }
+                }
+                '"' => {
+                    in_quotes = !in_quotes;
+                    quoted = true;
+                }
+                c if c.is_whitespace() && !in_quotes => {
+                    if !current.is_empty() || quoted {
+                        words.push((std::mem::take(&mut current), quoted));
+                    }
+                    quoted = false;
+                }
+                c => current.push(c),
+            }
+        }
+        if !current.is_empty() || quoted {
+            words.push((current, quoted));
+        }
+
+        words
+    }
+    /// Split a filter in ids, dates and tags. Returns `None` if it has hints or dates that only
+    /// Timewarrior understands.
+    pub fn parse_filter(filter: &[String]) -> Result<Option<DataFilter>, FypmError> {
+        let mut data_filter = DataFilter::default();
+
+        for word in filter
+            .iter()
+            .filter(|word| !matches!(word.as_str(), "-" | "from" | "to"))
+        {
+            if let Some(id) = word.strip_prefix('@') {
+                data_filter
+                    .ids
+                    .push(id.parse::<i32>().map_err(|_| FypmError {
+                        message: format!("Invalid Timewarrior id: {}", word),
+                        kind: FypmErrorKind::InvalidInput,
+                    })?);
+            } else if let Some(date) = Self::parse_date(word) {
+                data_filter.dates.push(date);
+            } else if word.starts_with(|char: char| char == ':' || char.is_ascii_digit())
+                || TIMEWARRIOR_DATE_WORDS.contains(&word.to_lowercase().as_str())
+            {
+                return Ok(None);
+            } else {
+                data_filter.tags.push(word.clone());
+            }
+        }
+
+        Ok(Some(data_filter))
+    }
+    fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+        let local = |date: NaiveDateTime| {
+            Local
+                .from_local_datetime(&date)
+                .earliest()
+                .map(|date| date.with_timezone(&Utc))
+        };
+
+        match value {
+            "now" => return Some(Utc::now()),
+            "today" => return local(Local::now().date_naive().and_time(NaiveTime::MIN)),
+            _ => {}
+        }
+
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, TIMEWARRIOR_DATE_FORMAT) {
+            return Some(Utc.from_utc_datetime(&date));
+        }
+        if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+            return Some(date.with_timezone(&Utc));
+        }
+        for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+            if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+                return local(date);
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return local(date.and_time(NaiveTime::MIN));
+        }
+
+        None
+    }
+}
+
+impl TimeBackend for TimewarriorDataBackend {
+    fn export(&self, filter: &[String]) -> Result<Vec<TimeWarriorExported>, FypmError> {
+        let Some(DataFilter { ids, dates, tags }) = Self::parse_filter(filter)? else {
+            let mut args = vec!["export".to_string()];
+            args.extend(filter.iter().cloned());
+
+            return parse_export(&run_timew(Some(&self.database), &args)?);
+        };
+
+        let range_start = dates.first().cloned();
+        let range_end = dates.get(1).cloned();
+
+        let now = Utc::now();
+        let mut filtered = self
+            .load()?
+            .iter()
+            .filter(
+                |DataInterval {
+                     interval,
+                     start,
+                     end,
+                 }| {
+                    if !ids.is_empty() {
+                        return ids.contains(&interval.id);
+                    }
+
+                    let end = end.unwrap_or(now);
+
+                    let in_range = range_start.is_none_or(|range_start| end > range_start)
+                        && range_end.is_none_or(|range_end| *start < range_end);
+                    let has_tags = tags.iter().all(|tag| {
+                        interval
+                            .tags
+                            .as_ref()
+                            .is_some_and(|interval_tags| interval_tags.contains(tag))
+                    });
+
+                    in_range && has_tags
+                },
+            )
+            .map(|data_interval| data_interval.interval.clone())
+            .collect::<Vec<TimeWarriorExported>>();
+
+        if !ids.is_empty() {
+            // Like `timew export @2 @1`, the order is the order of the ids.
+            filtered.sort_by_key(|interval| ids.iter().position(|id| *id == interval.id));
+        }
+
+        Ok(filtered)
+    }
+    fn modify(&self, action: &TimewAction, id: &str, time: &str) -> Result<String, FypmError> {
+        self.run(&modify_args(action, id, time))
+    }
+    fn track(&self, start: &str, end: &str, tags: &[String]) -> Result<String, FypmError> {
+        self.run(&track_args(start, end, tags))
+    }
+    fn continue_interval(&self, id: &str, start: &str, end: &str) -> Result<String, FypmError> {
+        self.run(&continue_args(id, start, end))
+    }
+    fn annotate(&self, ids: &str, annotation: &str) -> Result<String, FypmError> {
+        self.run(&annotate_args(ids, annotation))
+    }
+    fn start(&self, tags: &[String]) -> Result<String, FypmError> {
+        self.run(&interval_args("start", tags))
+    }
+    fn stop(&self, tags: &[String]) -> Result<String, FypmError> {
+        self.run(&interval_args("stop", tags))
+    }
+    fn tag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+        self.run(&tag_args("tag", id, tags))
+    }
+    fn untag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+        self.run(&tag_args("untag", id, tags))
+    }
+}
+//#endregion
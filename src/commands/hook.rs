@@ -0,0 +1,124 @@
+//#region           Crates
+use std::io::{self, BufRead};
+
+use chrono::Local;
+
+//#endregion
+//#region           Modules
+use crate::backends;
+use crate::commands::timew::interval_tags;
+use crate::func::hooks::{self, HookOutput};
+use crate::handlers::config::ConfigHandler;
+use crate::values::enums::HookEvent;
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::values::structs::TaskWarriorExported;
+
+//#endregion
+//#region           Functions
+fn read_task(line: &str) -> Result<TaskWarriorExported, FypmError> {
+    serde_json::from_str(line).map_err(|error| FypmError {
+        message: format!(
+            "fypm: Failed to read the task received by the hook: {}",
+            error
+        ),
+        kind: FypmErrorKind::InvalidInput,
+    })
+}
+/// Print the result as Taskwarrior expects: the task JSON in one line, then the feedback.
+/// If the hook fails, only the message is printed and fypm exits with 1, so the command is rejected.
+fn respond(result: Result<HookOutput, FypmError>) -> Result<(), FypmError> {
+    match result {
+        Ok(output) => {
+            println!("{}", serde_json::to_string(&output.task).unwrap());
+            for message in output.feedback {
+                println!("{}", message);
+            }
+
+            Ok(())
+        }
+        Err(error) => {
+            println!("{}", error.message);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reflect a start, a stop or a change of the task in Timewarrior, as `ti-track` would tag it.
+/// Returns the feedback for Taskwarrior. Timewarrior errors don't reject the modification.
+pub fn bridge_timewarrior(old: &TaskWarriorExported, new: &TaskWarriorExported) -> Vec<String> {
+    let time_backend = backends::time::current();
+    let old_tags = interval_tags(old);
+    let new_tags = interval_tags(new);
+
+    let started = new.start.is_some() && old.start.is_none();
+    let stopped = old.start.is_some() && (new.start.is_none() || new.end.is_some());
+
+    let outputs = if started {
+        vec![time_backend.start(&new_tags)]
+    } else if stopped {
+        vec![time_backend.stop(&old_tags)]
+    } else if old.start.is_some() && new.start.is_some() {
+        let mut outputs = Vec::new();
+
+        if old_tags != new_tags {
+            outputs.push(time_backend.untag("@1", &old_tags));
+            outputs.push(time_backend.tag("@1", &new_tags));
+        }
+
+        let annotation = |task: &TaskWarriorExported| {
+            task.annotations
+                .as_ref()
+                .and_then(|annotations| annotations.first())
+                .map(|annotation| annotation.description.clone())
+        };
+        let new_annotation = annotation(new);
+        if new_annotation != annotation(old) {
+            if let Some(new_annotation) = new_annotation {
+                outputs.push(time_backend.annotate("@1", &new_annotation));
+            }
+        }
+
+        outputs
+    } else {
+        Vec::new()
+    };
+
+    outputs
+        .into_iter()
+        .filter_map(|output| match output {
+            Ok(stdout) if stdout.trim().is_empty() => None,
+            Ok(stdout) => Some(stdout.trim().to_string()),
+            Err(error) => Some(format!("fypm warn: {}", error.message)),
+        })
+        .collect()
+}
+
+pub fn run(event: &HookEvent) -> Result<(), FypmError> {
+    let mut lines = io::stdin().lock().lines().map_while(Result::ok);
+    let rules = ConfigHandler::get_rules();
+
+    match event {
+        HookEvent::OnAdd => {
+            let added = lines.next().unwrap_or_default();
+
+            respond(
+                rules.and_then(|rules| hooks::on_add(read_task(&added)?, &Local::now(), &rules)),
+            )
+        }
+        HookEvent::OnModify => {
+            let old = lines.next().unwrap_or_default();
+            let new = lines.next().unwrap_or_default();
+
+            respond(rules.and_then(|rules| {
+                let old = read_task(&old)?;
+                let mut output = hooks::on_modify(&old, read_task(&new)?, &rules)?;
+                output
+                    .feedback
+                    .extend(bridge_timewarrior(&old, &output.task));
+
+                Ok(output)
+            }))
+        }
+    }
+}
+//#endregion
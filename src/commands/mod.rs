@@ -2,21 +2,68 @@ pub mod init_day;
 pub mod task;
 pub mod timew;
 pub mod worktime;
-pub mod instance;
+pub mod hook;
 
 
+use colored::Colorize;
 use diesel::SqliteConnection;
 use diesel::Connection;
 
 use crate::handlers::aliases;
+use crate::handlers::config::ConfigHandler;
+use crate::handlers::database::DBHandler;
 use crate::handlers::filters::FiltersHandler;
+use crate::handlers::hooks::HooksHandler;
+use crate::handlers::instance::InstanceHandler;
+use crate::handlers::journal::JournalHandler;
+use crate::handlers::special_aliases::SpecialAliasesHandler;
+use crate::handlers::status::StatusHandler;
 use crate::handlers::worktime::WorktimeHandler;
 use crate::values::enums;
-use crate::values::enums::AliasActions;
+use crate::values::enums::{AliasActions, ControlActions, InstanceActions, TaAliasActions};
+use crate::values::enums::HooksActions;
 use crate::values::enums::{Commands, TimewAction};
-use crate::values::err::FypmError;
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::func::parser;
 use crate::{func, DATABASE_URL};
 
+/// Resolve the special aliases and the saved filters of an optional filter argument.
+fn resolve_option(filter: &Option<String>) -> Result<Option<String>, FypmError> {
+    filter
+        .as_ref()
+        .map(|filter| parser::match_special_aliases(filter))
+        .transpose()
+}
+
+/// Before `ta-alias`, `alias add <filter>` and `alias change <filter> [alias]` managed the ALIAS
+/// of the tasks. They still work, with a warning, and return the resolved filter and the alias.
+fn deprecated_alias_args(
+    action: &str,
+    args: &[String],
+) -> Result<(String, Option<String>), FypmError> {
+    let (filter, alias) = match args {
+        [filter] => (filter, None),
+        [filter, alias] => (filter, Some(alias.clone())),
+        _ => {
+            return Err(FypmError {
+                message: format!("Usage: ta-alias {} <filter> [alias]", action),
+                kind: FypmErrorKind::InvalidInput,
+            })
+        }
+    };
+
+    eprintln!(
+        "{}",
+        format!(
+            "`fypm alias {} <filter>` is deprecated! Use `fypm ta-alias {} <filter>` instead.",
+            action, action
+        )
+        .yellow()
+    );
+
+    Ok((parser::match_special_aliases(filter)?, alias))
+}
+
 pub fn matching(command: &Commands) -> Result<(), FypmError> {
     match command {
         //#region               Misc
@@ -25,36 +72,115 @@ pub fn matching(command: &Commands) -> Result<(), FypmError> {
         //#region               Systems
         Commands::InitDay => todo!(),
 
+        Commands::Undo {
+            quantity,
+            skip_confirmation,
+        } => JournalHandler::undo(
+            &mut SqliteConnection::establish(&DBHandler::instance_url()).unwrap(),
+            quantity,
+            skip_confirmation,
+        ),
+        Commands::History { limit } => JournalHandler::history(
+            &mut SqliteConnection::establish(&DBHandler::instance_url()).unwrap(),
+            limit,
+        ),
+
+        Commands::Status { format, write } => StatusHandler::status(format, write),
+
         Commands::Verify { script } => func::matchs::match_verify_script(script),
 
-        Commands::Alias { action, filter } => {
+        Commands::Hook { event } => hook::run(event),
+        Commands::Control { action, filter } => match (action, filter) {
+            (ControlActions::Set, Some(filter)) => {
+                task::update::set_control_task(&parser::match_task_aliases(filter)?)
+            }
+            (ControlActions::Set, None) => Err(FypmError {
+                message: "You must write a filter to the control task!".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            }),
+            (ControlActions::Show, _) => task::update::show_control_task(),
+        },
+        Commands::Hooks {
+            action,
+            skip_confirmation,
+        } => match action {
+            HooksActions::Install => HooksHandler::install(skip_confirmation),
+            HooksActions::Status => HooksHandler::status(),
+            HooksActions::Uninstall => HooksHandler::uninstall(),
+        },
+
+        Commands::Alias {
+            action,
+            args,
+            description,
+            output,
+        } => {
+            let conn = &mut SqliteConnection::establish(&DBHandler::instance_url()).unwrap();
+
             match action {
-                AliasActions::Add => aliases::AliasesHandler::add(filter),
+                AliasActions::Add if args.len() == 1 => {
+                    let (filter, alias) = deprecated_alias_args("add", args)?;
+                    aliases::AliasesHandler::add(&filter, &alias)
+                }
+                AliasActions::Add => SpecialAliasesHandler::add(conn, args, description),
+                AliasActions::Remove => SpecialAliasesHandler::remove(conn, args),
+                AliasActions::List => SpecialAliasesHandler::list(conn, &output.format()),
+                AliasActions::Rename => SpecialAliasesHandler::rename(conn, args),
                 AliasActions::Change => {
-                    //aliases::AliasesHandler::change(filter)
-                    todo!()
+                    let (filter, alias) = deprecated_alias_args("change", args)?;
+                    aliases::AliasesHandler::change(&filter, &alias)
                 }
             }
         }
 
-        Commands::Filter { action } => {
+        Commands::Filter {
+            action,
+            args,
+            description,
+            name,
+            on_conflict,
+            output,
+        } => {
             let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
 
             match action {
-                enums::FilterActions::Add => FiltersHandler::add(conn),
-                enums::FilterActions::List => FiltersHandler::list(conn),
-                enums::FilterActions::Remove => FiltersHandler::remove(conn),
-                enums::FilterActions::Edit => FiltersHandler::edit(conn),
+                enums::FilterActions::Add => FiltersHandler::add(conn, args, description),
+                enums::FilterActions::List => FiltersHandler::list(conn, &output.format()),
+                enums::FilterActions::Remove => FiltersHandler::remove(conn, args),
+                enums::FilterActions::Edit => FiltersHandler::edit(conn, args, name, description),
+                enums::FilterActions::Run => FiltersHandler::run(conn, args),
+                enums::FilterActions::Export => FiltersHandler::export_to(conn, args),
+                enums::FilterActions::Import => {
+                    FiltersHandler::import_from(conn, args, on_conflict)
+                }
             }
         },
 
-        Commands::WtAdd { worktime_name } => {
+        Commands::WtAdd {
+            worktime_name,
+            fields,
+        } => {
             WorktimeHandler::add(
                 &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap(),
                 worktime_name,
+                fields,
             )?;
 
-            Ok(())
+            ConfigHandler::handle_config()
+        }
+        Commands::WtEdit {
+            worktime_name,
+            name,
+            fields,
+        } => {
+            WorktimeHandler::edit(
+                &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap(),
+                worktime_name,
+                name,
+                fields,
+            )?;
+
+            ConfigHandler::handle_config()
         }
         Commands::WtRemove { worktime_name } => {
             WorktimeHandler::remove(
@@ -62,27 +188,62 @@ pub fn matching(command: &Commands) -> Result<(), FypmError> {
                 worktime_name,
             )?;
 
-            Ok(())
+            ConfigHandler::handle_config()
         }
-        Commands::WtLs => {
+        Commands::WtLs { output } => {
             WorktimeHandler::list(
                 &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap(),
+                &output.format(),
             )?;
 
             Ok(())
         }
-        Commands::WtApply { worktime_name } => {
-            worktime::apply(worktime_name)?;
+        Commands::WtApply { worktime_name } => match worktime_name {
+            Some(worktime_name) => worktime::apply(worktime_name),
+            None => worktime::apply_current(),
+        },
+        Commands::WtCurrent { output } => worktime::current(&output.format()),
+        Commands::WtReport { date_args, output } => worktime::report(date_args, &output.format()),
+        Commands::WtExport { path } => worktime::export(path),
+        Commands::WtImport {
+            path,
+            on_conflict,
+            allow_overlap,
+        } => {
+            worktime::import(path, on_conflict, allow_overlap)?;
 
-            Ok(())
+            ConfigHandler::handle_config()
         }
+        Commands::WtSchedule {
+            action,
+            args,
+            description,
+            output,
+        } => worktime::schedule(action, args, description, &output.format()),
+        Commands::WtDaemon { foreground, stop } => worktime::daemon(foreground, stop),
+
+        Commands::Instance {
+            action,
+            args,
+            name,
+            fields,
+            output,
+        } => {
+            let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
 
-        Commands::Instance { action, actionargs } => instance::match_action(action, actionargs),
+            match action {
+                InstanceActions::Add => InstanceHandler::add(conn, args, fields),
+                InstanceActions::List => InstanceHandler::list(conn, &output.format()),
+                InstanceActions::Remove => InstanceHandler::remove(conn, args),
+                InstanceActions::Edit => InstanceHandler::edit(conn, args, name, fields),
+                InstanceActions::Switch => InstanceHandler::switch(conn, args),
+            }
+        }
         //#endregion
         //#region               Task Subcommands
-        Commands::TaInfo {
-            filter,
-        } => task::list::info(filter),
+        Commands::TaInfo { filter, output } => {
+            task::list::info(&parser::match_special_aliases(filter)?, &output.format())
+        }
 
         Commands::TaAdd {
             description,
@@ -166,22 +327,29 @@ pub fn matching(command: &Commands) -> Result<(), FypmError> {
             Ok(())
         }
         Commands::TaLsMotAndSub { modifier, filter } => {
-            task::list::mother_and_subtasks(modifier, filter)?;
+            let filter = filter
+                .iter()
+                .map(|filter| parser::match_special_aliases(filter))
+                .collect::<Result<Vec<String>, FypmError>>()?;
+
+            task::list::mother_and_subtasks(modifier, &filter)?;
 
             Ok(())
         }
-        Commands::TaLsScore { date_args } => {
+        Commands::TaLsScore { date_args, output } => {
             if let Some(date_args) = date_args {
-                task::list::completion_score(date_args).unwrap();
+                task::list::completion_score(date_args, &output.format())?;
             } else {
-                task::list::completion_score(&vec!["-w".to_string()]).unwrap();
+                task::list::completion_score(&vec!["-w".to_string()], &output.format())?;
             }
 
             Ok(())
         }
 
         Commands::TaStart { filter } => task::update::start(filter),
-        Commands::TaStop { filter } => task::update::stop(filter, true),
+        Commands::TaStop { filter } => task::update::stop(&resolve_option(filter)?, true),
+        Commands::TaSwitch => task::update::start("last"),
+        Commands::TaRecent { limit, output } => task::update::recent(limit, &output.format()),
         Commands::TaDone {
             tasks_to_done,
             tastart_filter,
@@ -190,44 +358,95 @@ pub fn matching(command: &Commands) -> Result<(), FypmError> {
             not_necessary,
             delegated,
         } => task::update::done(
-            tasks_to_done,
-            tastart_filter,
+            &resolve_option(tasks_to_done)?,
+            &resolve_option(tastart_filter)?,
             annotation,
             skip_confirmation,
             not_necessary,
             delegated,
         ),
         Commands::TaAnnotate { filter, annotation } => {
-            func::action::annotate("task", filter, annotation, false)
+            func::action::annotate(
+                "task",
+                &parser::match_special_aliases(filter)?,
+                annotation,
+                false,
+            )
         }
         Commands::TaAbandon {
             tag,
             filter,
             annotation,
             annotation_filter,
-        } => task::update::abandon(tag, filter, annotation, annotation_filter),
-        Commands::TaStatistic { name, no_parents } => task::list::statistic(name, no_parents),
+        } => task::update::abandon(
+            tag,
+            &parser::match_special_aliases(filter)?,
+            annotation,
+            &resolve_option(annotation_filter)?,
+        ),
+        Commands::TaStatistic {
+            name,
+            no_parents,
+            output,
+        } => task::list::statistic(name, no_parents, &output.format()),
         Commands::TaSchedule {
             filter,
             alarm_date,
             due_date,
             worktime,
-        } => task::update::schedule(filter, alarm_date, due_date, worktime),
+        } => task::update::schedule(
+            &parser::match_special_aliases(filter)?,
+            alarm_date,
+            due_date,
+            worktime,
+        ),
         Commands::TaUnschedule {
             filter,
             no_alarm,
             no_due,
             no_worktime,
-        } => task::update::unschedule(filter, no_alarm, no_due, no_worktime),
-        Commands::TaUnd { filter, unarchive } => task::update::und(filter, unarchive),
+        } => task::update::unschedule(
+            &parser::match_special_aliases(filter)?,
+            no_alarm,
+            no_due,
+            no_worktime,
+        ),
+        Commands::TaUnd { filter, unarchive } => {
+            task::update::und(&parser::match_special_aliases(filter)?, unarchive)
+        }
         Commands::TaRecurTime { filter, new_time } => {
-            task::update::recur_time(filter, new_time)
+            task::update::recur_time(&parser::match_special_aliases(filter)?, new_time)
+        }
+        Commands::TaAlias {
+            action,
+            filter,
+            alias,
+        } => {
+            let required = |filter: &Option<String>| {
+                resolve_option(filter)?.ok_or(FypmError {
+                    message: "You must write a filter to the task!".to_string(),
+                    kind: FypmErrorKind::InvalidInput,
+                })
+            };
+
+            match action {
+                TaAliasActions::Add => aliases::AliasesHandler::add(&required(filter)?, alias),
+                TaAliasActions::Change => {
+                    aliases::AliasesHandler::change(&required(filter)?, alias)
+                }
+                TaAliasActions::Remove => aliases::AliasesHandler::remove(&required(filter)?),
+                TaAliasActions::Bulk => aliases::AliasesHandler::bulk(),
+            }
         }
         Commands::TaProject { action, arg } => task::task_project(action, arg),
         //#endregion
         //#region               Timew Subcommands
-        Commands::TiLs { initial_date, final_date } => {
-            timew::list(initial_date, final_date)?;
+        Commands::TiLs {
+            initial_date,
+            final_date,
+            output,
+        } => {
+            timew::list(initial_date, final_date, &output.format())?;
 
             Ok(())
         }
@@ -1,10 +1,8 @@
-use std::process::Command;
-use std::str;
-
 use chrono::{DateTime, Datelike, Local};
 use dialoguer::Confirm;
 
 use crate::{
+    backends,
     utils::get,
     values::{
         constants::DEFAULT_GET_JSON_OPTIONS,
@@ -47,8 +45,6 @@ pub fn new(
     }
 
     let mut args = vec![
-        "rc.verbose=new-id".to_string(),
-        "add".to_string(),
         description.to_string(),
         format!("project:{}", project),
         format!("STYLE:{}", style),
@@ -59,33 +55,9 @@ pub fn new(
         args.extend(other_args.clone());
     }
 
-    let execute = Command::new("task").args(args).output();
-
-    let id: String;
-    if let Ok(output) = execute {
-        if output.status.success() {
-            let stdout = str::from_utf8(&output.stdout).unwrap();
-
-            id = stdout
-                .trim()
-                .replace("Created task ", "")
-                .replace(".", "")
-                .to_string();
-        } else {
-            panic!(
-                "An error occurred trying to create a task: {}",
-                str::from_utf8(&output.stderr).unwrap()
-            );
-        }
-    } else {
-        let error = execute.unwrap_err();
-
-        panic!("An error occurred trying to create a task: {}", error);
-    }
-
-    let uuid = get::get_uuids_by_filter(&id, DEFAULT_GET_JSON_OPTIONS)?[0].clone();
+    let uuid = backends::task::current().add(&args)?;
 
-    println!("Created task with id \"{}\"! ({})", id, uuid);
+    println!("Created task \"{}\"! ({})", description, uuid);
 
     Ok(uuid)
 }
@@ -180,8 +152,6 @@ pub fn subtask(
         }
 
         let mut new_seq_subtask_args = vec![
-            subtask.clone(),
-            "modify".to_string(),
             "+SUBTASK".to_string(),
             "+Sequence".to_string(),
             format!("+{}", seq_id),
@@ -191,14 +161,8 @@ pub fn subtask(
             Ok(last_seq_subtask_vec) => {
                 let last_seq_subtask = last_seq_subtask_vec.get(0).unwrap();
 
-                Command::new("task")
-                    .args([
-                        &last_seq_subtask.uuid,
-                        &"modify".to_string(),
-                        &format!("SEQ_NEXT:{}", subtask),
-                    ])
-                    .output()
-                    .unwrap();
+                backends::task::current()
+                    .modify(&last_seq_subtask.uuid, &[format!("SEQ_NEXT:{}", subtask)])?;
 
                 new_seq_subtask_args.push(format!("SEQ_PREVIOUS:{}", last_seq_subtask.uuid));
             }
@@ -208,10 +172,7 @@ pub fn subtask(
             },
         }
 
-        Command::new("task")
-            .args(new_seq_subtask_args)
-            .output()
-            .unwrap();
+        backends::task::current().modify(&subtask, &new_seq_subtask_args)?;
     } else {
         if other_args.len() == 1 {
             let get_subtask_uuid =
@@ -228,21 +189,19 @@ pub fn subtask(
 
     // Define mother task as a mother task and set the subtask to mother task
     {
-        Command::new("task")
-            .args([mother_task.as_str(), "modify", "STATE:Info", "+MOTHER"])
-            .output()
-            .unwrap();
+        backends::task::current().modify(
+            mother_task,
+            &["STATE:Info".to_string(), "+MOTHER".to_string()],
+        )?;
         println!("Mother task setted.");
 
-        Command::new("task")
-            .args([
-                &subtask,
-                &"modify".to_string(),
-                &format!("MOTHER:{}", mother_task_json.uuid),
-                &"+SUBTASK".to_string(),
-            ])
-            .output()
-            .unwrap();
+        backends::task::current().modify(
+            &subtask,
+            &[
+                format!("MOTHER:{}", mother_task_json.uuid),
+                "+SUBTASK".to_string(),
+            ],
+        )?;
     }
     println!(
         "Subtask added to its MOTHER '{}'!",
@@ -330,24 +289,12 @@ pub fn sequence(
             let current_task_uuid = subtask(&mother_task_uuid, &args, &true).unwrap();
 
             if let Some(last_season_id) = last_season_id {
-                Command::new("task")
-                    .args([
-                        last_season_id,
-                        &"modify".to_string(),
-                        &format!("SEQ_PREVIOUS:{}", current_task_uuid),
-                    ])
-                    .output()
-                    .unwrap();
+                backends::task::current()
+                    .modify(last_season_id, &[format!("SEQ_PREVIOUS:{}", current_task_uuid)])?;
             }
 
-            Command::new("task")
-                .args([
-                    mother_task_uuid,
-                    &"modify".to_string(),
-                    &format!("SEQ_CURRENT:{}", current_task_uuid),
-                ])
-                .output()
-                .unwrap();
+            backends::task::current()
+                .modify(mother_task_uuid, &[format!("SEQ_CURRENT:{}", current_task_uuid)])?;
 
             previous_task_uuid = current_task_uuid;
         } else {
@@ -357,22 +304,10 @@ pub fn sequence(
 
             let current_task_uuid = subtask(&mother_task_uuid, &args, &true).unwrap();
 
-            Command::new("task")
-                .args([
-                    &current_task_uuid,
-                    &"modify".to_string(),
-                    &format!("SEQ_PREVIOUS:{}", previous_task_uuid),
-                ])
-                .output()
-                .unwrap();
-            Command::new("task")
-                .args([
-                    previous_task_uuid,
-                    "modify".to_string(),
-                    format!("SEQ_NEXT:{}", &current_task_uuid),
-                ])
-                .output()
-                .unwrap();
+            backends::task::current()
+                .modify(&current_task_uuid, &[format!("SEQ_PREVIOUS:{}", previous_task_uuid)])?;
+            backends::task::current()
+                .modify(&previous_task_uuid, &[format!("SEQ_NEXT:{}", &current_task_uuid)])?;
 
             previous_task_uuid = current_task_uuid;
         }
@@ -5,17 +5,44 @@ use std::process::{Command, Stdio};
 
 use crate::values::constants::DEFAULT_GET_JSON_OPTIONS;
 use crate::values::err::FypmErrorKind;
-use crate::values::structs::TaskWarriorStatus;
+use crate::values::structs::{CompletionScore, TaskInfo};
 use crate::{
     func::list,
     handlers::date::NaiveDateIter,
-    utils::{extract, get, term},
-    values::{enums, err::FypmError},
+    utils::{extract, get, output, term},
+    values::{
+        enums::{self, OutputFormat},
+        err::FypmError,
+    },
 };
 
-pub fn info(filter: &String) -> Result<(), FypmError> {
+pub fn info(filter: &str, output_format: &OutputFormat) -> Result<(), FypmError> {
     let grid_separator_len = 1;
-    let task = get::json_by_filter(filter, DEFAULT_GET_JSON_OPTIONS)?;
+    let tasks = get::json_by_filter(filter, DEFAULT_GET_JSON_OPTIONS)?;
+
+    if tasks.is_empty() {
+        return Err(FypmError {
+            message: "No task was found with this filter!".to_string(),
+            kind: FypmErrorKind::NotEnoughTasks,
+        });
+    }
+
+    let task = TaskInfo {
+        id: tasks[0].id,
+        uuid: tasks[0].uuid.clone(),
+        description: tasks[0].description.clone(),
+        project: tasks[0].project.clone(),
+        style: tasks[0].style.clone(),
+        wt: tasks[0].wt.clone(),
+        r#type: tasks[0].r#type.clone(),
+        quadrant: tasks[0].quadrant.clone(),
+        effort: tasks[0].effort.clone(),
+        tags: tasks[0].tags.clone().unwrap_or_default(),
+    };
+
+    if output::print_record(output_format, &task)? {
+        return Ok(());
+    }
 
     let mut left_lines: Vec<String> = vec![];
     let mut right_lines: Vec<String> = vec![];
@@ -24,10 +51,10 @@ pub fn info(filter: &String) -> Result<(), FypmError> {
     let mut right_values = vec![];
 
     {
-        if task[0].id == 0 {
-            left_values.push(task[0].uuid.chars().take(8).collect::<String>());
+        if task.id == 0 {
+            left_values.push(task.uuid.chars().take(8).collect::<String>());
         } else {
-            left_values.push(task[0].id.to_string());
+            left_values.push(task.id.to_string());
         }
 
         left_values.extend(vec![
@@ -42,21 +69,16 @@ pub fn info(filter: &String) -> Result<(), FypmError> {
         ]);
 
         right_values.extend(vec![
-            task[0].description.clone(),
-            task[0].project.clone().unwrap_or("".to_string()),
-            task[0].style.clone().unwrap_or("".to_string()),
-            task[0].wt.clone(),
-            task[0].r#type.clone(),
-            task[0].quadrant.clone().unwrap_or("".to_string()),
-            task[0].effort.clone().unwrap_or("".to_string()),
+            task.description.clone(),
+            task.project.clone().unwrap_or("".to_string()),
+            task.style.as_ref().map(|style| style.to_string()).unwrap_or_default(),
+            task.wt.clone().unwrap_or_default(),
+            task.r#type.as_ref().map(|r#type| r#type.to_string()).unwrap_or_default(),
+            task.quadrant.as_ref().map(|quadrant| quadrant.to_string()).unwrap_or_default(),
+            task.effort.as_ref().map(|effort| effort.to_string()).unwrap_or_default(),
             "".to_string(),
+            task.tags.join(", "),
         ]);
-
-        if let Some(tags) = &task[0].tags {
-            right_values.push(tags.join(", "));
-        } else {
-            right_values.push("".to_string());
-        }
     }
 
     let left_side_len = left_values.iter().map(|s| s.chars().count()).max().unwrap();
@@ -114,13 +136,25 @@ pub fn info(filter: &String) -> Result<(), FypmError> {
     Ok(())
 }
 
-pub fn statistic(command: &enums::StatisticsCommands, no_parents: &bool) -> Result<(), FypmError> {
+pub fn statistic(
+    command: &enums::StatisticsCommands,
+    no_parents: &bool,
+    output_format: &OutputFormat,
+) -> Result<(), FypmError> {
     match command {
         enums::StatisticsCommands::Deleted => {
-            list::deleted_tasks(no_parents)?;
+            let statistic = list::deleted_tasks(no_parents)?;
+
+            if !output::print_record(output_format, &statistic)? {
+                list::print_deleted_tasks(&statistic);
+            }
         }
         enums::StatisticsCommands::Pending => {
-            list::pending_tasks(no_parents)?;
+            let statistic = list::pending_tasks(no_parents)?;
+
+            if !output::print_record(output_format, &statistic)? {
+                list::print_pending_tasks(&statistic);
+            }
         }
     }
 
@@ -237,134 +271,148 @@ pub fn mother_and_subtasks(modifier: &String, filter: &Vec<String>) -> Result<()
 
     Ok(())
 }
-pub fn completion_score(date_args: &Vec<String>) -> Result<(), FypmError> {
+/// Count the tasks due in each day of the period, with a "week" record after each sunday and after the last day.
+pub fn completion_score_records(date_args: &Vec<String>) -> Result<Vec<CompletionScore>, FypmError> {
     let initial_date: NaiveDate;
     let final_date: NaiveDate;
 
     [initial_date, final_date] = extract::date_period(date_args);
 
+    let mut records: Vec<CompletionScore> = Vec::new();
+
     let mut week_pending = 0;
     let mut week_completed = 0;
     let mut week_deleted = 0;
     let mut week_total = 0;
     for date in NaiveDateIter::new(initial_date, final_date) {
-        let initial_day = date.format("%Y-%m-%d").to_string();
-        let final_day = (date + Duration::days(1)).format("%Y-%m-%d").to_string();
+        let day_score = list::day_completion_score(&date)?;
 
-        let tasks_json = get::json_by_filter(format!("((due.after:{initial_day} or due:{initial_day}) and due.before:{final_day}) and +INSTANCE").as_str(), None)?;
+        week_pending += day_score.pending;
+        week_completed += day_score.completed;
+        week_deleted += day_score.deleted;
+        week_total += day_score.total;
 
-        let pending_count = tasks_json
-            .iter()
-            .filter(|task| task.status == TaskWarriorStatus::Pending)
-            .count();
-        week_pending += pending_count;
+        if day_score.total == 0 {
+            continue;
+        }
 
-        let completed_count = tasks_json
-            .iter()
-            .filter(|task| task.status == TaskWarriorStatus::Completed)
-            .count();
-        week_completed += completed_count;
+        let initial_day = day_score.date.clone();
+        records.push(day_score);
+
+        if date.weekday() == Weekday::Sun || date == final_date {
+            records.push(CompletionScore {
+                kind: "week".to_string(),
+                date: initial_day,
+                pending: week_pending,
+                completed: week_completed,
+                deleted: week_deleted,
+                total: week_total,
+            });
+
+            week_pending = 0;
+            week_completed = 0;
+            week_deleted = 0;
+            week_total = 0;
+        }
+    }
 
-        let deleted_count = tasks_json
-            .iter()
-            .filter(|task| task.status == TaskWarriorStatus::Deleted)
-            .count();
-        week_deleted += deleted_count;
+    Ok(records)
+}
+pub fn completion_score(
+    date_args: &Vec<String>,
+    output_format: &OutputFormat,
+) -> Result<(), FypmError> {
+    let records = completion_score_records(date_args)?;
 
-        let total_count = pending_count + completed_count + deleted_count;
-        week_total += total_count;
+    if output::print_records(output_format, &records)? {
+        return Ok(());
+    }
 
-        let no_pend_count = total_count - pending_count;
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
 
-        if total_count == 0 {
-            continue;
-        }
+    for record in records {
+        if record.kind == "week" {
+            term::print_full_divisory();
 
-        {
             println!(
                 "{}: {} {} {}",
-                initial_day.bold(),
-                pending_count.to_string().cyan(),
-                completed_count.to_string().bright_green(),
-                deleted_count.to_string().bright_red()
+                "Week Status".to_string().bold(),
+                record.pending.to_string().cyan(),
+                record.completed.to_string().bright_green(),
+                record.deleted.to_string().bright_red(),
             );
 
-            if pending_count > 0 {
+            if record.pending > 0 {
                 print!(
-                    "              - ({} / {}) -> {}%",
-                    no_pend_count.to_string().bright_black(),
-                    total_count.to_string().bright_black(),
-                    ((pending_count * 100) / total_count)
-                        .to_string()
-                        .bright_black(),
+                    "              - ({} / {}) ->",
+                    (record.total - record.pending).to_string().bright_black(),
+                    record.total.to_string().bright_black(),
                 );
             } else {
                 print!(
                     "              - ({}) ->",
-                    total_count.to_string().bright_black(),
+                    record.total.to_string().bright_black()
                 );
             }
 
             print!(
-                " {}% {}%",
-                ((completed_count * 100) / total_count)
+                " {}% {}%\n",
+                ((record.completed * 100) / record.total)
                     .to_string()
                     .bright_green(),
-                ((deleted_count * 100) / total_count)
+                ((record.deleted * 100) / record.total)
                     .to_string()
                     .bright_red()
             );
 
-            if date == Local::now().date_naive() {
-                print!(
-                    "                    {}",
-                    "<───── TODAY".bright_white().bold()
-                );
-            }
-
-            println!();
-            println!();
-
-            if date.weekday() == Weekday::Sun || date == final_date {
-                term::print_full_divisory();
+            term::print_full_divisory();
 
-                println!(
-                    "{}: {} {} {}",
-                    "Week Status".to_string().bold(),
-                    week_pending.to_string().cyan(),
-                    week_completed.to_string().bright_green(),
-                    week_deleted.to_string().bright_red(),
-                );
+            continue;
+        }
 
-                if week_pending > 0 {
-                    print!(
-                        "              - ({} / {}) ->",
-                        (week_total - week_pending).to_string().bright_black(),
-                        week_total.to_string().bright_black(),
-                    );
-                } else {
-                    print!(
-                        "              - ({}) ->",
-                        week_total.to_string().bright_black()
-                    );
-                }
+        println!(
+            "{}: {} {} {}",
+            record.date.bold(),
+            record.pending.to_string().cyan(),
+            record.completed.to_string().bright_green(),
+            record.deleted.to_string().bright_red()
+        );
 
-                print!(
-                    " {}% {}%\n",
-                    ((week_completed * 100) / week_total)
-                        .to_string()
-                        .bright_green(),
-                    ((week_deleted * 100) / week_total).to_string().bright_red()
-                );
+        if record.pending > 0 {
+            print!(
+                "              - ({} / {}) -> {}%",
+                (record.total - record.pending).to_string().bright_black(),
+                record.total.to_string().bright_black(),
+                ((record.pending * 100) / record.total)
+                    .to_string()
+                    .bright_black(),
+            );
+        } else {
+            print!(
+                "              - ({}) ->",
+                record.total.to_string().bright_black(),
+            );
+        }
 
-                week_pending = 0;
-                week_completed = 0;
-                week_deleted = 0;
-                week_total = 0;
+        print!(
+            " {}% {}%",
+            ((record.completed * 100) / record.total)
+                .to_string()
+                .bright_green(),
+            ((record.deleted * 100) / record.total)
+                .to_string()
+                .bright_red()
+        );
 
-                term::print_full_divisory();
-            }
+        if record.date == today {
+            print!(
+                "                    {}",
+                "<───── TODAY".bright_white().bold()
+            );
         }
+
+        println!();
+        println!();
     }
 
     Ok(())
@@ -1,23 +1,26 @@
-use std::io::Write;
-use std::{
-    fs,
-    process::{Command, Stdio},
-};
+#[cfg(feature = "fysm")]
+use std::process::Command;
 
-use chrono::NaiveTime;
+use chrono::{DateTime, Local, NaiveTime, Utc};
 use dialoguer::Input;
+use diesel::{Connection, SqliteConnection};
 
 use crate::values::structs::TaskWarriorStatus;
 use crate::{
+    backends::{self, dry_run},
     func::{
         action::{
             self, match_inforelat_and_sequence, verify_if_is_divisory, verify_if_wt_is_allday,
         },
-        command, dialog, parser,
+        dialog, parser,
+    },
+    handlers::{
+        config::ConfigHandler, database::DBHandler, special_aliases::SpecialAliasesHandler,
+        task_history::TaskHistoryHandler,
     },
     utils::get,
     values::{
-        constants::{CONTROL_TASK, DEFAULT_GET_JSON_OPTIONS, LAST_TASK_PATH},
+        constants::DEFAULT_GET_JSON_OPTIONS,
         enums,
         err::{FypmError, FypmErrorKind},
         structs::TaskWarriorExported,
@@ -35,19 +38,59 @@ pub fn stop(filter_option: &Option<String>, start_control_task: bool) -> Result<
         final_filter = active_tasks.uuid.to_string();
     }
 
-    Command::new("task")
-        .args([&final_filter, "stop"])
-        .output()
-        .unwrap();
+    backends::task::current().stop(&final_filter)?;
 
     if start_control_task {
-        start(&CONTROL_TASK.to_string())?;
+        self::start_control_task()?;
+    }
+
+    Ok(())
+}
+/// Start the control task (`fypm control set`), if there is one.
+pub fn start_control_task() -> Result<(), FypmError> {
+    match ConfigHandler::get_control_task()? {
+        Some(control_task) => start(&control_task),
+        None => {
+            eprintln!("There is no control task to start! Set one with `fypm control set <filter>`.");
+
+            Ok(())
+        }
     }
+}
+/// Save the task of the filter as the control task.
+pub fn set_control_task(filter: &str) -> Result<(), FypmError> {
+    let task = SpecialAliasesHandler::get_startable_task(filter)?;
+
+    ConfigHandler::set_setting("control.task", &task.uuid)?;
+    println!("`{}` is the control task now!", task.description);
 
     Ok(())
 }
-pub fn start(filter: &String) -> Result<(), FypmError> {
-    let mut filter = parser::match_special_aliases(filter);
+pub fn show_control_task() -> Result<(), FypmError> {
+    match ConfigHandler::get_control_task()? {
+        Some(control_task) => {
+            let task = get::json_by_filter(&control_task, DEFAULT_GET_JSON_OPTIONS)?.remove(0);
+
+            println!("{} - {}", task.uuid, task.description);
+        }
+        None => println!("There is no control task! Set one with `fypm control set <filter>`."),
+    }
+
+    Ok(())
+}
+/// List the previous tasks, without the active one.
+pub fn recent(limit: &usize, output_format: &enums::OutputFormat) -> Result<(), FypmError> {
+    let active_uuid = get::get_current_task_json().ok().map(|task| task.uuid);
+
+    TaskHistoryHandler::list(
+        &mut SqliteConnection::establish(&DBHandler::instance_url()).unwrap(),
+        limit,
+        active_uuid.as_deref(),
+        output_format,
+    )
+}
+pub fn start(filter: &str) -> Result<(), FypmError> {
+    let mut filter = parser::match_task_aliases(filter)?;
     let filter_json = if filter.starts_with("+ST_") {
         get::mother_json_by_sequence_id(&filter)?
     } else {
@@ -80,17 +123,23 @@ pub fn start(filter: &String) -> Result<(), FypmError> {
             }
         } else {
             let active_task_uuid = &active_tasks.unwrap().uuid;
-            fs::write(LAST_TASK_PATH, active_task_uuid.as_bytes()).unwrap();
 
             println!("Stopping active task with uuid: {}", active_task_uuid);
             stop(&Some(active_task_uuid.to_string()), false).unwrap();
         }
 
         println!("Starting task with uuid: {}", filter);
-        Command::new("task")
-            .args([filter.as_str(), "start"])
-            .output()
-            .unwrap();
+        backends::task::current().start(&filter)?;
+
+        // The task is already started, so a failure here only costs its place in `last`.
+        if !dry_run::is_enabled() {
+            if let Err(error) = TaskHistoryHandler::push(
+                &mut SqliteConnection::establish(&DBHandler::instance_url()).unwrap(),
+                &filter,
+            ) {
+                eprintln!("Failed to save the task in the history: {}", error);
+            }
+        }
 
         #[cfg(feature = "fysm")]
         {
@@ -110,7 +159,6 @@ pub fn done(
     not_necessary: &bool,
     delegated: &bool,
 ) -> Result<(), FypmError> {
-    let mut args = vec!["rc.confirmation=0", "rc.recurrence.confirmation=0"];
     let selected_tasks: Vec<TaskWarriorExported>;
 
     if let Some(filter) = filter {
@@ -123,7 +171,7 @@ pub fn done(
 
             for task in &task_json {
                 if task.uuid == current_task.uuid {
-                    start(&CONTROL_TASK.to_string())?;
+                    start_control_task()?;
                     break;
                 }
             }
@@ -136,7 +184,7 @@ pub fn done(
         if let Some(tastart_filter) = tastart_filter {
             start(tastart_filter)?;
         } else {
-            start(&CONTROL_TASK.to_string())?;
+            start_control_task()?;
         }
 
         selected_tasks = vec![current_task];
@@ -148,8 +196,6 @@ pub fn done(
         .collect::<Vec<&str>>()
         .join(" ");
 
-    args.extend([join_uuids.as_str()]);
-
     let confirmation: bool;
 
     if *skip_confirmation {
@@ -182,51 +228,14 @@ pub fn done(
                     kind: FypmErrorKind::InvalidInput,
                 });
             } else if tags.len() == 1 {
-                let mut tags_args = args.clone();
-
-                tags_args.push("modify");
-                tags_args.extend(tags);
-
-                let mut tag_binding = Command::new("task");
-                let tag_command = tag_binding
-                    .args(tags_args)
-                    .stdout(Stdio::inherit())
-                    .stderr(Stdio::inherit());
-
-                let mut tag_child = tag_command.stdin(Stdio::piped()).spawn().unwrap();
-
-                tag_child
-                    .stdin
-                    .take()
-                    .unwrap()
-                    .write_all("all\n".as_bytes())
-                    .unwrap();
-                tag_child.wait().unwrap();
+                backends::task::current().modify(
+                    &join_uuids,
+                    &tags.iter().map(|tag| tag.to_string()).collect::<Vec<String>>(),
+                )?;
             }
         }
-        let mut done_args = args.clone();
-
-        done_args.push("done");
-
-        let mut done_binding = Command::new("task");
-        let done_command = done_binding
-            .args(done_args)
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
-
-        if selected_tasks.len() > 2 {
-            let mut done_child = done_command.stdin(Stdio::piped()).spawn().unwrap();
-
-            done_child
-                .stdin
-                .take()
-                .unwrap()
-                .write_all("all\n".as_bytes())
-                .unwrap();
-            done_child.wait().unwrap();
-        } else {
-            done_command.output().unwrap();
-        }
+
+        backends::task::current().done(&join_uuids)?;
     } else {
         println!("Aborting...");
     }
@@ -246,18 +255,10 @@ pub fn abandon(
         panic!("You must specify an annotation when mark a task as NoControl or Abandoned!");
     }
     let tasks = get::json_by_filter(filter, None)?;
-    let tasks_count: usize = tasks.len();
     let confirmation = dialog::verify_selected_tasks(&tasks)?;
 
     if confirmation {
-        let mut modify_args = Vec::new();
-        modify_args.extend([
-            "rc.verbose=0".to_string(),
-            "rc.recurrence.confirmation=0".to_string(),
-            "rc.confirmation=0".to_string(),
-            filter.clone(),
-            "modify".to_string(),
-        ]);
+        let mut modify_args: Vec<String> = Vec::new();
 
         match tag {
             enums::TaAbandonTags::Archived => {
@@ -316,30 +317,8 @@ pub fn abandon(
             }
         }
 
-        let mut modify_binding = Command::new("task");
-        let modify_command = modify_binding.args(modify_args).stderr(Stdio::inherit());
-
-        let mut delete_binding = Command::new("task");
-        let delete_command = delete_binding
-            .args([
-                "rc.verbose=0",
-                "rc.confirmation=0",
-                "rc.recurrence.confirmation=0",
-                filter,
-                "delete",
-            ])
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
-
-        if tasks_count > 2 {
-            command::stdin_all(modify_command).unwrap();
-
-            command::stdin_all(delete_command).unwrap();
-        } else {
-            modify_command.output().unwrap();
-
-            delete_command.output().unwrap();
-        }
+        backends::task::current().modify(filter, &modify_args)?;
+        backends::task::current().delete(filter)?;
     } else {
         println!("Aborting...");
     }
@@ -353,18 +332,10 @@ pub fn schedule(
     worktime: &Option<String>,
 ) -> Result<(), FypmError> {
     let tasks = get::json_by_filter(filter, None)?;
-    let tasks_count: usize = tasks.len();
     let confirmation = dialog::verify_selected_tasks(&tasks)?;
 
     if confirmation {
-        let mut modify_args = Vec::new();
-        modify_args.extend([
-            "rc.verbose=0".to_string(),
-            "rc.recurrence.confirmation=0".to_string(),
-            "rc.confirmation=0".to_string(),
-            filter.clone(),
-            "modify".to_string(),
-        ]);
+        let mut modify_args: Vec<String> = Vec::new();
 
         if alarm_date != "cur" {
             modify_args.extend([format!("ALARM:{}", alarm_date)]);
@@ -379,25 +350,7 @@ pub fn schedule(
             modify_args.extend([format!("WT:{}", worktime)]);
         }
 
-        let mut modify_binding = Command::new("task");
-        let modify_command = modify_binding
-            .args(modify_args)
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
-
-        if tasks_count > 2 {
-            let mut modify_child = modify_command.stdin(Stdio::piped()).spawn().unwrap();
-
-            modify_child
-                .stdin
-                .take()
-                .unwrap()
-                .write_all("all\n".as_bytes())
-                .unwrap();
-            modify_child.wait().unwrap();
-        } else {
-            modify_command.output().unwrap();
-        }
+        backends::task::current().modify(filter, &modify_args)?;
     } else {
         println!("Aborting...");
     }
@@ -411,48 +364,22 @@ pub fn unschedule(
     no_worktime: &bool,
 ) -> Result<(), FypmError> {
     let tasks = get::json_by_filter(filter, None)?;
-    let tasks_count: usize = tasks.len();
     let confirmation = dialog::verify_selected_tasks(&tasks)?;
 
     if confirmation {
-        let mut modify_args = Vec::new();
-        modify_args.extend([
-            "rc.verbose=0",
-            "rc.recurrence.confirmation=0",
-            "rc.confirmation=0",
-            filter,
-            "modify",
-        ]);
+        let mut modify_args: Vec<String> = Vec::new();
 
         if !*no_alarm {
-            modify_args.extend(["ALARM:"]);
+            modify_args.extend(["ALARM:".to_string()]);
         }
         if !*no_due {
-            modify_args.extend(["due:"]);
+            modify_args.extend(["due:".to_string()]);
         }
         if !*no_worktime {
-            modify_args.extend(["WT:NonSched!"]);
+            modify_args.extend(["WT:NonSched!".to_string()]);
         }
 
-        let mut modify_binding = Command::new("task");
-        let modify_command = modify_binding
-            .args(modify_args)
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
-
-        if tasks_count > 2 {
-            let mut modify_child = modify_command.stdin(Stdio::piped()).spawn().unwrap();
-
-            modify_child
-                .stdin
-                .take()
-                .unwrap()
-                .write_all("all\n".as_bytes())
-                .unwrap();
-            modify_child.wait().unwrap();
-        } else {
-            modify_command.output().unwrap();
-        }
+        backends::task::current().modify(filter, &modify_args)?;
     } else {
         println!("Aborting...");
     }
@@ -471,31 +398,21 @@ pub fn und(filter: &String, unarchive: &bool) -> Result<(), FypmError> {
     let confirmation = dialog::verify_selected_tasks(&tasks)?;
 
     if confirmation {
-        let mut args = vec![
-            "rc.verbose=0",
-            "rc.confirmation=0",
-            "rc.recurrence.confirmation=0",
-            filter,
-            "modify",
-            "status:pending",
-        ];
-
         if *unarchive {
             action::unarchive(tasks)?;
 
             return Ok(());
-        } else {
-            args.extend(["-Failed", "-Abandoned", "-NoControl"]);
         }
 
-        let mut modify_binding = Command::new("task");
-        let modify_command = modify_binding.args(args).stderr(Stdio::inherit());
-
-        if tasks.len() > 2 {
-            command::stdin_all(modify_command).unwrap();
-        } else {
-            modify_command.output().unwrap();
-        }
+        backends::task::current().modify(
+            filter,
+            &[
+                "status:pending".to_string(),
+                "-Failed".to_string(),
+                "-Abandoned".to_string(),
+                "-NoControl".to_string(),
+            ],
+        )?;
     } else {
         println!("Aborting...");
     }
@@ -509,7 +426,7 @@ pub fn recur_time(filter: &String, new_time: &String) -> Result<(), FypmError> {
     let get_task = get::json_by_filter(filter, DEFAULT_GET_JSON_OPTIONS)?;
     let received_task = get_task.get(0).unwrap();
 
-    let old_parent_due: String;
+    let old_parent_due: DateTime<Utc>;
     let parent_task_uuid: &String;
     {
         let time = NaiveTime::parse_from_str(new_time, date_format);
@@ -525,10 +442,7 @@ pub fn recur_time(filter: &String, new_time: &String) -> Result<(), FypmError> {
     }
     {
         if received_task.status == TaskWarriorStatus::Recurring {
-            old_parent_due = parser::transform_dates_to_iso(
-                received_task.due.clone().expect(recur_without_due_msg),
-            )
-            .unwrap();
+            old_parent_due = received_task.due.expect(recur_without_due_msg);
 
             parent_task_uuid = &received_task.uuid;
         } else if let Some(get_parent_task_uuid) = &received_task.parent {
@@ -538,10 +452,7 @@ pub fn recur_time(filter: &String, new_time: &String) -> Result<(), FypmError> {
 
             parent_task_uuid = get_parent_task_uuid;
 
-            old_parent_due = parser::transform_dates_to_iso(
-                parent_task.due.clone().expect(recur_without_due_msg),
-            )
-            .unwrap();
+            old_parent_due = parent_task.due.expect(recur_without_due_msg);
         } else {
             return Err(FypmError {
                 message: "Selected task is not recurring!".to_string(),
@@ -550,57 +461,29 @@ pub fn recur_time(filter: &String, new_time: &String) -> Result<(), FypmError> {
         }
     }
 
-    if let Some((date, _)) = old_parent_due.split_once("T") {
-        let new_parent_due = format!("{}T{}", date, new_time);
-        let pending_instances = get::json_by_filter(
-            format!("+PENDING and +INSTANCE and parent:{}", parent_task_uuid).as_str(),
-            None,
-        );
-
-        Command::new("task")
-            .args(vec![
-                "rc.confirmation=0",
-                "rc.recurrence.confirmation=0",
-                parent_task_uuid,
-                "modify",
-                format!("due:{}", new_parent_due).as_str(),
-            ])
-            .stderr(Stdio::inherit())
-            .output()
-            .unwrap();
-
-        println!("Parent task updated! ({})", parent_task_uuid);
-
-        for (_, task) in pending_instances.unwrap().iter().enumerate() {
-            let new_instance_parsed_due = parser::transform_dates_to_iso(
-                task.due.clone().expect("An instance task needs a due!"),
-            )
-            .unwrap();
-
-            if let Some((date, _)) = new_instance_parsed_due.split_once("T") {
-                let new_instance_due = format!("{}T{}", date, new_time);
-
-                Command::new("task")
-                    .args(vec![
-                        "rc.confirmation=0",
-                        "rc.recurrence.confirmation=0",
-                        &task.uuid,
-                        "modify",
-                        format!("due:{}", new_instance_due).as_str(),
-                    ])
-                    .stderr(Stdio::inherit())
-                    .output()
-                    .unwrap();
+    let date = old_parent_due.with_timezone(&Local).date_naive();
+    let new_parent_due = format!("{}T{}", date, new_time);
+    let pending_instances = get::json_by_filter(
+        format!("+PENDING and +INSTANCE and parent:{}", parent_task_uuid).as_str(),
+        None,
+    );
 
-                println!("Instance task updated! ({})", &task.uuid);
-            }
-        }
+    backends::task::current().modify(parent_task_uuid, &[format!("due:{}", new_parent_due)])?;
 
-        Ok(())
-    } else {
-        Err(FypmError {
-            message: "The format of the due date is invalid!".to_string(),
-            kind: FypmErrorKind::InvalidInput,
-        })
+    println!("Parent task updated! ({})", parent_task_uuid);
+
+    for task in pending_instances.unwrap().iter() {
+        let date = task
+            .due
+            .expect("An instance task needs a due!")
+            .with_timezone(&Local)
+            .date_naive();
+        let new_instance_due = format!("{}T{}", date, new_time);
+
+        backends::task::current().modify(&task.uuid, &[format!("due:{}", new_instance_due)])?;
+
+        println!("Instance task updated! ({})", &task.uuid);
     }
+
+    Ok(())
 }
@@ -2,20 +2,27 @@
 use chrono::{Duration, NaiveDate};
 use colored::Colorize;
 use std::collections::HashMap;
-use std::process::{Command, Stdio};
-use std::str::{self, FromStr};
+use std::str::FromStr;
 use uuid::Uuid;
 
 //#endregion
 //#region           Modules
+use crate::backends;
 use crate::func::{date, parser};
 use crate::utils::get;
 use crate::values::constants::DEFAULT_GET_JSON_OPTIONS;
-use crate::values::enums::TimewAction;
+use crate::utils::output;
+use crate::values::enums::{OutputFormat, TimewAction};
 use crate::values::err::{FypmError, FypmErrorKind};
-use crate::values::structs::{TaskWarriorExported, TimeWarriorExported};
+use crate::values::structs::{TaskWarriorExported, TimeLogRecord, TimeWarriorExported};
 //#endregion
 //#region           Functions
+fn print_timew_output(output: Result<String, FypmError>) {
+    match output {
+        Ok(stdout) => println!("{}", stdout),
+        Err(error) => eprintln!("{}", error.message),
+    }
+}
 pub fn move_log(
     action: &TimewAction,
     manipulation_id: &String,
@@ -77,40 +84,18 @@ pub fn set_log(
         panic!("Hey!! Are you trying to use a taskwarrior id? Specify with \"@\"!");
     }
 
-    let mut action = String::new();
-    match received_action {
-        TimewAction::Start => {
-            action.push_str("start");
-        }
-        TimewAction::End => {
-            action.push_str("end");
-        }
-    }
     let mut time: String = received_time.to_string();
 
     if time.starts_with("@") {
         time = parser::match_special_timing_properties(received_time).unwrap();
     }
 
-    let execute = Command::new("timew")
-        .args(["modify", &action, received_id, &time, ":adjust"])
-        .output();
+    print_timew_output(backends::time::current().modify(received_action, received_id, &time));
 
-    match execute {
-        Ok(output) => {
-            if output.status.success() {
-                println!("{}", str::from_utf8(&output.stdout).unwrap());
-            } else {
-                eprintln!("{}", str::from_utf8(&output.stderr).unwrap());
-            }
-
-            Ok(())
-        }
-        Err(e) => panic!("Failed to execute timew command, error: {}", e),
-    }
+    Ok(())
 }
-pub fn track(received_id: &String, params: &Vec<String>) -> Result<(), FypmError> {
-    let id = parser::match_special_aliases(received_id);
+pub fn track(received_id: &str, params: &[String]) -> Result<(), FypmError> {
+    let id = parser::match_task_aliases(received_id)?;
 
     if params.len() % 2 != 0 {
         return Err(FypmError {
@@ -138,84 +123,66 @@ pub fn track(received_id: &String, params: &Vec<String>) -> Result<(), FypmError
         };
 
         if received_id.starts_with("@") {
-            let execute = Command::new("timew")
-                .args([
-                    &String::from("continue"),
-                    &received_id,
-                    &start_time,
-                    &String::from("-"),
-                    &end_time,
-                    &String::from(":adjust"),
-                ])
-                .output();
-
-            match execute {
-                Ok(output) => {
-                    if output.status.success() {
-                        println!("{}", str::from_utf8(&output.stdout).unwrap());
-                    } else {
-                        eprintln!("{}", str::from_utf8(&output.stderr).unwrap());
-                    }
-                }
-                Err(e) => eprintln!("Failed to execute timew command, error: {}", e),
-            }
+            print_timew_output(backends::time::current().continue_interval(
+                received_id,
+                &start_time,
+                &end_time,
+            ));
         } else {
-            let max_description_length = 25;
-
             let get_task_json = get::json_by_filter(&id, DEFAULT_GET_JSON_OPTIONS).unwrap();
             let task_json = get_task_json.get(0).unwrap();
 
-            let mut truncated_description = String::new();
-            if &task_json.description.len() > &25 {
-                truncated_description = format!(
-                    "{}...",
-                    &task_json.description[..max_description_length - 3]
-                )
-            } else {
-                truncated_description.push_str(&task_json.description);
-            }
-
-            let mut args = vec![
-                "track",
+            print_timew_output(backends::time::current().track(
                 &start_time,
-                "-",
                 &end_time,
-                &task_json.uuid,
-                &truncated_description,
-                &task_json.wt,
-                ":adjust",
-                &task_json.r#type,
-            ];
-
-            if let Some(style) = &task_json.style {
-                args.push(&style);
-            }
+                &interval_tags(task_json),
+            ));
+        }
+    }
 
-            if let Some(project) = &task_json.project {
-                args.push(&project);
-            }
+    Ok(())
+}
+/// Get the tags of the intervals of a task: uuid, description (truncated in 25 chars), WT, TYPE,
+/// STYLE, project and the task tags.
+pub fn interval_tags(task_json: &TaskWarriorExported) -> Vec<String> {
+    let max_description_length = 25;
+
+    let mut truncated_description = String::new();
+    if task_json.description.chars().count() > max_description_length {
+        truncated_description = format!(
+            "{}...",
+            task_json
+                .description
+                .chars()
+                .take(max_description_length - 3)
+                .collect::<String>()
+        )
+    } else {
+        truncated_description.push_str(&task_json.description);
+    }
 
-            // Add tags
-            if let Some(tags) = &task_json.tags {
-                args.extend(tags.iter().map(|tag| tag.as_str()));
-            }
+    let mut tags = vec![task_json.uuid.clone(), truncated_description];
 
-            let execute = Command::new("timew").args(&args).output();
+    if let Some(wt) = &task_json.wt {
+        tags.push(wt.clone());
+    }
+    if let Some(r#type) = &task_json.r#type {
+        tags.push(r#type.to_string());
+    }
+    if let Some(style) = &task_json.style {
+        tags.push(style.to_string());
+    }
 
-            match execute {
-                Ok(output) => {
-                    if output.status.success() {
-                        println!("{}", str::from_utf8(&output.stdout).unwrap());
-                    } else {
-                        eprintln!("{}", str::from_utf8(&output.stderr).unwrap());
-                    }
-                }
-                Err(e) => eprintln!("Failed to execute timew command, error: {}", e),
-            }
-        }
+    if let Some(project) = &task_json.project {
+        tags.push(project.clone());
     }
 
-    Ok(())
+    // Add tags
+    if let Some(task_tags) = &task_json.tags {
+        tags.extend(task_tags.iter().cloned());
+    }
+
+    tags
 }
 pub fn replace(
     received_original_id: &String,
@@ -230,7 +197,11 @@ pub fn replace(
 
     track(received_replacement_id, &vec![start_time, end_time])
 }
-pub fn list(initial_date: &String, final_date: &Option<String>) -> Result<(), FypmError> {
+pub fn list(
+    initial_date: &String,
+    final_date: &Option<String>,
+    output_format: &OutputFormat,
+) -> Result<(), FypmError> {
     let start: NaiveDate;
     let end: NaiveDate;
 
@@ -274,15 +245,36 @@ pub fn list(initial_date: &String, final_date: &Option<String>) -> Result<(), Fy
         .map(|task| (task.uuid.clone(), task.clone()))
         .collect::<HashMap<String, TaskWarriorExported>>();
 
-    for entry in timew_entries {
-        println!(
-            "{} - {}",
-            format!("@{}", entry.1.id).to_string().bold().truecolor(180, 0, 230),
-            tasks_map
-                .get(&entry.0)
+    let records = timew_entries
+        .into_iter()
+        .map(|(uuid, timew_entry)| TimeLogRecord {
+            id: timew_entry.id,
+            description: tasks_map
+                .get(&uuid)
                 .expect("There is a problem with the UUID!")
                 .description
+                .clone(),
+            uuid,
+            start: timew_entry.start,
+            end: timew_entry.end,
+            annotation: timew_entry.annotation,
+        })
+        .collect::<Vec<TimeLogRecord>>();
+
+    if output::print_records(output_format, &records)? {
+        return Ok(());
+    }
+
+    for record in records {
+        println!(
+            "{} - {}",
+            format!("@{}", record.id).to_string().bold().truecolor(180, 0, 230),
+            record.description
         );
+
+        if let Some(annotation) = &record.annotation {
+            println!("    {}", annotation.bright_black());
+        }
     }
 
     Ok(())
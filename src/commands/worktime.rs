@@ -1,78 +1,39 @@
 //#region           Crates
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeDelta};
+use colored::Colorize;
+use daemonize::Daemonize;
 use diesel::Connection;
 use diesel::SqliteConnection;
-use regex::Regex;
+use itertools::Itertools;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io::Error;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-
-use crate::handlers::worktime::WorktimeHandler;
+use std::thread;
+use uuid::Uuid;
+
+use crate::backends;
+use crate::backends::time::TIMEWARRIOR_DATE_FORMAT;
+use crate::db::models::Worktime;
+use crate::handlers::config::ConfigHandler;
+use crate::handlers::status::{format_elapsed, StatusHandler};
+use crate::handlers::worktime::{WorktimeHandler, OFF_WORKTIME};
+use crate::utils::{extract, get, output};
 use crate::values::err::FypmError;
 //#endregion
 //#region           Modules
+use crate::values::enums::{ImportConflict, OutputFormat, WtScheduleActions};
 use crate::values::err::FypmErrorKind;
-use crate::DATABASE_URL;
+use crate::values::structs::{TrackedInterval, WorktimesFile};
+use crate::{DATABASE_URL, DB_PATH};
 //#endregion
+//#region           Constants
+/// Longest time the daemon sleeps, so changes in the worktimes are noticed.
+const DAEMON_MAX_SLEEP: TimeDelta = TimeDelta::seconds(60);
 //#endregion
 //#region           Implementation
-fn write_values_on_temp_files(
-    current_wt: &String,
-    bg_poly_color: &String,
-    fg_poly_color: &String,
-) -> Result<(), Error> {
-    let path = Path::new("/var/tmp");
-    let last_wt_path = path.join(".last_work_time");
-
-    fs::write(path.join("current_work_time"), current_wt)?;
-    fs::write(path.join("current_polybar_b_wt_color"), bg_poly_color)?;
-    fs::write(path.join("current_polybar_f_wt_color"), fg_poly_color)?;
-
-    fs::write(last_wt_path, &current_wt)?;
-
-    Ok(())
-}
-
-fn update_filter(current_wt: &String, cfg_line: &str) -> Result<(), Error> {
-    let current_filter_path = Path::new("/var/tmp/.worktime_filter");
-    let config_file_path = dirs::home_dir().unwrap().join(".taskrc");
-
-    let essential_string = "(+TODAY and +INSTANCE)";
-    let scheduled_string =
-        "((scheduled.after:today or scheduled:today) and scheduled.before:tomorrow)";
-
-    let worktime_filter = format!(
-        "(WT:{} or WT:AllDay) and ({} or {})",
-        current_wt, essential_string, scheduled_string
-    );
-
-    let due_filter = env::var("DUE_FILTER").expect("The DUE_FILTER is not set! Fix it >:(");
-    let tw_filter = env::var("TW_FILTER").expect("The TW_FILTER is not set! Fix it >:(");
-
-    let habit_filter = format!(
-        "({} and WT:AllDay and (status.not:recurring and status.not:waiting))",
-        due_filter
-    );
-
-    let main_filter = format!("({} or {}) and status:pending", tw_filter, worktime_filter);
-
-    let final_filter = format!("({}) or {}", main_filter, habit_filter).replace("\\", "");
-
-    let regex = Regex::new(format!("{}.*", cfg_line).as_str()).unwrap();
-
-    let config_file = fs::read_to_string(&config_file_path).unwrap();
-
-    let new_config_file = regex
-        .replace_all(&config_file, format!("{}={}", cfg_line, final_filter))
-        .to_string();
-
-    fs::write(config_file_path, new_config_file)?;
-
-    fs::write(current_filter_path, final_filter)?;
-
-    Ok(())
-}
 fn update_viewer_session(viewer: &str, viewer_quit_key: &str) -> Result<(), Error> {
     //. DEV: switch to tmux interface
 
@@ -95,6 +56,27 @@ fn update_viewer_session(viewer: &str, viewer_quit_key: &str) -> Result<(), Erro
     Ok(())
 }
 
+/// Apply a worktime: store it as the applied one, update the status and mount the taskrc again.
+/// In a dry run, nothing is saved.
+fn apply_worktime(preset: &Worktime) -> Result<(), FypmError> {
+    if backends::dry_run::is_enabled() {
+        println!("{} apply {}", "[dry-run]".bold().yellow(), preset.name);
+
+        return Ok(());
+    }
+
+    env::set_var("WORKTIME", &preset.name);
+
+    WorktimeHandler::set_applied(&preset.name)?;
+    // The status bar only shows the worktime, it can't stop it from being applied.
+    if let Err(error) = StatusHandler::gather().and_then(|status| StatusHandler::write_all(&status))
+    {
+        eprintln!("Failed to update the status: {}", error.message);
+    }
+
+    // The taskrc is mounted again, with the context of the worktime.
+    ConfigHandler::handle_config()
+}
 pub fn apply(name: &String) -> Result<(), FypmError> {
     let mut conn = SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
 
@@ -102,27 +84,15 @@ pub fn apply(name: &String) -> Result<(), FypmError> {
 
     match get_preset {
         Ok(preset) => {
-            let current_wt_string = format!("{} -> {}", preset.name, preset.end_time.as_str());
-
-            env::set_var("WORKTIME", preset.name);
-
-            write_values_on_temp_files(
-                &current_wt_string,
-                &preset.polybar_background,
-                &preset.polybar_foreground,
-            )
-            .unwrap();
-
-            {
-                let twui_cfg_line = "uda.taskwarrior-tui.task-report.next.filter";
+            apply_worktime(&preset)?;
 
-                update_filter(&name, twui_cfg_line).unwrap();
+            // The viewer only shows the tasks, it can't stop the worktime from being applied.
+            if !backends::dry_run::is_enabled() {
+                if let Err(error) = update_viewer_session("wvit", ":q") {
+                    eprintln!("Failed to refresh the viewer: {}", error);
+                }
             }
 
-            update_viewer_session("wvit", ":q").unwrap();
-
-            Command::new("polybar-msg").args(["cmd", "restart"]);
-
             Ok(())
         }
         Err(error) => match error.kind {
@@ -130,7 +100,7 @@ pub fn apply(name: &String) -> Result<(), FypmError> {
                 println!("{}", error.message);
                 println!("These are the available presets:");
 
-                WorktimeHandler::list(&mut conn)?;
+                WorktimeHandler::list(&mut conn, &OutputFormat::Pretty)?;
 
                 Ok(())
             }
@@ -138,4 +108,389 @@ pub fn apply(name: &String) -> Result<(), FypmError> {
         },
     }
 }
+/// Apply the worktime of the current time, following the schedule.
+pub fn apply_current() -> Result<(), FypmError> {
+    let mut conn = SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+
+    let worktimes = WorktimeHandler::get_all(&mut conn)?;
+    let schedules = WorktimeHandler::get_schedules(&mut conn)?;
+
+    match WorktimeHandler::get_current(&worktimes, &schedules, &Local::now().naive_local()) {
+        Some(worktime) => apply(&worktime.name),
+        None => Err(FypmError {
+            message: "There's no worktime for the current time!".to_string(),
+            kind: FypmErrorKind::NotFound,
+        }),
+    }
+}
+
+/// Show the worktime of the current time in the schedule, without applying it.
+pub fn current(output_format: &OutputFormat) -> Result<(), FypmError> {
+    let mut conn = SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+
+    let worktimes = WorktimeHandler::get_all(&mut conn)?;
+    let schedules = WorktimeHandler::get_schedules(&mut conn)?;
+
+    let Some(worktime) =
+        WorktimeHandler::get_current(&worktimes, &schedules, &Local::now().naive_local())
+    else {
+        println!("There's no worktime for the current time!");
+        return Ok(());
+    };
+
+    if output::print_record(output_format, worktime)? {
+        return Ok(());
+    }
+
+    println!(
+        "{} ({} -> {})",
+        worktime.name.bold(),
+        worktime.start_time,
+        worktime.end_time
+    );
+
+    Ok(())
+}
+
+/// The Timewarrior intervals between the dates, with the WT of their tasks.
+fn tracked_intervals(
+    start: &NaiveDate,
+    end: &NaiveDate,
+) -> Result<Vec<TrackedInterval>, FypmError> {
+    let intervals =
+        backends::time::current().export(&[start.to_string(), "-".to_string(), end.to_string()])?;
+
+    let uuids: Vec<String> = intervals
+        .iter()
+        .flat_map(|interval| interval.tags.clone().unwrap_or_default())
+        .filter(|tag| Uuid::parse_str(tag).is_ok())
+        .unique()
+        .collect();
+    let tasks_wt: HashMap<String, Option<String>> = match uuids.is_empty() {
+        true => HashMap::new(),
+        false => get::json_by_filter(&uuids.join(" "), None)?
+            .into_iter()
+            .map(|task| (task.uuid, task.wt))
+            .collect(),
+    };
+
+    let parse_date = |date: &str| {
+        NaiveDateTime::parse_from_str(date, TIMEWARRIOR_DATE_FORMAT)
+            .map(|date| date.and_utc().with_timezone(&Local).naive_local())
+            .map_err(|_| FypmError {
+                message: format!("Timewarrior returned an invalid date: {}", date),
+                kind: FypmErrorKind::BackendError,
+            })
+    };
+
+    intervals
+        .iter()
+        .map(|interval| {
+            Ok(TrackedInterval {
+                start: parse_date(&interval.start)?,
+                end: match &interval.end {
+                    Some(end) => parse_date(end)?,
+                    None => Local::now().naive_local(),
+                },
+                worktime: interval
+                    .tags
+                    .iter()
+                    .flatten()
+                    .find_map(|tag| tasks_wt.get(tag).cloned().flatten()),
+            })
+        })
+        .collect()
+}
+/// Planned, tracked and untracked minutes of each worktime in the period (by default, today).
+pub fn report(
+    date_args: &Option<Vec<String>>,
+    output_format: &OutputFormat,
+) -> Result<(), FypmError> {
+    let [start, end] = match date_args {
+        Some(date_args) => extract::date_period(date_args),
+        None => {
+            let today = Local::now().date_naive();
+            [today, today + TimeDelta::days(1)]
+        }
+    };
+
+    let mut conn = SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+
+    let records = WorktimeHandler::report(
+        &WorktimeHandler::get_all(&mut conn)?,
+        &WorktimeHandler::get_schedules(&mut conn)?,
+        // The windows of the last day may end in the next one.
+        &tracked_intervals(&start, &(end + TimeDelta::days(1)))?,
+        &start,
+        &end,
+    );
+
+    if output::print_records(output_format, &records)? {
+        return Ok(());
+    }
+
+    if records.is_empty() {
+        println!("No worktimes or tracked time in this period!");
+    }
+
+    for (date, day_records) in &records.iter().chunk_by(|record| record.date.clone()) {
+        println!("{}", date.bold());
+
+        for record in day_records {
+            if record.worktime == OFF_WORKTIME {
+                println!(
+                    "  {:<16} {} tracked",
+                    "Off worktime".bright_black(),
+                    format_elapsed(record.tracked)
+                );
+                continue;
+            }
+
+            println!(
+                "  {:<16} {} of {} tracked, {} on other worktimes, {} untracked",
+                record.worktime,
+                format_elapsed(record.tracked).bright_green(),
+                format_elapsed(record.planned),
+                format_elapsed(record.other).yellow(),
+                format_elapsed(record.untracked).bright_red()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn export(path: &Option<String>) -> Result<(), FypmError> {
+    let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+
+    let content =
+        toml::to_string_pretty(&WorktimeHandler::export(conn)?).map_err(|error| FypmError {
+            message: format!("Failed to serialize the worktimes: {}", error),
+            kind: FypmErrorKind::BackendError,
+        })?;
+
+    match path {
+        Some(path) => {
+            fs::write(path, content).map_err(|error| FypmError {
+                message: format!("Failed to write {}: {}", path, error),
+                kind: FypmErrorKind::BackendError,
+            })?;
+            println!("Worktimes exported to {}!", path);
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+pub fn import(
+    path: &String,
+    on_conflict: &ImportConflict,
+    allow_overlap: &bool,
+) -> Result<(), FypmError> {
+    let content = fs::read_to_string(path).map_err(|error| FypmError {
+        message: format!("Failed to read {}: {}", path, error),
+        kind: FypmErrorKind::NotFound,
+    })?;
+    let file: WorktimesFile = toml::from_str(&content).map_err(|error| FypmError {
+        message: format!("{} is not a valid worktimes file: {}", path, error),
+        kind: FypmErrorKind::InvalidInput,
+    })?;
+
+    let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+    let imported = WorktimeHandler::import(
+        conn,
+        &file,
+        on_conflict,
+        &ConfigHandler::get_uda_values("STYLE")?,
+        allow_overlap,
+    )?;
+
+    println!(
+        "Imported {} of {} worktimes and {} holidays.",
+        imported.len(),
+        file.worktimes.len(),
+        file.holidays.len()
+    );
+
+    Ok(())
+}
+
+pub fn schedule(
+    action: &WtScheduleActions,
+    args: &Vec<String>,
+    description: &Option<String>,
+    output_format: &OutputFormat,
+) -> Result<(), FypmError> {
+    let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+
+    match action {
+        WtScheduleActions::Add => {
+            let Some((name, days)) = args.split_first() else {
+                return Err(FypmError {
+                    message: "Usage: wt-schedule add <worktime> <days...>".to_string(),
+                    kind: FypmErrorKind::InvalidInput,
+                });
+            };
+
+            WorktimeHandler::schedule_add(conn, name, &WorktimeHandler::parse_schedule_days(days)?)?;
+            println!("{} scheduled!", name);
+        }
+        WtScheduleActions::Remove => {
+            // The worktime is optional: without it, every schedule of the days is removed.
+            let (name, days) = match args.split_first() {
+                Some((first, rest))
+                    if WorktimeHandler::parse_schedule_days(std::slice::from_ref(first)).is_err() =>
+                {
+                    (Some(first.clone()), rest)
+                }
+                _ => (None, args.as_slice()),
+            };
+
+            let removed =
+                WorktimeHandler::schedule_remove(conn, &name, &WorktimeHandler::parse_schedule_days(days)?)?;
+            println!("{} schedules removed.", removed);
+        }
+        WtScheduleActions::Holiday => {
+            WorktimeHandler::schedule_holiday(conn, &WorktimeHandler::parse_schedule_days(args)?, description)?;
+            println!("Holidays added!");
+        }
+        WtScheduleActions::List => WorktimeHandler::schedule_list(conn, output_format)?,
+    }
+
+    Ok(())
+}
+
+fn daemon_files() -> (PathBuf, PathBuf) {
+    let path = Path::new(DB_PATH.as_str());
+
+    (path.join("wt-daemon.pid"), path.join("wt-daemon.log"))
+}
+fn daemon_log(message: &str) {
+    println!("[{}] {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+}
+fn daemon_loop() -> Result<(), FypmError> {
+    let mut conn = SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+    let mut applied: Option<String> = None;
+
+    daemon_log("Started");
+
+    loop {
+        let now = Local::now().naive_local();
+        let (worktimes, schedules) = match WorktimeHandler::get_all(&mut conn)
+            .and_then(|worktimes| Ok((worktimes, WorktimeHandler::get_schedules(&mut conn)?)))
+        {
+            Ok(result) => result,
+            Err(error) => {
+                // The database may be locked by another fypm, try again later
+                daemon_log(&format!("Failed to read the worktimes: {}", error.message));
+                thread::sleep(DAEMON_MAX_SLEEP.to_std().unwrap());
+
+                continue;
+            }
+        };
+
+        match WorktimeHandler::get_current(&worktimes, &schedules, &now) {
+            Some(worktime) if applied.as_ref() != Some(&worktime.name) => {
+                match apply_worktime(worktime) {
+                    Ok(()) => daemon_log(&format!("Applied {}", worktime.name)),
+                    Err(error) => daemon_log(&format!(
+                        "Failed to apply {}: {}",
+                        worktime.name, error.message
+                    )),
+                }
+
+                applied = Some(worktime.name.clone());
+            }
+            None if applied.is_some() => {
+                daemon_log("No worktime for the current time, keeping the last one");
+
+                applied = None;
+            }
+            _ => {}
+        }
+
+        let until_boundary = WorktimeHandler::next_boundary(&worktimes, &now.time())
+            .map(|boundary| {
+                TimeDelta::seconds((boundary - now.time()).num_seconds().rem_euclid(86400))
+            })
+            .unwrap_or(DAEMON_MAX_SLEEP);
+
+        thread::sleep(until_boundary.min(DAEMON_MAX_SLEEP).to_std().unwrap());
+    }
+}
+
+/// Apply the worktime of the current time, and the next ones when their windows begin.
+/// Unless `foreground` is set, it runs in background, with a pid and a log file in the fypm data dir.
+pub fn daemon(foreground: &bool, stop: &bool) -> Result<(), FypmError> {
+    let (pid_path, log_path) = daemon_files();
+
+    if *stop {
+        let pid = fs::read_to_string(&pid_path).map_err(|_| FypmError {
+            message: "The worktime daemon is not running!".to_string(),
+            kind: FypmErrorKind::NotFound,
+        })?;
+
+        let output = Command::new("kill")
+            .arg(pid.trim())
+            .output()
+            .map_err(|error| FypmError {
+                message: format!("Failed to stop the worktime daemon: {}", error),
+                kind: FypmErrorKind::BackendError,
+            })?;
+        if !output.status.success() {
+            return Err(FypmError {
+                message: format!(
+                    "Failed to stop the worktime daemon ({}): {}",
+                    pid.trim(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                kind: FypmErrorKind::BackendError,
+            });
+        }
+        fs::remove_file(&pid_path).map_err(|error| FypmError {
+            message: format!("Failed to remove {}: {}", pid_path.display(), error),
+            kind: FypmErrorKind::BackendError,
+        })?;
+
+        println!("Worktime daemon ({}) stopped.", pid.trim());
+
+        return Ok(());
+    }
+
+    if *foreground {
+        return daemon_loop();
+    }
+
+    let log_error = |error: Error| FypmError {
+        message: format!("Failed to open {}: {}", log_path.display(), error),
+        kind: FypmErrorKind::WrongInitialization,
+    };
+    let log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .map_err(log_error)?;
+
+    println!(
+        "Starting the worktime daemon (pid file: {}, log: {})",
+        pid_path.display(),
+        log_path.display()
+    );
+
+    Daemonize::new()
+        .pid_file(&pid_path)
+        .working_directory(DB_PATH.as_str())
+        .stdout(log.try_clone().map_err(log_error)?)
+        .stderr(log)
+        .start()
+        .map_err(|error| FypmError {
+            message: format!(
+                "Failed to start the worktime daemon (is it already running?): {}",
+                error
+            ),
+            kind: FypmErrorKind::WrongInitialization,
+        })?;
+
+    daemon_loop()
+}
 //#endregion
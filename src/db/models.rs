@@ -1,14 +1,16 @@
 use diesel::{
-    prelude::{Insertable, Queryable},
+    prelude::{AsChangeset, Insertable, Queryable},
     Selectable,
 };
 use diesel_migrations::{embed_migrations, EmbeddedMigrations};
 
-use crate::db::schema::{worktimes, filters};
+use crate::db::schema::{
+    aliases, filters, instances, journal, task_history, worktime_schedules, worktimes,
+};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
-#[derive(Queryable, Selectable, Insertable)]
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Clone, Debug, serde::Serialize, Default)]
 #[diesel(table_name = worktimes)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Worktime {
@@ -22,11 +24,76 @@ pub struct Worktime {
     pub polybar_foreground: String,
 }
 
-#[derive(Queryable, Selectable, Insertable)]
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, serde::Serialize, Default)]
 #[diesel(table_name = filters)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Filter {
     pub id: String,
     pub name: String,
     pub filter: String,
+    pub description: Option<String>,
+    /// When the filter was last expanded (`%name`) or run
+    pub last_used: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone)]
+#[diesel(table_name = journal)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct JournalEntry {
+    pub id: String,
+    pub operation_id: String,
+    pub command: String,
+    pub created_at: String,
+    pub task_uuid: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Assign a worktime to a weekday (`Mon`, `Tue`...) or to a date (`YYYY-MM-DD`).
+/// Dates override the weekdays, and a date without worktime is a holiday.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, PartialEq)]
+#[diesel(table_name = worktime_schedules)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct WorktimeSchedule {
+    pub id: String,
+    pub worktime_id: Option<String>,
+    pub weekday: Option<String>,
+    pub date: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A short name (ex: `d`) that can be used instead of a filter to reach a task.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, serde::Serialize, Default)]
+#[diesel(table_name = aliases)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Alias {
+    pub id: String,
+    pub name: String,
+    pub task_uuid: String,
+    pub description: Option<String>,
+}
+
+/// A start of a task, kept to switch back to the previous tasks (`last`, `last2`...).
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, serde::Serialize)]
+#[diesel(table_name = task_history)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct TaskHistoryEntry {
+    pub id: String,
+    pub task_uuid: String,
+    /// `YYYY-MM-DD HH:MM:SS.fffffffff`
+    pub started_at: String,
+}
+
+/// A separated Taskwarrior and Timewarrior database (ex: work and personal tasks).
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, serde::Serialize, Default)]
+#[diesel(table_name = instances)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Instance {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// The Taskwarrior data directory (`TASKDATA`)
+    pub taskdata: String,
+    /// The Timewarrior database directory (`TIMEWARRIORDB`)
+    pub timewarriordb: String,
 }
@@ -16,5 +16,56 @@ diesel::table! {
         id -> Text,
         name -> Text,
         filter -> Text,
+        description -> Nullable<Text>,
+        last_used -> Nullable<Text>,
     }
-}
\ No newline at end of file
+}
+
+diesel::table! {
+    journal (id) {
+        id -> Text,
+        operation_id -> Text,
+        command -> Text,
+        created_at -> Text,
+        task_uuid -> Text,
+        before -> Nullable<Text>,
+        after -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    worktime_schedules (id) {
+        id -> Text,
+        worktime_id -> Nullable<Text>,
+        weekday -> Nullable<Text>,
+        date -> Nullable<Text>,
+        description -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    aliases (id) {
+        id -> Text,
+        name -> Text,
+        task_uuid -> Text,
+        description -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    task_history (id) {
+        id -> Text,
+        task_uuid -> Text,
+        started_at -> Text,
+    }
+}
+
+diesel::table! {
+    instances (id) {
+        id -> Text,
+        name -> Text,
+        description -> Text,
+        taskdata -> Text,
+        timewarriordb -> Text,
+    }
+}
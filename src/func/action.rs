@@ -1,19 +1,18 @@
 //#region           Crates
-use std::fs;
-use std::io::{Error, ErrorKind, Write};
-use std::process::Command;
-use std::process::Stdio;
+use std::io::{Error, ErrorKind};
 use std::str;
 
 use itertools::Itertools;
 
 //#region           Modules
-use crate::values::constants::{DEFAULT_GET_JSON_OPTIONS, LAST_TASK_PATH};
+use crate::values::constants::DEFAULT_GET_JSON_OPTIONS;
+use crate::values::enums::TaskState;
 use crate::values::err::{FypmError, FypmErrorKind};
 use crate::utils::get;
+use crate::backends;
 use crate::values::structs::{TaskWarriorExported, TaskWarriorStatus};
 
-use super::command;
+use super::dialog;
 //#endregion
 //#region           Implementation
 pub fn annotate(
@@ -22,100 +21,37 @@ pub fn annotate(
     annotation: &String,
     skip_confirmation: bool,
 ) -> Result<(), FypmError> {
-    let mut args = Vec::new();
-    {
-        args.extend(["rc.verbose=0", "rc.recurrence.confirmation=off"]);
+    if command == "task" {
+        if !skip_confirmation {
+            let tasks = get::json_by_filter(filter, None)?;
 
-        if skip_confirmation {
-            args.extend(["rc.confirmation=off"]);
-        }
-        args.extend([filter, "annotate", annotation]);
-    }
-
-    let mut binding = Command::new(command);
-    let mut execute = binding
-        .args(args)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit());
-
-    if skip_confirmation {
-        execute = execute.stdin(Stdio::piped());
-
-        let get_count = Command::new("task")
-            .args(["rc.verbose=0", filter, "count"])
-            .output()
-            .unwrap();
-        let tasks_count = String::from_utf8(get_count.stdout)
-            .unwrap()
-            .trim()
-            .parse::<u32>()
-            .unwrap();
-
-        if tasks_count > 2 {
-            let mut child = execute.spawn().unwrap();
+            if tasks.len() > 2 && !dialog::verify_selected_tasks(&tasks)? {
+                println!("Aborting...");
 
-            let mut stdin = child.stdin.take().unwrap();
-            stdin.write_all("all\n".as_bytes()).unwrap();
-
-            child.wait().unwrap();
-
-            return Ok(());
+                return Ok(());
+            }
         }
-    } else {
-        execute = execute.stdin(Stdio::inherit());
+
+        return backends::task::current().annotate(filter, annotation);
     }
 
-    execute.output().unwrap();
+    let output = backends::time::current().annotate(filter, annotation)?;
+    print!("{}", output);
 
     Ok(())
 }
 
 pub fn unarchive(tasks: Vec<TaskWarriorExported>) -> Result<(), FypmError> {
-    let mut modify_binding = Command::new("task");
-    let modify_command = modify_binding
-        .args(vec![
-            "rc.verbose=0",
-            "rc.confirmation=0",
-            "rc.recurrence.confirmation=0",
-            tasks
-                .iter()
-                .map(|task| task.uuid.clone())
-                .join(" ")
-                .as_str(),
-            "modify",
-            "status:pending",
-            "-Archived",
-        ])
-        .stderr(Stdio::inherit());
-
-    if tasks.len() > 2 {
-        command::stdin_all(modify_command).unwrap();
-    } else {
-        modify_command.output().unwrap();
-    }
-
-    Ok(())
-}
-
-pub fn receive_last_task() -> Result<String, Error> {
-    let get_last_task = fs::read(LAST_TASK_PATH)?;
-
-    let last_task = String::from_utf8(get_last_task);
-
-    if last_task.is_ok() {
-        Ok(last_task.unwrap())
-    } else {
-        Err(Error::new(
-            std::io::ErrorKind::InvalidData,
-            last_task.err().unwrap(),
-        ))
-    }
+    backends::task::current().modify(
+        &tasks.iter().map(|task| task.uuid.clone()).join(" "),
+        &["status:pending".to_string(), "-Archived".to_string()],
+    )
 }
 
 /// Verify if the task is allday.
 /// If true, it will return an error warning that you are trying to start a task that is AllDay.
 pub fn verify_if_wt_is_allday(json: &TaskWarriorExported) -> Result<(), Error> {
-    if json.wt == "AllDay" {
+    if json.wt.as_deref() == Some("AllDay") {
         Err(Error::new(
             ErrorKind::InvalidInput,
             "You are trying to start a task that is AllDay!".to_string(),
@@ -152,7 +88,7 @@ pub fn match_inforelat_and_sequence(
         is_sequence = false;
     }
 
-    if state == "Info" {
+    if *state == Some(TaskState::Info) {
         let inforelat = &filter_json.inforelat;
 
         if let Some(inforelat) = inforelat {
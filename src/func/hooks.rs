@@ -0,0 +1,230 @@
+//! Rules applied by the Taskwarrior hooks. They don't read or write anything, so they can be
+//! tested without Taskwarrior: the hook commands (`commands::hook`) do the I/O.
+
+//#region           Crates
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+//#endregion
+//#region           Modules
+use crate::handlers::config::ConfigHandler;
+use crate::values::enums::{TaskState, TaskType};
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::values::structs::{TaskRule, TaskWarriorExported, TaskWarriorStatus};
+
+//#endregion
+//#region           Structs
+/// What a hook returns to Taskwarrior: the (possibly changed) task and feedback messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookOutput {
+    pub task: TaskWarriorExported,
+    pub feedback: Vec<String>,
+}
+/// The period between two instances of a recurring task, as written in `recur`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecurrencePeriod {
+    Days(u32),
+    Months(u32),
+    /// Every day from monday to friday
+    Weekdays,
+}
+//#endregion
+//#region           Functions
+fn type_error(message: &str) -> FypmError {
+    FypmError {
+        message: message.to_string(),
+        kind: FypmErrorKind::TaskTypeError,
+    }
+}
+/// The last second of `date` in the timezone `tz`, in UTC.
+fn end_of_day<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> DateTime<Utc> {
+    tz.from_local_datetime(&date.and_hms_opt(23, 59, 59).unwrap())
+        .earliest()
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// The local time of `date` in `tz`, converted back to UTC. In DST gaps, the earliest time is used.
+fn from_local<Tz: TimeZone>(date: NaiveDateTime, tz: &Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&date)
+        .earliest()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Parse the periods supported by fypm: daily, weekly, monthly, yearly, weekdays, `Nd` and `Nw`.
+pub fn parse_recurrence(recur: &str) -> Option<RecurrencePeriod> {
+    match recur {
+        "daily" => return Some(RecurrencePeriod::Days(1)),
+        "weekly" => return Some(RecurrencePeriod::Days(7)),
+        "monthly" => return Some(RecurrencePeriod::Months(1)),
+        "yearly" => return Some(RecurrencePeriod::Months(12)),
+        "weekdays" => return Some(RecurrencePeriod::Weekdays),
+        _ => {}
+    }
+
+    let (quantity, unit) = recur.split_at(recur.find(|c: char| !c.is_ascii_digit())?);
+    let quantity = quantity.parse::<u32>().ok()?;
+
+    match unit {
+        "d" => Some(RecurrencePeriod::Days(quantity)),
+        "w" => Some(RecurrencePeriod::Days(quantity.checked_mul(7)?)),
+        _ => None,
+    }
+}
+/// Move `date` forward `times` periods. The calendar of `tz` is used, so the local time of the
+/// date is kept even across DST changes.
+pub fn shift_date<Tz: TimeZone>(
+    date: DateTime<Utc>,
+    period: RecurrencePeriod,
+    times: u32,
+    tz: &Tz,
+) -> Option<DateTime<Utc>> {
+    let local = date.with_timezone(tz).naive_local();
+
+    let shifted = match period {
+        RecurrencePeriod::Days(days) => {
+            local.checked_add_days(Days::new(u64::from(days) * u64::from(times)))?
+        }
+        RecurrencePeriod::Months(months) => {
+            local.checked_add_months(Months::new(months.checked_mul(times)?))?
+        }
+        RecurrencePeriod::Weekdays => {
+            let mut shifted = local;
+            let mut remaining = times;
+
+            while remaining > 0 {
+                shifted = shifted.checked_add_days(Days::new(1))?;
+
+                if !matches!(shifted.weekday(), Weekday::Sat | Weekday::Sun) {
+                    remaining -= 1;
+                }
+            }
+
+            shifted
+        }
+    };
+
+    from_local(shifted, tz)
+}
+/// Instances are created with the ALARM, GOAL and scheduled of their parent. Move them by `imask`
+/// periods, so they keep the same distance to the due of the instance.
+fn shift_recurrence_dates<Tz: TimeZone>(
+    task: &mut TaskWarriorExported,
+    tz: &Tz,
+    feedback: &mut Vec<String>,
+) {
+    let (Some(recur), Some(imask)) = (&task.recur, task.imask) else {
+        return;
+    };
+    if task.parent.is_none() || imask == 0 {
+        return;
+    }
+
+    let Some(period) = parse_recurrence(recur) else {
+        feedback.push(format!(
+            "fypm warn: The recurrence \"{}\" is not supported, ALARM, GOAL and scheduled were not moved.",
+            recur
+        ));
+        return;
+    };
+
+    for date in [&mut task.alarm, &mut task.goal, &mut task.scheduled] {
+        if let Some(shifted) = date.and_then(|date| shift_date(date, period, imask, tz)) {
+            *date = Some(shifted);
+        }
+    }
+}
+
+/// Fail with the message of the first rule (from `rules.fypm.ini`) broken by the task.
+fn check_rules(rules: &[TaskRule], task: &TaskWarriorExported) -> Result<(), FypmError> {
+    match ConfigHandler::broken_rules(rules, task).first() {
+        Some(rule) => Err(type_error(&rule.message)),
+        None => Ok(()),
+    }
+}
+
+/// Apply the rules and the TYPE dates (see docs/fypm/Workflow/Properties) to a task that is being
+/// added. Recurring instances also have their dates moved (see `shift_recurrence_dates`).
+/// `now` gives the current date and the timezone used for "today" and "23:59:59".
+pub fn on_add<Tz: TimeZone>(
+    mut task: TaskWarriorExported,
+    now: &DateTime<Tz>,
+    rules: &[TaskRule],
+) -> Result<HookOutput, FypmError> {
+    let mut feedback = Vec::new();
+    let tz = now.timezone();
+
+    shift_recurrence_dates(&mut task, &tz, &mut feedback);
+    check_rules(rules, &task)?;
+
+    match &task.r#type {
+        Some(TaskType::Eventual) => {
+            let today = now.date_naive();
+
+            if let Some(due) = task.due {
+                let tomorrow = tz
+                    .from_local_datetime(&(today + Days::new(1)).and_hms_opt(0, 0, 0).unwrap())
+                    .earliest()
+                    .unwrap();
+
+                if due >= tomorrow {
+                    return Err(type_error(
+                        "Eventual tasks must be completed in the same day! You can use 'Objective' instead.",
+                    ));
+                }
+            } else {
+                feedback.push(
+                    "fypm warn: No due specified, setting due to today at 23:59:59.".to_string(),
+                );
+
+                task.due = Some(end_of_day(today, &tz));
+            }
+        }
+        Some(TaskType::Event) if task.parent.is_none() => {
+            let Some(due) = task.due else {
+                return Err(type_error("Event tasks must have a due!"));
+            };
+
+            task.due = Some(end_of_day(due.with_timezone(&tz).date_naive(), &tz));
+        }
+        _ => {}
+    }
+
+    Ok(HookOutput { task, feedback })
+}
+/// Apply the rules to a modification. Only the rules that the task didn't break before are checked,
+/// so old tasks can still be modified.
+pub fn on_modify(
+    old: &TaskWarriorExported,
+    new: TaskWarriorExported,
+    rules: &[TaskRule],
+) -> Result<HookOutput, FypmError> {
+    let completed =
+        new.status == TaskWarriorStatus::Completed && old.status != TaskWarriorStatus::Completed;
+    let started = new.start.is_some() && old.start.is_none();
+
+    if completed && new.r#type == Some(TaskType::Continuous) {
+        return Err(type_error("You cannot done a continuous task!"));
+    }
+    if started && new.r#type == Some(TaskType::Check) {
+        return Err(type_error("Check tasks can't be started!"));
+    }
+    if started && new.state == Some(TaskState::Info) {
+        return Err(type_error(
+            "Tasks with STATE set to 'Info' can't be started!",
+        ));
+    }
+
+    let already_broken = ConfigHandler::broken_rules(rules, old);
+    if let Some(rule) = ConfigHandler::broken_rules(rules, &new)
+        .into_iter()
+        .find(|rule| !already_broken.contains(rule))
+    {
+        return Err(type_error(&rule.message));
+    }
+
+    Ok(HookOutput {
+        task: new,
+        feedback: Vec::new(),
+    })
+}
+//#endregion
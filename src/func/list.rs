@@ -1,7 +1,16 @@
-use crate::values::{err::FypmError, structs::TaskWarriorExported};
+use chrono::{Duration, NaiveDate};
+
+use crate::values::{
+    enums::{TaskStyle, TaskType},
+    err::FypmError,
+    structs::{
+        CompletionScore, DeletedTasksStatistic, PendingTasksStatistic, TaskWarriorExported,
+        TaskWarriorStatus,
+    },
+};
 use crate::utils::get;
 
-pub fn deleted_tasks(no_parents: &bool) -> Result<(), FypmError> {
+pub fn deleted_tasks(no_parents: &bool) -> Result<DeletedTasksStatistic, FypmError> {
     let base_filter = "+DELETED and -COMPLETED and -PENDING";
 
     let final_filter = if *no_parents {
@@ -19,20 +28,20 @@ pub fn deleted_tasks(no_parents: &bool) -> Result<(), FypmError> {
             .count()
     }
 
-    let archived = get_count(&tasks_json, &"Archived".to_string());
-    let failed = get_count(&tasks_json, &"Failed".to_string());
-    let abandoned = get_count(&tasks_json, &"Abandoned".to_string());
-    let nocontrol = get_count(&tasks_json, &"NoControl".to_string());
-
-    println!("Archived tasks: {}", archived);
-    println!("Failed tasks: {}", failed);
-    println!("Abandoned tasks: {}", abandoned);
-    println!("NoControl tasks: {}", nocontrol);
-
-    Ok(())
+    Ok(DeletedTasksStatistic {
+        archived: get_count(&tasks_json, &"Archived".to_string()),
+        failed: get_count(&tasks_json, &"Failed".to_string()),
+        abandoned: get_count(&tasks_json, &"Abandoned".to_string()),
+        nocontrol: get_count(&tasks_json, &"NoControl".to_string()),
+    })
 }
-pub fn pending_tasks(no_parents: &bool) -> Result<(), FypmError> {
-    let divisory = "----------------------";
+pub fn print_deleted_tasks(statistic: &DeletedTasksStatistic) {
+    println!("Archived tasks: {}", statistic.archived);
+    println!("Failed tasks: {}", statistic.failed);
+    println!("Abandoned tasks: {}", statistic.abandoned);
+    println!("NoControl tasks: {}", statistic.nocontrol);
+}
+pub fn pending_tasks(no_parents: &bool) -> Result<PendingTasksStatistic, FypmError> {
     let base_filter = "-DELETED and -COMPLETED";
 
     let final_filter = if *no_parents {
@@ -47,40 +56,40 @@ pub fn pending_tasks(no_parents: &bool) -> Result<(), FypmError> {
 
     let necessity = tasks_json
         .iter()
-        .filter(|task| task.style == Some("Necessity".to_string()))
+        .filter(|task| task.style == Some(TaskStyle::Necessity))
         .count();
     let apollonian = tasks_json
         .iter()
-        .filter(|task| task.style == Some("Apollonian".to_string()))
+        .filter(|task| task.style == Some(TaskStyle::Apollonian))
         .count();
     let dionysian = tasks_json
         .iter()
-        .filter(|task| task.style == Some("Dionysian".to_string()))
+        .filter(|task| task.style == Some(TaskStyle::Dionysian))
         .count();
 
     let habit = tasks_json
         .iter()
-        .filter(|task| task.r#type == "Habit".to_string())
+        .filter(|task| task.r#type == Some(TaskType::Habit))
         .count();
     let eventual = tasks_json
         .iter()
-        .filter(|task| task.r#type == "Eventual".to_string())
+        .filter(|task| task.r#type == Some(TaskType::Eventual))
         .count();
     let objective = tasks_json
         .iter()
-        .filter(|task| task.r#type == "Objective".to_string())
+        .filter(|task| task.r#type == Some(TaskType::Objective))
         .count();
     let continuous = tasks_json
         .iter()
-        .filter(|task| task.r#type == "Continuous".to_string())
+        .filter(|task| task.r#type == Some(TaskType::Continuous))
         .count();
     let event = tasks_json
         .iter()
-        .filter(|task| task.r#type == "Event".to_string())
+        .filter(|task| task.r#type == Some(TaskType::Event))
         .count();
     let check = tasks_json
         .iter()
-        .filter(|task| task.r#type == "Check".to_string())
+        .filter(|task| task.r#type == Some(TaskType::Check))
         .count();
 
     let mother = tasks_json
@@ -98,6 +107,40 @@ pub fn pending_tasks(no_parents: &bool) -> Result<(), FypmError> {
 
     let style_none = tasks_json.iter().filter(|task| task.style == None).count();
 
+    Ok(PendingTasksStatistic {
+        all: all_pending,
+        necessity,
+        apollonian,
+        dionysian,
+        habit,
+        eventual,
+        objective,
+        continuous,
+        event,
+        check,
+        mother,
+        subtask,
+        style_none,
+    })
+}
+pub fn print_pending_tasks(statistic: &PendingTasksStatistic) {
+    let divisory = "----------------------";
+    let PendingTasksStatistic {
+        all: all_pending,
+        necessity,
+        apollonian,
+        dionysian,
+        habit,
+        eventual,
+        objective,
+        continuous,
+        event,
+        check,
+        mother,
+        subtask,
+        style_none,
+    } = *statistic;
+
     println!("All pending tasks: {}", all_pending);
     println!("{}", divisory);
     println!("Necessity: {} ({})", necessity, all_pending - necessity);
@@ -117,6 +160,30 @@ pub fn pending_tasks(no_parents: &bool) -> Result<(), FypmError> {
     println!(
         "OBS: The count in parentheses is the total of tasks without the respective counting."
     );
+}
+/// Count the instances due in the day, by status.
+pub fn day_completion_score(date: &NaiveDate) -> Result<CompletionScore, FypmError> {
+    let initial_day = date.format("%Y-%m-%d").to_string();
+    let final_day = (*date + Duration::days(1)).format("%Y-%m-%d").to_string();
+
+    let tasks_json = get::json_by_filter(format!("((due.after:{initial_day} or due:{initial_day}) and due.before:{final_day}) and +INSTANCE").as_str(), None)?;
 
-    Ok(())
+    let count = |status: TaskWarriorStatus| {
+        tasks_json
+            .iter()
+            .filter(|task| task.status == status)
+            .count()
+    };
+    let pending = count(TaskWarriorStatus::Pending);
+    let completed = count(TaskWarriorStatus::Completed);
+    let deleted = count(TaskWarriorStatus::Deleted);
+
+    Ok(CompletionScore {
+        kind: "day".to_string(),
+        date: initial_day,
+        pending,
+        completed,
+        deleted,
+        total: pending + completed + deleted,
+    })
 }
@@ -39,5 +39,6 @@ pub fn match_date_arg(option: &String, option_arg: Option<&String>) -> [NaiveDat
 pub fn match_verify_script(script: &enums::VerifyScripts) -> Result<(), FypmError> {
     match script {
         enums::VerifyScripts::Aliases => handlers::aliases::verify_aliases_tasks(),
+        enums::VerifyScripts::Rules => handlers::config::verify_rules_tasks(),
     }
 }
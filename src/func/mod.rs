@@ -4,5 +4,5 @@ pub mod list;
 pub mod matchs;
 pub mod parser;
 pub mod dialog;
-pub mod command;
-pub mod completion;
\ No newline at end of file
+pub mod completion;
+pub mod hooks;
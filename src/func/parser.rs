@@ -1,13 +1,19 @@
 use chrono::{DateTime, Local, Offset, ParseError};
+use diesel::{Connection, SqliteConnection};
 use regex::Regex;
 
-use super::action;
 use crate::values::{
-    constants::CONTROL_TASK,
     enums::TimewAction,
     err::{FypmError, FypmErrorKind},
 };
+use crate::handlers::aliases::AliasesHandler;
+use crate::handlers::config::ConfigHandler;
+use crate::handlers::database::DBHandler;
+use crate::handlers::filters::FiltersHandler;
+use crate::handlers::special_aliases::SpecialAliasesHandler;
+use crate::handlers::task_history::TaskHistoryHandler;
 use crate::utils::get;
+use crate::DATABASE_URL;
 
 pub fn transform_dates_to_iso(received_time: String) -> Result<String, ParseError> {
     let transformed_time_str = Regex::new(r"(\d{4})(\d{2})(\d{2})T(\d{2})(\d{2})(\d{2})Z")
@@ -29,49 +35,63 @@ pub fn transform_dates_to_iso(received_time: String) -> Result<String, ParseErro
 
     Ok(final_time)
 }
-pub fn match_special_aliases(filter: &String) -> String {
-    match filter.as_str() {
-        // Last Task
-        "last" => {
-            let get_task = action::receive_last_task();
-
-            match get_task {
-                Ok(task) => task,
-                Err(error) => {
-                    if error.kind() == std::io::ErrorKind::NotFound {
-                        CONTROL_TASK.to_string()
-                    } else {
-                        panic!("{}", error);
-                    }
-                }
-            }
-        },
+/// Expand the saved filters (`%name`) of a filter argument.
+pub fn expand_saved_filters(filter: &str) -> Result<String, FypmError> {
+    if !filter.contains('%') {
+        return Ok(filter.to_string());
+    }
+
+    let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+    let filters = FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
+
+    let expanded = FiltersHandler::expand(filter, &filters)?;
+    FiltersHandler::mark_used(conn, filter)?;
+
+    Ok(expanded)
+}
+/// Resolve the special aliases (`last`, `last2`..., `t` and the ones saved with `fypm alias add`)
+/// to the uuid of their task. Other filters only have their saved filters expanded.
+pub fn match_special_aliases(filter: &str) -> Result<String, FypmError> {
+    let alias = match filter {
         // Time without specific use
-        "t" => CONTROL_TASK.to_string(),
-        // Lost time
-        "l" => "1469ac5d-78ab-463d-bf77-f56a9f042f48".to_string(),
-        // Rest and breaks
-        "d" => "309d9b37-cd99-4b2c-b3c7-a9c60cb1754f".to_string(),
-        // Hygiene and Selfcare
-        "h" => "a371cb4e-6fad-452f-a22c-abc932f0a83f".to_string(),
-        // Singing
-        "s" => "2d5d97b5-fe43-415f-8501-045aca46cdbb".to_string(),
-        // Active Thought || DNM
-        "p" => "dd67efbb-f010-42c7-b84c-5d0da1936e57".to_string(),
-        // Calisthenics and Stretching
-        "e" => "7806d5f7-db60-4841-ba83-97c2106499d3".to_string(),
-        // Chess Practice
-        "x" => "100372a8-5ca2-493a-b6f3-4b74195c8848".to_string(),
-        // House Maintening
-        "hm" => "ef5dbc2c-326e-4443-b0dc-b2595de6e012".to_string(),
-        // Workflow Maintening
-        "wm" => "b719a399-0b21-4fed-9118-017096466073".to_string(),
-        // Tasks Maintening
-        "tm" => "8980c7be-1fda-4888-b45a-1a2e52345947".to_string(),
-        _ => filter.to_string(),
-        // Need to implement a filter to prevent cases like "r", "ab", etc.
-        // Now, if I write "r", it will pass and break
+        "t" => ConfigHandler::get_control_task()?.ok_or(FypmError {
+            message: "There is no control task! Set one with `fypm control set <filter>`."
+                .to_string(),
+            kind: FypmErrorKind::WrongInitialization,
+        })?,
+        // Previous tasks
+        _ if TaskHistoryHandler::parse_last(filter).is_some() => {
+            let conn = &mut SqliteConnection::establish(&DBHandler::instance_url()).unwrap();
+            let active_uuid = get::get_current_task_json().ok().map(|task| task.uuid);
+
+            TaskHistoryHandler::get_last(
+                conn,
+                TaskHistoryHandler::parse_last(filter).unwrap(),
+                active_uuid.as_deref(),
+            )?
+        }
+        _ if SpecialAliasesHandler::is_alias_like(filter) => {
+            let conn = &mut SqliteConnection::establish(&DBHandler::instance_url()).unwrap();
+
+            match SpecialAliasesHandler::resolve(conn, filter).map_err(FypmError::database)? {
+                Some(task_uuid) => task_uuid,
+                None => filter.to_string(),
+            }
+        }
+        _ => expand_saved_filters(filter)?,
+    };
+
+    Ok(alias)
+}
+/// Like `match_special_aliases`, but also resolving the ALIAS of the tasks (ex: `ta-start rest`).
+pub fn match_task_aliases(filter: &str) -> Result<String, FypmError> {
+    let resolved = match_special_aliases(filter)?;
+
+    if resolved != filter {
+        return Ok(resolved);
     }
+
+    Ok(AliasesHandler::find_by_alias(filter)?.unwrap_or(resolved))
 }
 pub fn match_special_timing_properties(id: &String) -> Result<String, FypmError> {
     if id.starts_with("@") {
@@ -1,10 +1,14 @@
 use dialoguer::{Confirm, Input};
 
+use crate::backends;
 use crate::values::constants::DEFAULT_GET_JSON_OPTIONS;
 use crate::values::err::{FypmError, FypmErrorKind};
 use crate::utils::get;
 use crate::values::structs::TaskWarriorExported;
 
+/// Max length of an ALIAS.
+pub const MAX_ALIAS_LENGTH: usize = 5;
+
 pub struct AliasesHandler;
 
 impl AliasesHandler {
@@ -29,7 +33,7 @@ impl AliasesHandler {
 
         if tasks_without_alias.len() > 0 {
             Err(FypmError {
-                message: "Tasks with Continuous TYPE must have an alias! Run `fypm alias --help to verify them`".to_string(),
+                message: "Tasks with Continuous TYPE must have an alias! Run `fypm ta-alias --help` to verify them".to_string(),
                 kind: FypmErrorKind::TaskTypeError,
             })
         } else {
@@ -37,62 +41,256 @@ impl AliasesHandler {
         }
     }
 
-    pub fn add(filter: &String) -> Result<(), FypmError> {
-        let get_task = get::json_by_filter(filter, DEFAULT_GET_JSON_OPTIONS)?;
-        let task = get_task
-            .get(0)
-            .unwrap();
+    /// If `alias` can be written in a filter, like `ta-start <alias>`. Ids (ex: `12`) can't.
+    pub fn is_valid(alias: &str) -> bool {
+        !alias.is_empty()
+            && alias.chars().count() <= MAX_ALIAS_LENGTH
+            && alias
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            && !alias.chars().all(|c| c.is_ascii_digit())
+    }
+    /// The uuid of the pending task with this ALIAS, if there is one.
+    pub fn find_by_alias(alias: &str) -> Result<Option<String>, FypmError> {
+        if !AliasesHandler::is_valid(alias) {
+            return Ok(None);
+        }
 
-        if let Some(alias) = &task.alias {
-            println!("Task {} already has alias {}. Are you trying to change? You can use `fypm alias change`!", task.uuid, alias);
-        } else {
-            let valid_alias = false;
-
-            while !valid_alias {
-                let get_input = Input::<String>::new()
-                    .with_prompt(format!("Write an alias for `{}` task:", task.description))
-                    .validate_with(|input: &String| -> Result<(), &str> {
-                        if input.is_empty() {
-                            Err("Alias cannot be empty!")
-                        } else if input.len() > 5 {
-                            Err("Alias cannot be longer than 5 characters!")
-                        } else {
-                            Ok(())
-                        }
-                    })
-                    .interact_text()
-                    .unwrap();
-
-                let tasks_with_this_alias = get::json_by_filter(
-                    &format!(
-                        "(TYPE:Continuous and status:pending and ALIAS:\"{}\")",
-                        get_input
-                    ),
-                    DEFAULT_GET_JSON_OPTIONS,
-                )
-                .unwrap();
-
-                if tasks_with_this_alias.len() > 0 {
-                    eprintln!("Oh no! You have a task with this alias!");
-
-                    let try_again = Confirm::new()
-                        .with_prompt("Do you want to try again?")
-                        .interact()
-                        .unwrap();
-
-                    if !try_again {
-                        return Ok(());
-                    }
+        let tasks = get::json_by_filter(&format!("(status:pending and ALIAS.is:{})", alias), None)?;
+
+        Ok(tasks.first().map(|task| task.uuid.clone()))
+    }
+    /// Check the format of the alias and if another pending task already has it.
+    pub fn verify(alias: &str, task: &TaskWarriorExported) -> Result<(), FypmError> {
+        if !AliasesHandler::is_valid(alias) {
+            return Err(FypmError {
+                message: format!(
+                    "{} is not a valid alias! Use until {} letters, numbers, '_' and '-' (not an id).",
+                    alias, MAX_ALIAS_LENGTH
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        if let Some(uuid) = AliasesHandler::find_by_alias(alias)? {
+            if uuid != task.uuid {
+                return Err(FypmError {
+                    message: format!("The task {} already has the alias {}!", uuid, alias),
+                    kind: FypmErrorKind::AlreadyExists,
+                });
+            }
+        }
+
+        Ok(())
+    }
+    /// Aliases made from the description (initials, then the start of the words), without the
+    /// ones in `taken`.
+    pub fn suggest(description: &str, taken: &[String]) -> Vec<String> {
+        let words: Vec<String> = description
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(|c| c.to_lowercase())
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        let mut candidates: Vec<String> = Vec::new();
+
+        let initials: String = words
+            .iter()
+            .filter_map(|word| word.chars().next())
+            .take(MAX_ALIAS_LENGTH)
+            .collect();
+        candidates.push(initials.clone());
+
+        if let Some(first_word) = words.first() {
+            for length in 2..=MAX_ALIAS_LENGTH {
+                candidates.push(first_word.chars().take(length).collect());
+            }
+        }
+        if let [first_word, second_word, ..] = words.as_slice() {
+            for length in 2..MAX_ALIAS_LENGTH {
+                candidates.push(
+                    first_word
+                        .chars()
+                        .take(length)
+                        .chain(second_word.chars().take(1))
+                        .collect(),
+                );
+            }
+        }
+        for number in 1..10 {
+            candidates.push(format!(
+                "{}{}",
+                initials.chars().take(MAX_ALIAS_LENGTH - 1).collect::<String>(),
+                number
+            ));
+        }
+
+        let mut suggestions: Vec<String> = Vec::new();
+        for candidate in candidates {
+            if AliasesHandler::is_valid(&candidate)
+                && !taken.contains(&candidate)
+                && !suggestions.contains(&candidate)
+            {
+                suggestions.push(candidate);
+            }
+        }
+
+        suggestions
+    }
+    /// Write the ALIAS of a task, after verifying it.
+    pub fn set_alias(task: &TaskWarriorExported, alias: &str) -> Result<(), FypmError> {
+        AliasesHandler::verify(alias, task)?;
+
+        backends::task::current().modify(&task.uuid, &[format!("ALIAS:{}", alias)])
+    }
+
+    fn get_task(filter: &str) -> Result<TaskWarriorExported, FypmError> {
+        Ok(get::json_by_filter(filter, DEFAULT_GET_JSON_OPTIONS)?.remove(0))
+    }
+    /// The aliases of the pending tasks.
+    fn get_taken() -> Result<Vec<String>, FypmError> {
+        Ok(get::json_by_filter("(status:pending and ALIAS.any:)", None)?
+            .into_iter()
+            .filter_map(|task| task.alias)
+            .collect())
+    }
+    /// Ask an alias to the task, suggesting one made from its description. Returns `None` if
+    /// nothing was written and `allow_skip` is set.
+    fn ask(
+        task: &TaskWarriorExported,
+        taken: &[String],
+        allow_skip: bool,
+    ) -> Result<Option<String>, FypmError> {
+        let suggestions = AliasesHandler::suggest(&task.description, taken);
+
+        let mut prompt = format!("Write an alias for `{}` task", task.description);
+        if !suggestions.is_empty() {
+            prompt.push_str(&format!(
+                " (suggestions: {})",
+                suggestions.iter().take(5).cloned().collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        let mut input = Input::<String>::new()
+            .with_prompt(prompt)
+            .allow_empty(allow_skip)
+            .validate_with(|input: &String| -> Result<(), String> {
+                if input.is_empty() && allow_skip {
+                    Ok(())
+                } else if taken.contains(input) {
+                    Err("Oh no! You have a task with this alias!".to_string())
+                } else if !AliasesHandler::is_valid(input) {
+                    Err(format!(
+                        "Use until {} letters, numbers, '_' and '-'!",
+                        MAX_ALIAS_LENGTH
+                    ))
                 } else {
-                    unimplemented!()
+                    Ok(())
                 }
-            }
+            });
+        if let Some(suggestion) = suggestions.first().filter(|_| !allow_skip) {
+            input = input.default(suggestion.clone());
         }
 
+        let alias = input.interact_text().unwrap();
+
+        Ok(Some(alias.trim().to_string()).filter(|alias| !alias.is_empty()))
+    }
+
+    /// Add an ALIAS to a task without one. Without alias, it's asked.
+    pub fn add(filter: &str, alias: &Option<String>) -> Result<(), FypmError> {
+        let task = AliasesHandler::get_task(filter)?;
+
+        if let Some(current) = &task.alias {
+            return Err(FypmError {
+                message: format!(
+                    "Task {} already has alias {}. Are you trying to change? You can use `fypm ta-alias change`!",
+                    task.uuid, current
+                ),
+                kind: FypmErrorKind::AlreadyExists,
+            });
+        }
+
+        let alias = match alias {
+            Some(alias) => alias.clone(),
+            None => AliasesHandler::ask(&task, &AliasesHandler::get_taken()?, false)?.unwrap(),
+        };
+
+        AliasesHandler::set_alias(&task, &alias)?;
+        println!("Alias {} added to `{}`!", alias, task.description);
+
         Ok(())
     }
+    /// Change the ALIAS of a task. Without alias, it's asked.
+    pub fn change(filter: &str, alias: &Option<String>) -> Result<(), FypmError> {
+        let task = AliasesHandler::get_task(filter)?;
+
+        let Some(current) = &task.alias else {
+            return Err(FypmError {
+                message: format!(
+                    "Task {} doesn't have an alias! You can use `fypm ta-alias add`.",
+                    task.uuid
+                ),
+                kind: FypmErrorKind::NotFound,
+            });
+        };
 
-    //pub fn change(self, name: &String) -> Result<(), FypmError> {}
+        let alias = match alias {
+            Some(alias) => alias.clone(),
+            None => {
+                AliasesHandler::ask(&task, &AliasesHandler::get_taken()?, false)?.unwrap()
+            }
+        };
+
+        AliasesHandler::set_alias(&task, &alias)?;
+        println!("Alias of `{}` changed from {} to {}!", task.description, current, alias);
+
+        Ok(())
+    }
+    /// Remove the ALIAS of a task.
+    pub fn remove(filter: &str) -> Result<(), FypmError> {
+        let task = AliasesHandler::get_task(filter)?;
+
+        let Some(current) = &task.alias else {
+            return Err(FypmError {
+                message: format!("Task {} doesn't have an alias!", task.uuid),
+                kind: FypmErrorKind::NotFound,
+            });
+        };
+
+        backends::task::current().modify(&task.uuid, &["ALIAS:".to_string()])?;
+        println!("Alias {} removed from `{}`!", current, task.description);
+
+        Ok(())
+    }
+    /// Ask an alias to every Continuous task without one. Empty answers skip the task.
+    pub fn bulk() -> Result<(), FypmError> {
+        let [_, tasks_without_alias] = AliasesHandler::get_tasks_by_alias_existence()?;
+        let mut taken = AliasesHandler::get_taken()?;
+
+        if tasks_without_alias.is_empty() {
+            println!("You do not have any tasks without alias, congrats! 🎉");
+            return Ok(());
+        }
+
+        println!(
+            "{} tasks without alias. Leave it empty to skip one.",
+            tasks_without_alias.len()
+        );
+        for task in tasks_without_alias {
+            if let Some(alias) = AliasesHandler::ask(&task, &taken, true)? {
+                AliasesHandler::set_alias(&task, &alias)?;
+                taken.push(alias);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub fn verify_aliases_tasks() -> Result<(), FypmError> {
@@ -102,7 +300,7 @@ pub fn verify_aliases_tasks() -> Result<(), FypmError> {
 
     if tasks_without_alias.len() > 0 {
         eprintln!(
-            "Oh no! You have {} tasks without alias! Fix it soon.",
+            "Oh no! You have {} tasks without alias! Fix it soon (`fypm ta-alias bulk`).",
             tasks_without_alias.len()
         );
 
@@ -1,19 +1,25 @@
+use crate::backends;
+use crate::db::models::Worktime;
+use crate::handlers::worktime::WorktimeHandler;
 use crate::values::comments::{
-    COLORS_CONFIG_COMMENT, OVERLAY_CONFIG_COMMENT, REPORT_CONFIG_COMMENT, TASK_CONFIG_COMMENT,
-    UDA_CONFIG_COMMENT, URGENCY_CONFIG_COMMENT,
+    COLORS_CONFIG_COMMENT, OVERLAY_CONFIG_COMMENT, REPORT_CONFIG_COMMENT, RULES_CONFIG_COMMENT,
+    SETTINGS_CONFIG_COMMENT, TASK_CONFIG_COMMENT, UDA_CONFIG_COMMENT, URGENCY_CONFIG_COMMENT,
 };
 use crate::values::constants::DEFAULT_CONFIG_FILES;
 use crate::values::enums::{FypmReports, FypmUDAs, FypmUrgency};
 use crate::values::err::{FypmError, FypmErrorKind};
 use crate::values::structs::{
-    FypmConfigFile, TaskWarriorReportConfig, TaskWarriorUDAConfig, TaskWarriorUrgencyConfig,
-    TaskWarriorUrgencyConfigScope, TaskWarriorUserScopeProperty,
+    FypmConfigFile, TaskRule, TaskRuleCondition, TaskWarriorExported, TaskWarriorReportConfig,
+    TaskWarriorUDAConfig, TaskWarriorUrgencyConfig, TaskWarriorUrgencyConfigScope,
+    TaskWarriorUserScopeProperty,
 };
-use crate::CONFIG_PATH;
+use crate::{CONFIG_PATH, DATABASE_URL};
+use diesel::{Connection, SqliteConnection};
+use regex::Regex;
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{env, fs};
 
 pub struct FypmConfigs {
@@ -46,11 +52,13 @@ impl ConfigHandler {
         ensure_file_existence(DEFAULT_CONFIG_FILES[3], URGENCY_CONFIG_COMMENT);
         ensure_file_existence(DEFAULT_CONFIG_FILES[4], COLORS_CONFIG_COMMENT);
         ensure_file_existence(DEFAULT_CONFIG_FILES[5], OVERLAY_CONFIG_COMMENT);
+        ensure_file_existence(DEFAULT_CONFIG_FILES[6], RULES_CONFIG_COMMENT);
+        ensure_file_existence(DEFAULT_CONFIG_FILES[7], SETTINGS_CONFIG_COMMENT);
 
         Ok(())
     }
 
-    fn create_config_defaults() -> FypmConfigs {
+    pub fn create_config_defaults() -> FypmConfigs {
         FypmConfigs {
             report: BTreeMap::from([
                 (
@@ -1006,8 +1014,15 @@ impl ConfigHandler {
             configs_map.extend(defaults_map);
         }
 
+        // Worktime contexts
         {
-            //. Put Worktime config here
+            let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+
+            configs_map.extend(ConfigHandler::get_worktime_configs(
+                &WorktimeHandler::get_all(conn)?,
+                &WorktimeHandler::get_applied(),
+                &ConfigHandler::get_settings()?,
+            ));
         }
 
         // General user-defined configs
@@ -1072,7 +1087,375 @@ impl ConfigHandler {
 
         Ok(configs_map)
     }
+    /// The rules of docs/fypm/Workflow/Properties, in the format of `rules.fypm.ini`.
+    pub fn create_rules_defaults() -> BTreeMap<String, String> {
+        let rules = [
+            (
+                "habit_recur",
+                "TYPE:Habit",
+                "recur",
+                "Habit tasks must have a recur!",
+            ),
+            (
+                "habit_state",
+                "TYPE:Habit",
+                "STATE:Time",
+                "Habit tasks must have STATE set to 'Time'!",
+            ),
+            (
+                "event_worktime",
+                "TYPE:Event",
+                "WT:AllDay!",
+                "You can only add an task with Event TYPE if it has an AllDay WT! You can use 'Check' instead.",
+            ),
+            (
+                "check_state",
+                "TYPE:Check",
+                "STATE:Info",
+                "Check tasks must have STATE set to 'Info'!",
+            ),
+            (
+                "continuous_state",
+                "TYPE:Continuous",
+                "STATE:Time",
+                "Continuous tasks must have STATE set to 'Time'!",
+            ),
+            (
+                "continuous_alias",
+                "TYPE:Continuous",
+                "ALIAS",
+                "Continuous tasks must have an ALIAS!",
+            ),
+            (
+                "mother_objective",
+                "+MOTHER",
+                "TYPE:Objective",
+                "Only Objective tasks can be a MOTHER!",
+            ),
+        ];
+
+        let mut defaults = BTreeMap::new();
+        for (name, when, require, message) in rules {
+            defaults.insert(format!("rule.{}.when", name), when.to_string());
+            defaults.insert(format!("rule.{}.require", name), require.to_string());
+            defaults.insert(format!("rule.{}.message", name), message.to_string());
+        }
+
+        defaults
+    }
+    /// Read the rules of a `rules.fypm.ini` map, sorted by name.
+    pub fn parse_rules(configs_map: &BTreeMap<String, String>) -> Result<Vec<TaskRule>, FypmError> {
+        fn invalid_rule(message: String) -> FypmError {
+            FypmError {
+                message,
+                kind: FypmErrorKind::InvalidConfig,
+            }
+        }
+        fn parse_conditions(value: &str) -> Vec<TaskRuleCondition> {
+            value
+                .split_whitespace()
+                .map(|condition| {
+                    if let Some(tag) = condition.strip_prefix('+') {
+                        TaskRuleCondition::Tag(tag.to_string())
+                    } else if let Some((attribute, value)) = condition.split_once(':') {
+                        TaskRuleCondition::Equals(attribute.to_string(), value.to_string())
+                    } else {
+                        TaskRuleCondition::Present(condition.to_string())
+                    }
+                })
+                .collect()
+        }
+
+        let mut rules: BTreeMap<String, TaskRule> = BTreeMap::new();
+        let mut disabled = Vec::new();
+
+        for (key, value) in configs_map {
+            let Some((name, field)) = key
+                .strip_prefix("rule.")
+                .and_then(|rule| rule.rsplit_once('.'))
+            else {
+                return Err(invalid_rule(format!(
+                    "Invalid key in rules config: {}",
+                    key
+                )));
+            };
+
+            let rule = rules.entry(name.to_string()).or_insert(TaskRule {
+                name: name.to_string(),
+                when: Vec::new(),
+                require: Vec::new(),
+                forbid: Vec::new(),
+                message: format!("The task breaks the rule '{}'!", name),
+            });
+
+            match field {
+                "when" => rule.when = parse_conditions(value),
+                "require" => rule.require = parse_conditions(value),
+                "forbid" => rule.forbid = parse_conditions(value),
+                "message" => rule.message = value.clone(),
+                "enabled" => match value.as_str() {
+                    "true" => {}
+                    "false" => disabled.push(name.to_string()),
+                    _ => return Err(invalid_rule(format!("{} must be 'true' or 'false'!", key))),
+                },
+                _ => {
+                    return Err(invalid_rule(format!(
+                        "Unknown field in rules config: {}. Are you sure if it's allowed?",
+                        key
+                    )))
+                }
+            }
+        }
+
+        for name in disabled {
+            rules.remove(&name);
+        }
+        if let Some(rule) = rules
+            .values()
+            .find(|rule| rule.require.is_empty() && rule.forbid.is_empty())
+        {
+            return Err(invalid_rule(format!(
+                "The rule {} doesn't require or forbid anything!",
+                rule.name
+            )));
+        }
+
+        Ok(rules.into_values().collect())
+    }
+    /// The fypm rules, changed and extended by `rules.fypm.ini`.
+    pub fn get_rules() -> Result<Vec<TaskRule>, FypmError> {
+        let rules_configs = ConfigHandler::get_config(DEFAULT_CONFIG_FILES[6])?;
+        ConfigHandler::verify_config_entries(&rules_configs, &vec![], &vec!["rule"])?;
+
+        let mut configs_map = ConfigHandler::create_rules_defaults();
+        configs_map.extend(rules_configs.map);
+
+        ConfigHandler::parse_rules(&configs_map)
+    }
+    /// The rules broken by the task, in the order they were received.
+    pub fn broken_rules<'a>(
+        rules: &'a [TaskRule],
+        task: &TaskWarriorExported,
+    ) -> Vec<&'a TaskRule> {
+        let task = serde_json::to_value(task).unwrap();
+
+        let matches = |condition: &TaskRuleCondition| match condition {
+            TaskRuleCondition::Tag(tag) => task["tags"]
+                .as_array()
+                .is_some_and(|tags| tags.iter().any(|item| item.as_str() == Some(tag))),
+            TaskRuleCondition::Present(attribute) => match &task[attribute] {
+                serde_json::Value::Null => false,
+                serde_json::Value::String(value) => !value.is_empty(),
+                _ => true,
+            },
+            TaskRuleCondition::Equals(attribute, value) => match &task[attribute] {
+                serde_json::Value::String(string) => string == value,
+                serde_json::Value::Array(items) => {
+                    items.iter().any(|item| item.as_str() == Some(value))
+                }
+                serde_json::Value::Null => value.is_empty(),
+                other => serde_json::from_str::<serde_json::Value>(value)
+                    .is_ok_and(|value| value == *other),
+            },
+        };
+
+        rules
+            .iter()
+            .filter(|rule| {
+                rule.when.iter().all(matches)
+                    && (!rule.require.iter().all(matches) || rule.forbid.iter().any(matches))
+            })
+            .collect()
+    }
+
+    pub fn create_settings_defaults() -> BTreeMap<String, String> {
+        BTreeMap::from(
+            [
+                ("status.sinks", "polybar"),
+                ("status.polybar.path", "/var/tmp"),
+                ("status.waybar.path", "/var/tmp/fypm-waybar.json"),
+                ("status.i3blocks.path", "/var/tmp/fypm-i3blocks.txt"),
+                ("status.plain.path", "/var/tmp/fypm-status.txt"),
+                ("status.json.path", "/var/tmp/fypm-status.json"),
+                (
+                    "worktime.filter.due",
+                    "(due:today or due.after:today) and due.before:tomorrow",
+                ),
+                (
+                    "worktime.filter.base",
+                    "+ACTIVE or +OVERDUE or +Divisory or ({due} and (WT:Quantify or WT:NonSched))",
+                ),
+                ("worktime.filter.essential", "+TODAY and +INSTANCE"),
+                (
+                    "worktime.filter.scheduled",
+                    "(scheduled.after:today or scheduled:today) and scheduled.before:tomorrow",
+                ),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string())),
+        )
+    }
+    /// The fypm settings, with the values of `settings.fypm.ini` over the defaults.
+    pub fn get_settings() -> Result<BTreeMap<String, String>, FypmError> {
+        let settings_configs = ConfigHandler::get_config(DEFAULT_CONFIG_FILES[7])?;
+        ConfigHandler::verify_config_entries(
+            &settings_configs,
+            &vec![],
+            &vec!["control", "instance", "status", "worktime"],
+        )?;
+
+        let mut configs_map = ConfigHandler::create_settings_defaults();
+        configs_map.extend(settings_configs.map);
+
+        Ok(configs_map)
+    }
+
+    /// The uuid of the control task (`control.task`), if it was set.
+    pub fn get_control_task() -> Result<Option<String>, FypmError> {
+        Ok(ConfigHandler::get_settings()?
+            .remove("control.task")
+            .filter(|uuid| !uuid.is_empty()))
+    }
+    /// The ini content with `key = value`, replacing the old value of the key if there is one.
+    pub fn replace_setting(content: &str, key: &str, value: &str) -> String {
+        let key_regex = Regex::new(&format!(r"(?m)^\s*{}\s*=.*$", regex::escape(key))).unwrap();
+        let line = format!("{} = {}", key, value);
+
+        if key_regex.is_match(content) {
+            key_regex.replace(content, line.as_str()).to_string()
+        } else {
+            format!("{}\n{}\n", content.trim_end(), line)
+        }
+    }
+    /// Write a value in `settings.fypm.ini`, keeping the rest of the file.
+    pub fn set_setting(key: &str, value: &str) -> Result<(), FypmError> {
+        let settings_path = Path::new(CONFIG_PATH.as_str()).join(DEFAULT_CONFIG_FILES[7]);
+        let content = fs::read_to_string(&settings_path).map_err(|error| FypmError {
+            message: format!("Failed to read {}: {}", settings_path.display(), error),
+            kind: FypmErrorKind::InvalidConfig,
+        })?;
+
+        fs::write(
+            &settings_path,
+            ConfigHandler::replace_setting(&content, key, value),
+        )
+        .map_err(|error| FypmError {
+            message: format!("Failed to write {}: {}", settings_path.display(), error),
+            kind: FypmErrorKind::InvalidConfig,
+        })?;
+
+        Ok(())
+    }
+
+    /// The tasks of the worktime: the base filter, its own tasks of today and the AllDay tasks due today.
+    /// `{due}` in the base filter is replaced by the due filter.
+    pub fn get_worktime_filter(name: &str, settings: &BTreeMap<String, String>) -> String {
+        let setting = |key: &str| {
+            settings
+                .get(&format!("worktime.filter.{}", key))
+                .cloned()
+                .unwrap_or_default()
+        };
+        let due_filter = setting("due");
+
+        let worktime_filter = format!(
+            "(WT:{} or WT:AllDay) and (({}) or ({}))",
+            name,
+            setting("essential"),
+            setting("scheduled")
+        );
+        let habit_filter = format!(
+            "(({}) and WT:AllDay and (status.not:recurring and status.not:waiting))",
+            due_filter
+        );
+        let main_filter = format!(
+            "({} or {}) and status:pending",
+            setting("base").replace("{due}", &format!("({})", due_filter)),
+            worktime_filter
+        );
+
+        format!("({}) or {}", main_filter, habit_filter)
+    }
+    /// A Taskwarrior context for each worktime. The applied worktime is the active context and
+    /// the filter of the taskwarrior-tui report, only in the taskrc of its instance.
+    pub fn get_worktime_configs(
+        worktimes: &[Worktime],
+        applied: &Option<String>,
+        settings: &BTreeMap<String, String>,
+    ) -> BTreeMap<String, String> {
+        let mut configs_map = BTreeMap::new();
+
+        for worktime in worktimes {
+            let filter = ConfigHandler::get_worktime_filter(&worktime.name, settings);
+
+            if applied.as_ref() == Some(&worktime.name) {
+                configs_map.insert("context".to_string(), worktime.name.clone());
+                configs_map.insert(
+                    "uda.taskwarrior-tui.task-report.next.filter".to_string(),
+                    filter.clone(),
+                );
+            }
+
+            configs_map.insert(format!("context.{}.read", worktime.name), filter);
+        }
+
+        configs_map
+    }
+
+    /// The values of a UDA in the generated taskrc, like the ones of `STYLE`.
+    pub fn get_uda_values(uda: &str) -> Result<Vec<String>, FypmError> {
+        let configs_map = ConfigHandler::mount_taskrc()?;
+
+        Ok(configs_map
+            .get(&format!("uda.{}.values", uda))
+            .map(|values| {
+                values
+                    .split(',')
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+    /// Where Taskwarrior looks for hooks, following its own precedence: `hooks.location`, then
+    /// `$TASKDATA`, then `data.location` (both plus `/hooks`), then `~/.task/hooks`.
+    pub fn resolve_hooks_location(
+        configs_map: &BTreeMap<String, String>,
+        taskdata: Option<String>,
+        home: &Path,
+    ) -> PathBuf {
+        let expand = |path: &str| match path.strip_prefix("~/") {
+            Some(relative) => home.join(relative),
+            None if path == "~" => home.to_path_buf(),
+            None => PathBuf::from(path),
+        };
+
+        if let Some(hooks_location) = configs_map.get("hooks.location") {
+            return expand(hooks_location);
+        }
+
+        let data_location = taskdata
+            .filter(|taskdata| !taskdata.is_empty())
+            .or_else(|| configs_map.get("data.location").cloned())
+            .unwrap_or("~/.task".to_string());
+
+        expand(&data_location).join("hooks")
+    }
+    /// The hooks directory of the Taskwarrior data dir configured in the generated taskrc.
+    pub fn get_hooks_location() -> Result<PathBuf, FypmError> {
+        let configs_map = ConfigHandler::mount_taskrc()?;
+
+        Ok(ConfigHandler::resolve_hooks_location(
+            &configs_map,
+            env::var("TASKDATA").ok(),
+            &dirs::home_dir().unwrap(),
+        ))
+    }
+    /// Write the generated taskrc, if it changed. Nothing is written in a dry run.
     pub fn handle_config() -> Result<(), FypmError> {
+        if backends::dry_run::is_enabled() {
+            return Ok(());
+        }
+
         let taskrc_env = env::var("TASKRC").unwrap_or("".to_string());
 
         let taskrc_path = match taskrc_env.as_str() {
@@ -1115,3 +1498,34 @@ impl ConfigHandler {
         Ok(())
     }
 }
+
+pub fn verify_rules_tasks() -> Result<(), FypmError> {
+    let rules = ConfigHandler::get_rules()?;
+    let tasks = backends::task::current().export("status:pending", &[])?;
+
+    let broken = tasks
+        .iter()
+        .flat_map(|task| {
+            ConfigHandler::broken_rules(&rules, task)
+                .into_iter()
+                .map(move |rule| (task, rule))
+        })
+        .collect::<Vec<_>>();
+
+    println!("{} rules checked in {} tasks", rules.len(), tasks.len());
+
+    if broken.is_empty() {
+        println!("Your tasks follow all the rules, congrats! 🎉");
+    } else {
+        eprintln!("Oh no! Your tasks broke {} rules:", broken.len());
+
+        for (task, rule) in broken {
+            println!(
+                "{} - {} ({}): {}",
+                task.uuid, task.description, rule.name, rule.message
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -1,12 +1,16 @@
 //#region           Crates
+use std::env;
 use std::fs;
 use std::io::Error;
+use std::path::{Path, PathBuf};
 
 use diesel::Connection;
 use diesel::SqliteConnection;
 use diesel_migrations::MigrationHarness;
 
 use crate::db::models::MIGRATIONS;
+use crate::values::constants::INSTANCE_PATH_VAR;
+use crate::values::err::{FypmError, FypmErrorKind};
 use crate::{DATABASE_URL, DB_PATH};
 
 //#endregion
@@ -31,4 +35,40 @@ impl DBHandler {
 
         Ok(())
     }
+
+    /// The directory of the data that belongs to the tasks of the current instance (the
+    /// journal, the task history, the aliases and the applied worktime). DB_PATH without one.
+    pub fn instance_path() -> PathBuf {
+        env::var(INSTANCE_PATH_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DB_PATH.as_str()))
+    }
+    /// The database of the current instance. The other data (ex: filters and worktimes) is
+    /// shared by every instance, in DATABASE_URL.
+    pub fn instance_url() -> String {
+        DBHandler::instance_path()
+            .join("fypm.db")
+            .to_string_lossy()
+            .into_owned()
+    }
+    /// Create the directory and the database of an instance, with every migration.
+    pub fn ensure_instance_db(path: &Path) -> Result<SqliteConnection, FypmError> {
+        let error = |error: String| FypmError {
+            message: format!(
+                "Failed to create the database of the instance in {}: {}",
+                path.display(),
+                error
+            ),
+            kind: FypmErrorKind::BackendError,
+        };
+
+        fs::create_dir_all(path).map_err(|e| error(e.to_string()))?;
+
+        let mut conn = SqliteConnection::establish(&path.join("fypm.db").to_string_lossy())
+            .map_err(|e| error(e.to_string()))?;
+        conn.run_pending_migrations(MIGRATIONS)
+            .map_err(|e| error(e.to_string()))?;
+
+        Ok(conn)
+    }
 }
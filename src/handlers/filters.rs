@@ -1,26 +1,49 @@
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+use chrono::Local;
 use colored::Colorize;
 use dialoguer::Input;
 use diesel::{Connection, ExpressionMethods, QueryResult, RunQueryDsl, SqliteConnection};
+use lazy_static::lazy_static;
 use ratatui::style::Stylize;
+use regex::Regex;
+use strfmt::strfmt;
 
 use crate::{
+    backends::{self, dry_run},
     db::{models::Filter, schema::filters},
-    values::err::{FypmError, FypmErrorKind},
+    utils::output,
+    values::{
+        enums::{FypmReports, ImportConflict, OutputFormat},
+        err::{FypmError, FypmErrorKind},
+        structs::{FilterEntry, FiltersFile},
+    },
     DATABASE_URL,
 };
 
+lazy_static! {
+    /// A reference to a saved filter: `%name`.
+    static ref SAVED_FILTER_REGEX: Regex = Regex::new(r"%([\w-]+)").unwrap();
+    /// A parameter of a saved filter, filled by `filter run`: `{name}`.
+    static ref PLACEHOLDER_REGEX: Regex = Regex::new(r"\{\w+\}").unwrap();
+}
+
 pub struct FiltersHandler;
 
 impl FiltersHandler {
     pub fn ensure_defaults() -> Result<(), FypmError> {
         let conn = &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
 
-        let filters = FiltersHandler::get_filters(conn).unwrap();
+        let filters = FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
 
         let defaults = vec![Filter {
             id: uuid::Uuid::now_v7().to_string(),
             name: "late_alarm".to_string(),
             filter: "(ALARM.before:now -PARENT -COMPLETED -DELETED)".to_string(),
+            description: Some("Tasks whose alarm already rang".to_string()),
+            last_used: None,
         }];
 
         for default in defaults {
@@ -36,178 +59,620 @@ impl FiltersHandler {
                 diesel::insert_into(filters::table)
                     .values(default)
                     .execute(conn)
-                    .unwrap();
+                    .map_err(FypmError::database)?;
             }
         }
 
         Ok(())
     }
 
-    pub fn verify_filter_already_exists(
-        input: &String,
-        filters: &Vec<Filter>,
-    ) -> Result<(), String> {
-        for filter in filters {
-            if filter.name == *input {
-                return Err(format!(
-                    "Filter with name '{}' already exists! Choose a different name.",
-                    input.clone()
-                ));
+    pub fn get_filters(conn: &mut SqliteConnection) -> QueryResult<Vec<Filter>> {
+        filters::dsl::filters.load(conn)
+    }
+    /// Replace each `%name` of the filter by the saved filter, between parentheses. Saved filters
+    /// can use other saved filters, as long as they don't end up using themselves.
+    pub fn expand(filter: &str, filters: &[Filter]) -> Result<String, FypmError> {
+        fn expand_with(
+            filter: &str,
+            filters: &[Filter],
+            stack: &mut Vec<String>,
+        ) -> Result<String, FypmError> {
+            let mut expanded = String::new();
+            let mut last_end = 0;
+
+            for captures in SAVED_FILTER_REGEX.captures_iter(filter) {
+                let reference = captures.get(0).unwrap();
+                let name = &captures[1];
+
+                if stack.iter().any(|used| used == name) {
+                    stack.push(name.to_string());
+
+                    return Err(FypmError {
+                        message: format!(
+                            "The filter {} uses itself! ({})",
+                            name,
+                            stack.join(" -> ")
+                        ),
+                        kind: FypmErrorKind::InvalidInput,
+                    });
+                }
+
+                let saved_filter = filters
+                    .iter()
+                    .find(|saved_filter| saved_filter.name == name)
+                    .ok_or(FypmError {
+                        message: format!("The filter {} doesn't exist!", name),
+                        kind: FypmErrorKind::NotFound,
+                    })?;
+
+                stack.push(name.to_string());
+                let saved_expanded = expand_with(&saved_filter.filter, filters, stack)?;
+                stack.pop();
+
+                expanded.push_str(&filter[last_end..reference.start()]);
+                expanded.push_str(&format!("({})", saved_expanded));
+                last_end = reference.end();
             }
+            expanded.push_str(&filter[last_end..]);
+
+            Ok(expanded)
         }
 
-        Ok(())
+        expand_with(filter, filters, &mut Vec::new())
     }
-    pub fn verify_filter_not_exists(input: &String, filters: &Vec<Filter>) -> Result<(), String> {
-        for filter in filters {
-            if filter.name == *input {
-                return Ok(());
-            }
+
+    fn find<'a>(name: &str, filters: &'a [Filter]) -> Result<&'a Filter, FypmError> {
+        filters
+            .iter()
+            .find(|filter| filter.name == name)
+            .ok_or(FypmError {
+                message: format!("The filter {} doesn't exist!", name),
+                kind: FypmErrorKind::NotFound,
+            })
+    }
+    fn verify_name(name: &str, filters: &[Filter]) -> Result<(), FypmError> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(FypmError {
+                message: format!(
+                    "{} is not a valid name! Use only letters, numbers, '_' and '-'.",
+                    name
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+        if filters.iter().any(|filter| filter.name == name) {
+            return Err(FypmError {
+                message: format!(
+                    "Filter with name '{}' already exists! Choose a different name.",
+                    name
+                ),
+                kind: FypmErrorKind::AlreadyExists,
+            });
         }
 
-        Err(format!(
-            "Filter with name '{}' does not exist! Choose a different name.",
-            input.clone()
-        ))
+        Ok(())
     }
+    /// Check the filter by counting its tasks in the backend, with its `%name` references
+    /// expanded. Filters with `{placeholders}` only have their references checked, since they
+    /// can't run before `filter run` fills them.
+    pub fn verify(filter: &str, filters: &[Filter]) -> Result<(), FypmError> {
+        let expanded = FiltersHandler::expand(filter, filters)?;
+
+        if PLACEHOLDER_REGEX.is_match(&expanded) {
+            return Ok(());
+        }
 
-    pub fn get_filters(conn: &mut SqliteConnection) -> QueryResult<Vec<Filter>> {
-        filters::dsl::filters.load(conn)
+        backends::task::current()
+            .count(&format!("({})", expanded))
+            .map(|_| ())
+            .map_err(|error| FypmError {
+                message: format!("{} is not a valid filter! {}", filter, error.message),
+                kind: FypmErrorKind::InvalidInput,
+            })
+    }
+    /// The filters that use `%name`.
+    fn get_dependents<'a>(name: &str, filters: &'a [Filter]) -> Vec<&'a Filter> {
+        filters
+            .iter()
+            .filter(|filter| {
+                SAVED_FILTER_REGEX
+                    .captures_iter(&filter.filter)
+                    .any(|captures| &captures[1] == name)
+            })
+            .collect()
     }
 
-    pub fn add(conn: &mut SqliteConnection) -> Result<(), FypmError> {
-        let filters = FiltersHandler::get_filters(conn).unwrap();
+    pub fn add_filter(
+        conn: &mut SqliteConnection,
+        name: &str,
+        filter: &str,
+        description: &Option<String>,
+    ) -> Result<(), FypmError> {
+        let filters = FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
 
-        let name = Input::<String>::new()
-            .with_prompt("Write a name for your filter")
-            .validate_with(|input: &String| -> Result<(), String> {
-                FiltersHandler::verify_filter_already_exists(input, &filters)
-            })
-            .interact_text()
-            .unwrap()
-            .trim()
-            .to_string();
-
-        let new_filter = Input::<String>::new()
-            .with_prompt("Write your filter")
-            .interact_text()
-            .unwrap()
-            .trim()
-            .to_string();
+        FiltersHandler::verify_name(name, &filters)?;
+
+        let new_filter = Filter {
+            id: uuid::Uuid::now_v7().to_string(),
+            name: name.to_string(),
+            filter: filter.trim().to_string(),
+            description: description.clone(),
+            last_used: None,
+        };
+
+        let mut new_filters = filters.clone();
+        new_filters.push(new_filter.clone());
+        FiltersHandler::verify(&new_filter.filter, &new_filters)?;
 
         diesel::insert_into(filters::table)
-            .values(Filter {
-                id: uuid::Uuid::now_v7().to_string(),
-                name: name.clone(),
-                filter: new_filter,
-            })
+            .values(new_filter)
             .execute(conn)
-            .unwrap();
+            .map_err(FypmError::database)?;
 
         Ok(())
     }
-    pub fn remove(conn: &mut SqliteConnection) -> Result<(), FypmError> {
-        let filters = FiltersHandler::get_filters(conn).unwrap();
+    /// Change the name, the filter or the description. A new name is also written in the filters
+    /// that used the old one.
+    pub fn edit_filter(
+        conn: &mut SqliteConnection,
+        name: &String,
+        new_name: &Option<String>,
+        new_filter: &Option<String>,
+        description: &Option<String>,
+    ) -> Result<(), FypmError> {
+        let filters = FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
+        let mut filter = FiltersHandler::find(name, &filters)?.clone();
+
+        if new_name.is_none() && new_filter.is_none() && description.is_none() {
+            return Err(FypmError {
+                message: "You must write something to edit!".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        if let Some(new_name) = new_name {
+            let other_filters: Vec<Filter> = filters
+                .iter()
+                .filter(|other| other.id != filter.id)
+                .cloned()
+                .collect();
+            FiltersHandler::verify_name(new_name, &other_filters)?;
+
+            filter.name = new_name.clone();
+        }
+        if let Some(new_filter) = new_filter {
+            filter.filter = new_filter.trim().to_string();
+        }
+        if let Some(description) = description {
+            filter.description = Some(description.clone()).filter(|d| !d.is_empty());
+        }
 
-        let name = Input::<String>::new()
-            .with_prompt("Write the name of the filter you want to remove")
-            .validate_with(|input: &String| -> Result<(), String> {
-                FiltersHandler::verify_filter_not_exists(input, &filters)
+        // The references to the old name
+        let dependents: Vec<Filter> = FiltersHandler::get_dependents(name, &filters)
+            .into_iter()
+            .filter(|dependent| dependent.id != filter.id)
+            .map(|dependent| Filter {
+                filter: SAVED_FILTER_REGEX
+                    .replace_all(&dependent.filter, |captures: &regex::Captures| {
+                        match &captures[1] == name {
+                            true => format!("%{}", filter.name),
+                            false => captures[0].to_string(),
+                        }
+                    })
+                    .to_string(),
+                ..dependent.clone()
+            })
+            .collect();
+
+        let new_filters: Vec<Filter> = filters
+            .iter()
+            .map(|other| {
+                dependents
+                    .iter()
+                    .chain([&filter])
+                    .find(|changed| changed.id == other.id)
+                    .unwrap_or(other)
+                    .clone()
             })
-            .interact_text()
-            .unwrap()
-            .trim()
-            .to_string();
+            .collect();
+        // Without a new filter, only the references need to be checked.
+        match new_filter {
+            Some(_) => FiltersHandler::verify(&filter.filter, &new_filters)?,
+            None => FiltersHandler::expand(&filter.filter, &new_filters).map(|_| ())?,
+        }
+
+        for changed in dependents.iter().chain([&filter]) {
+            diesel::update(filters::table)
+                .filter(filters::id.eq(&changed.id))
+                .set((
+                    filters::name.eq(&changed.name),
+                    filters::filter.eq(&changed.filter),
+                    filters::description.eq(&changed.description),
+                ))
+                .execute(conn)
+                .map_err(FypmError::database)?;
+        }
+
+        Ok(())
+    }
+    /// Remove a filter that isn't used by other filters.
+    pub fn remove_filter(conn: &mut SqliteConnection, name: &String) -> Result<(), FypmError> {
+        let filters = FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
+        FiltersHandler::find(name, &filters)?;
+
+        let dependents = FiltersHandler::get_dependents(name, &filters);
+        if !dependents.is_empty() {
+            return Err(FypmError {
+                message: format!(
+                    "The filter {} is used by {}! Edit them first.",
+                    name,
+                    dependents
+                        .iter()
+                        .map(|dependent| dependent.name.clone())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
 
         diesel::delete(filters::table)
             .filter(filters::name.eq(name))
             .execute(conn)
-            .unwrap();
+            .map_err(FypmError::database)?;
 
         Ok(())
     }
+    /// Save now as the last use of the filters referenced (`%name`) by `filter`.
+    /// Nothing is saved in a dry run.
+    pub fn mark_used(conn: &mut SqliteConnection, filter: &str) -> Result<(), FypmError> {
+        if dry_run::is_enabled() {
+            return Ok(());
+        }
 
-    pub fn edit(conn: &mut SqliteConnection) -> Result<(), FypmError> {
-        let filters = FiltersHandler::get_filters(conn).unwrap();
+        let names: Vec<String> = SAVED_FILTER_REGEX
+            .captures_iter(filter)
+            .map(|captures| captures[1].to_string())
+            .collect();
 
-        let name = Input::<String>::new()
-            .with_prompt("Write the name of the filter you want to edit")
-            .validate_with(|input: &String| -> Result<(), String> {
-                FiltersHandler::verify_filter_not_exists(input, &filters)
-            })
-            .interact_text()
-            .unwrap()
-            .trim()
-            .to_string();
-
-        let new_name = Input::<String>::new()
-            .with_prompt("Write the new name (enter a space to keep the same)")
-            .validate_with(|input: &String| -> Result<(), String> {
-                if input.trim().is_empty() {
-                    return Ok(());
-                }
+        diesel::update(filters::table)
+            .filter(filters::name.eq_any(names))
+            .set(filters::last_used.eq(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()))
+            .execute(conn)
+            .map_err(FypmError::database)?;
 
-                FiltersHandler::verify_filter_already_exists(input, &filters)
-            })
-            .interact_text()
-            .unwrap()
-            .trim()
-            .to_string();
-
-        let new_filter = Input::<String>::new()
-            .with_prompt("Write the new filter (enter a space to keep the same)")
-            .validate_with(|input: &String| -> Result<(), String> {
-                if input.trim().is_empty() {
-                    return Ok(());
-                }
+        Ok(())
+    }
 
-                FiltersHandler::verify_filter_already_exists(input, &filters)
-            })
-            .interact_text()
-            .unwrap()
-            .trim()
-            .to_string();
-
-        let mut set_values: (
-            diesel::dsl::Eq<filters::columns::name, String>,
-            diesel::dsl::Eq<filters::columns::filter, String>,
-        ) = (
-            filters::name.eq(new_name.clone()),
-            filters::filter.eq(new_filter.clone()),
-        );
+    /// `filter add <name> <filter...>`. Without arguments, they are asked.
+    pub fn add(
+        conn: &mut SqliteConnection,
+        args: &[String],
+        description: &Option<String>,
+    ) -> Result<(), FypmError> {
+        let (name, filter) = match args.split_first() {
+            Some((name, filter)) if !filter.is_empty() => (name.clone(), filter.join(" ")),
+            Some(_) => {
+                return Err(FypmError {
+                    message: "Usage: filter add <name> <filter...>".to_string(),
+                    kind: FypmErrorKind::InvalidInput,
+                })
+            }
+            None => (
+                Input::<String>::new()
+                    .with_prompt("Write a name for your filter")
+                    .interact_text()
+                    .unwrap()
+                    .trim()
+                    .to_string(),
+                Input::<String>::new()
+                    .with_prompt("Write your filter")
+                    .interact_text()
+                    .unwrap(),
+            ),
+        };
+
+        FiltersHandler::add_filter(conn, &name, &filter, description)?;
+        println!("Filter {} added!", name);
 
-        if !new_name.is_empty() && new_filter.is_empty() {
-            for filter in filters {
-                if filter.name == name {
-                    set_values = (
-                        filters::name.eq(new_name),
-                        filters::filter.eq(filter.filter),
-                    );
-                    break;
-                }
+        Ok(())
+    }
+    /// `filter remove <name>`. Without arguments, it's asked.
+    pub fn remove(conn: &mut SqliteConnection, args: &[String]) -> Result<(), FypmError> {
+        let name = match args {
+            [name] => name.clone(),
+            [] => Input::<String>::new()
+                .with_prompt("Write the name of the filter you want to remove")
+                .interact_text()
+                .unwrap()
+                .trim()
+                .to_string(),
+            _ => {
+                return Err(FypmError {
+                    message: "Usage: filter remove <name>".to_string(),
+                    kind: FypmErrorKind::InvalidInput,
+                })
             }
-        } else if new_name.is_empty() && !new_filter.is_empty() {
-            set_values = (
-                filters::name.eq(name.clone()),
-                filters::filter.eq(new_filter),
-            );
-        } else {
+        };
+
+        FiltersHandler::remove_filter(conn, &name)?;
+        println!("Filter {} removed!", name);
+
+        Ok(())
+    }
+    /// `filter edit <name> [filter...] [--name <new name>] [--description <description>]`.
+    /// Without arguments, they are asked.
+    pub fn edit(
+        conn: &mut SqliteConnection,
+        args: &[String],
+        new_name: &Option<String>,
+        description: &Option<String>,
+    ) -> Result<(), FypmError> {
+        let optional = |input: String| Some(input.trim().to_string()).filter(|i| !i.is_empty());
+
+        let (name, new_name, new_filter) = match args.split_first() {
+            Some((name, filter)) => (name.clone(), new_name.clone(), optional(filter.join(" "))),
+            None => (
+                Input::<String>::new()
+                    .with_prompt("Write the name of the filter you want to edit")
+                    .interact_text()
+                    .unwrap()
+                    .trim()
+                    .to_string(),
+                optional(
+                    Input::<String>::new()
+                        .with_prompt("Write the new name (enter a space to keep the same)")
+                        .interact_text()
+                        .unwrap(),
+                ),
+                optional(
+                    Input::<String>::new()
+                        .with_prompt("Write the new filter (enter a space to keep the same)")
+                        .interact_text()
+                        .unwrap(),
+                ),
+            ),
+        };
+
+        FiltersHandler::edit_filter(conn, &name, &new_name, &new_filter, description)?;
+        println!("Filter {} edited!", new_name.as_ref().unwrap_or(&name));
+
+        Ok(())
+    }
+
+    pub fn export(conn: &mut SqliteConnection) -> Result<FiltersFile, FypmError> {
+        Ok(FiltersFile {
+            filters: FiltersHandler::get_filters(conn)
+                .map_err(FypmError::database)?
+                .into_iter()
+                .map(|filter| FilterEntry {
+                    name: filter.name,
+                    filter: filter.filter,
+                    description: filter.description,
+                })
+                .collect(),
+        })
+    }
+    /// Add the filters of the file. Everything is verified before the first change. Returns the
+    /// names of the imported filters.
+    pub fn import(
+        conn: &mut SqliteConnection,
+        file: &FiltersFile,
+        on_conflict: &ImportConflict,
+    ) -> Result<Vec<String>, FypmError> {
+        let existing = FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
+
+        let conflicts: Vec<String> = file
+            .filters
+            .iter()
+            .filter(|entry| existing.iter().any(|filter| filter.name == entry.name))
+            .map(|entry| entry.name.clone())
+            .collect();
+        if !conflicts.is_empty() && *on_conflict == ImportConflict::Abort {
             return Err(FypmError {
-                message: "You must write something to edit!".to_string(),
+                message: format!(
+                    "These filters already exist: {}! Use --on-conflict skip or replace.",
+                    conflicts.join(", ")
+                ),
+                kind: FypmErrorKind::AlreadyExists,
+            });
+        }
+
+        if let Some(entry) = file.filters.iter().find(|entry| {
+            file.filters
+                .iter()
+                .filter(|other| other.name == entry.name)
+                .count()
+                > 1
+        }) {
+            return Err(FypmError {
+                message: format!("The filter {} is twice in the file!", entry.name),
                 kind: FypmErrorKind::InvalidInput,
             });
         }
 
-        diesel::update(filters::table)
-            .filter(filters::name.eq(name))
-            .set(set_values)
-            .execute(conn)
-            .unwrap();
+        // The filters as they will be after the import
+        let mut new_filters: Vec<Filter> = existing.clone();
+        let mut imported = Vec::new();
+        for entry in &file.filters {
+            match new_filters
+                .iter_mut()
+                .find(|filter| filter.name == entry.name)
+            {
+                Some(_) if *on_conflict == ImportConflict::Skip => continue,
+                Some(filter) => {
+                    filter.filter = entry.filter.clone();
+                    filter.description = entry.description.clone();
+                }
+                None => {
+                    FiltersHandler::verify_name(&entry.name, &new_filters)?;
+
+                    new_filters.push(Filter {
+                        id: uuid::Uuid::now_v7().to_string(),
+                        name: entry.name.clone(),
+                        filter: entry.filter.clone(),
+                        description: entry.description.clone(),
+                        last_used: None,
+                    });
+                }
+            }
+
+            imported.push(entry.name.clone());
+        }
+        for name in &imported {
+            FiltersHandler::verify(
+                &FiltersHandler::find(name, &new_filters)?.filter,
+                &new_filters,
+            )?;
+        }
+
+        conn.transaction(|conn| {
+            for filter in new_filters
+                .iter()
+                .filter(|filter| imported.contains(&filter.name))
+            {
+                if existing.iter().any(|other| other.id == filter.id) {
+                    diesel::update(filters::table)
+                        .filter(filters::id.eq(&filter.id))
+                        .set((
+                            filters::filter.eq(&filter.filter),
+                            filters::description.eq(&filter.description),
+                        ))
+                        .execute(conn)
+                        .map_err(FypmError::database)?;
+                } else {
+                    diesel::insert_into(filters::table)
+                        .values(filter)
+                        .execute(conn)
+                        .map_err(FypmError::database)?;
+                }
+            }
+
+            Ok(imported)
+        })
+    }
+    /// The saved filter with its `%name` references expanded and its `{placeholders}` filled.
+    pub fn render(
+        name: &str,
+        params: &HashMap<String, String>,
+        filters: &[Filter],
+    ) -> Result<String, FypmError> {
+        let expanded = FiltersHandler::expand(&format!("%{}", name), filters)?;
+
+        strfmt(&expanded, params).map_err(|error| FypmError {
+            message: format!(
+                "Failed to fill the parameters of the filter {}: {} (pass them as key=value)",
+                name, error
+            ),
+            kind: FypmErrorKind::InvalidInput,
+        })
+    }
+    /// Show the tasks of a saved filter in a report (`list` by default), like `taf` did.
+    /// Without a name, the filters are listed.
+    pub fn run(conn: &mut SqliteConnection, args: &[String]) -> Result<(), FypmError> {
+        let Some((name, args)) = args.split_first() else {
+            return FiltersHandler::list(conn, &OutputFormat::Pretty);
+        };
+
+        let (params, other_args): (Vec<&String>, Vec<&String>) =
+            args.iter().partition(|arg| arg.contains('='));
+        let report = match other_args.as_slice() {
+            [] => FypmReports::List,
+            [report] => FypmReports::from_str(report).map_err(|_| FypmError {
+                message: format!("{} is not a fypm report!", report),
+                kind: FypmErrorKind::InvalidInput,
+            })?,
+            _ => {
+                return Err(FypmError {
+                    message: "Usage: filter run <name> [report] [key=value...]".to_string(),
+                    kind: FypmErrorKind::InvalidInput,
+                })
+            }
+        };
+        let params: HashMap<String, String> = params
+            .iter()
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        let filters = FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
+        let filter = FiltersHandler::render(name, &params, &filters)?;
+        FiltersHandler::mark_used(conn, &format!("%{}", name))?;
+
+        backends::task::current().report(&filter, &report.to_string())
+    }
+    /// `filter export [path]`, to the standard output without a path.
+    pub fn export_to(conn: &mut SqliteConnection, args: &[String]) -> Result<(), FypmError> {
+        let content =
+            toml::to_string_pretty(&FiltersHandler::export(conn)?).map_err(|error| FypmError {
+                message: format!("Failed to serialize the filters: {}", error),
+                kind: FypmErrorKind::BackendError,
+            })?;
+
+        match args {
+            [] => print!("{}", content),
+            [path] => {
+                fs::write(path, content).map_err(|error| FypmError {
+                    message: format!("Failed to write {}: {}", path, error),
+                    kind: FypmErrorKind::BackendError,
+                })?;
+                println!("Filters exported to {}!", path);
+            }
+            _ => {
+                return Err(FypmError {
+                    message: "Usage: filter export [path]".to_string(),
+                    kind: FypmErrorKind::InvalidInput,
+                })
+            }
+        }
 
         Ok(())
     }
-    pub fn list(conn: &mut SqliteConnection) -> Result<(), FypmError> {
-        let filters: Vec<Filter> = FiltersHandler::get_filters(conn).unwrap();
+    /// `filter import <path>`.
+    pub fn import_from(
+        conn: &mut SqliteConnection,
+        args: &[String],
+        on_conflict: &ImportConflict,
+    ) -> Result<(), FypmError> {
+        let [path] = args else {
+            return Err(FypmError {
+                message: "Usage: filter import <path>".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        };
+
+        let content = fs::read_to_string(path).map_err(|error| FypmError {
+            message: format!("Failed to read {}: {}", path, error),
+            kind: FypmErrorKind::NotFound,
+        })?;
+        let file: FiltersFile = toml::from_str(&content).map_err(|error| FypmError {
+            message: format!("{} is not a valid filters file: {}", path, error),
+            kind: FypmErrorKind::InvalidInput,
+        })?;
+
+        let imported = FiltersHandler::import(conn, &file, on_conflict)?;
+        println!(
+            "Imported {} of {} filters.",
+            imported.len(),
+            file.filters.len()
+        );
+
+        Ok(())
+    }
+    pub fn list(
+        conn: &mut SqliteConnection,
+        output_format: &OutputFormat,
+    ) -> Result<(), FypmError> {
+        let filters: Vec<Filter> =
+            FiltersHandler::get_filters(conn).map_err(FypmError::database)?;
+
+        if output::print_records(output_format, &filters)? {
+            return Ok(());
+        }
 
         println!("{}", "These are the current filters:".bright_white().bold());
         for filter in filters {
@@ -216,6 +681,17 @@ impl FiltersHandler {
                 filter.name.bright_white(),
                 filter.filter.gray()
             );
+
+            if let Some(description) = &filter.description {
+                println!("    {}", description);
+            }
+            if let Some(last_used) = &filter.last_used {
+                println!(
+                    "    {} {}",
+                    "Last used:".bright_black(),
+                    last_used.bright_black()
+                );
+            }
         }
 
         Ok(())
@@ -0,0 +1,297 @@
+//! Install the launchers of the fypm hooks in the Taskwarrior hooks directory and check them.
+//! The rules themselves are run by `fypm hook` (see `commands::hook`).
+
+//#region           Crates
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use colored::Colorize;
+use dialoguer::Confirm;
+
+//#endregion
+//#region           Modules
+use crate::handlers::config::ConfigHandler;
+use crate::values::enums::HookEvent;
+use crate::values::err::{FypmError, FypmErrorKind};
+
+//#endregion
+//#region           Constants
+/// First line after the shebang of every launcher, followed by the version that wrote it.
+pub const HOOK_VERSION_MARKER: &str = "# fypm-hook-version:";
+/// Hooks that were copied by hand from the `hooks/` directory of the repository. Their rules are
+/// run by the launchers now, so keeping them would apply the rules (or track the time) twice.
+pub const LEGACY_HOOKS: [&str; 3] = [
+    "on-add.a_type_checker.fypm",
+    "on-add.recurrence_dates.fypm",
+    "on-modify.timewarrior",
+];
+
+//#endregion
+//#region           Structs
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookStatus {
+    Installed,
+    Missing,
+    /// There's a file with the name of the launcher, but fypm didn't write it
+    Foreign,
+    /// Written by another version of fypm
+    Outdated(String),
+    /// Written by this version, but edited or pointing to another binary
+    Modified,
+    NotExecutable,
+    /// The file exists, but can't be read (ex: a broken symlink or without permission)
+    Broken(String),
+}
+
+pub struct HooksHandler;
+//#endregion
+//#region           Implementation
+impl HooksHandler {
+    pub fn events() -> [HookEvent; 2] {
+        [HookEvent::OnAdd, HookEvent::OnModify]
+    }
+    pub fn file_name(event: &HookEvent) -> &'static str {
+        match event {
+            HookEvent::OnAdd => "on-add.fypm",
+            HookEvent::OnModify => "on-modify.fypm",
+        }
+    }
+    pub fn launcher(event: &HookEvent, binary: &Path) -> String {
+        let argument = match event {
+            HookEvent::OnAdd => "on-add",
+            HookEvent::OnModify => "on-modify",
+        };
+
+        format!(
+            "#!/bin/sh\n{} {}\n# Installed by `fypm hooks install`, don't edit it.\nexec \"{}\" hook {}\n",
+            HOOK_VERSION_MARKER,
+            env!("CARGO_PKG_VERSION"),
+            binary.display(),
+            argument
+        )
+    }
+
+    pub fn check(hooks_dir: &Path, event: &HookEvent, binary: &Path) -> HookStatus {
+        let path = hooks_dir.join(Self::file_name(event));
+
+        if fs::symlink_metadata(&path).is_err() {
+            return HookStatus::Missing;
+        }
+        let (content, metadata) = match fs::read_to_string(&path)
+            .and_then(|content| Ok((content, fs::metadata(&path)?)))
+        {
+            Ok(result) => result,
+            Err(error) => return HookStatus::Broken(error.to_string()),
+        };
+        let Some(version) = content
+            .lines()
+            .find_map(|line| line.strip_prefix(HOOK_VERSION_MARKER))
+            .map(|version| version.trim().to_string())
+        else {
+            return HookStatus::Foreign;
+        };
+
+        if version != env!("CARGO_PKG_VERSION") {
+            HookStatus::Outdated(version)
+        } else if content != Self::launcher(event, binary) {
+            HookStatus::Modified
+        } else if metadata.permissions().mode() & 0o111 == 0 {
+            HookStatus::NotExecutable
+        } else {
+            HookStatus::Installed
+        }
+    }
+    pub fn legacy_hooks(hooks_dir: &Path) -> Vec<String> {
+        LEGACY_HOOKS
+            .iter()
+            .filter(|name| hooks_dir.join(name).exists())
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    /// Write the launchers that aren't up to date. Files that fypm didn't write and the legacy
+    /// hooks are only replaced after a confirmation.
+    pub fn install_in(
+        hooks_dir: &Path,
+        binary: &Path,
+        skip_confirmation: &bool,
+    ) -> Result<Vec<String>, FypmError> {
+        let mut to_replace = Self::legacy_hooks(hooks_dir);
+        to_replace.extend(
+            Self::events()
+                .iter()
+                .filter(|event| {
+                    matches!(
+                        Self::check(hooks_dir, event, binary),
+                        HookStatus::Foreign | HookStatus::Broken(_)
+                    )
+                })
+                .map(|event| Self::file_name(event).to_string()),
+        );
+
+        if !to_replace.is_empty() && !*skip_confirmation {
+            println!(
+                "{} {}",
+                "These hooks will be replaced:".bright_white().bold(),
+                to_replace.join(", ")
+            );
+
+            let confirmation = Confirm::new()
+                .with_prompt("Do you want to continue?")
+                .interact()
+                .unwrap();
+
+            if !confirmation {
+                return Err(FypmError {
+                    message: "Aborted".to_string(),
+                    kind: FypmErrorKind::Aborted,
+                });
+            }
+        }
+
+        fs::create_dir_all(hooks_dir).map_err(|error| io_error(hooks_dir, error))?;
+
+        for name in Self::legacy_hooks(hooks_dir) {
+            let path = hooks_dir.join(name);
+            fs::remove_file(&path).map_err(|error| io_error(&path, error))?;
+        }
+
+        let mut installed = Vec::new();
+        for event in Self::events() {
+            let path = hooks_dir.join(Self::file_name(&event));
+
+            match Self::check(hooks_dir, &event, binary) {
+                HookStatus::Installed => continue,
+                // Writing would follow a broken symlink
+                HookStatus::Broken(_) => {
+                    fs::remove_file(&path).map_err(|error| io_error(&path, error))?
+                }
+                _ => {}
+            }
+
+            fs::write(&path, Self::launcher(&event, binary))
+                .map_err(|error| io_error(&path, error))?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .map_err(|error| io_error(&path, error))?;
+
+            installed.push(Self::file_name(&event).to_string());
+        }
+
+        Ok(installed)
+    }
+    /// Remove the launchers written by fypm. Other files are never touched.
+    pub fn uninstall_from(hooks_dir: &Path, binary: &Path) -> Result<Vec<String>, FypmError> {
+        let mut removed = Vec::new();
+
+        for event in Self::events() {
+            match Self::check(hooks_dir, &event, binary) {
+                HookStatus::Missing | HookStatus::Foreign | HookStatus::Broken(_) => {}
+                _ => {
+                    let path = hooks_dir.join(Self::file_name(&event));
+                    fs::remove_file(&path).map_err(|error| io_error(&path, error))?;
+
+                    removed.push(Self::file_name(&event).to_string());
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
+    pub fn install(skip_confirmation: &bool) -> Result<(), FypmError> {
+        let hooks_dir = ConfigHandler::get_hooks_location()?;
+        let installed = Self::install_in(&hooks_dir, &current_binary()?, skip_confirmation)?;
+
+        if installed.is_empty() {
+            println!("The hooks are already up to date!");
+        } else {
+            println!(
+                "Installed {} in {}",
+                installed.join(", "),
+                hooks_dir.display()
+            );
+        }
+
+        Ok(())
+    }
+    pub fn status() -> Result<(), FypmError> {
+        let hooks_dir = ConfigHandler::get_hooks_location()?;
+        let binary = current_binary()?;
+        let mut up_to_date = true;
+
+        println!(
+            "{} {}",
+            "Hooks directory:".bright_white().bold(),
+            hooks_dir.display()
+        );
+
+        for event in Self::events() {
+            let status = Self::check(&hooks_dir, &event, &binary);
+
+            let description = match &status {
+                HookStatus::Installed => "up to date".bright_green(),
+                HookStatus::Missing => "missing".bright_red(),
+                HookStatus::Foreign => "not installed by fypm".bright_red(),
+                HookStatus::Outdated(version) => format!(
+                    "installed by fypm {}, expected {}",
+                    version,
+                    env!("CARGO_PKG_VERSION")
+                )
+                .bright_yellow(),
+                HookStatus::Modified => "edited or pointing to another fypm binary".bright_yellow(),
+                HookStatus::NotExecutable => "not executable".bright_yellow(),
+                HookStatus::Broken(error) => format!("broken ({})", error).bright_red(),
+            };
+            up_to_date &= status == HookStatus::Installed;
+
+            println!("{:<20} {}", Self::file_name(&event), description);
+        }
+
+        for name in Self::legacy_hooks(&hooks_dir) {
+            up_to_date = false;
+
+            println!(
+                "{:<20} {}",
+                name,
+                "obsolete, its rules are run by the fypm hooks".bright_yellow()
+            );
+        }
+
+        if !up_to_date {
+            println!("Run `fypm hooks install` to fix them.");
+        }
+
+        Ok(())
+    }
+    pub fn uninstall() -> Result<(), FypmError> {
+        let hooks_dir = ConfigHandler::get_hooks_location()?;
+        let removed = Self::uninstall_from(&hooks_dir, &current_binary()?)?;
+
+        if removed.is_empty() {
+            println!("There are no fypm hooks in {}", hooks_dir.display());
+        } else {
+            println!(
+                "Removed {} from {}",
+                removed.join(", "),
+                hooks_dir.display()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn current_binary() -> Result<std::path::PathBuf, FypmError> {
+    std::env::current_exe().map_err(|error| FypmError {
+        message: format!("Failed to find the fypm binary: {}", error),
+        kind: FypmErrorKind::WrongInitialization,
+    })
+}
+fn io_error(path: &Path, error: std::io::Error) -> FypmError {
+    FypmError {
+        message: format!("Failed to write {}: {}", path.display(), error),
+        kind: FypmErrorKind::BackendError,
+    }
+}
+//#endregion
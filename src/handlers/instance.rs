@@ -0,0 +1,385 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use diesel::{ExpressionMethods, QueryResult, RunQueryDsl, SqliteConnection};
+
+use crate::{
+    db::{models::Instance, schema::instances},
+    handlers::{config::ConfigHandler, database::DBHandler},
+    utils::output,
+    values::{
+        constants::INSTANCE_PATH_VAR,
+        enums::{InstanceArgs, OutputFormat},
+        err::{FypmError, FypmErrorKind},
+    },
+    DB_PATH,
+};
+
+/// The name that means "no instance": the data of the taskrc and the usual Timewarrior database.
+pub const DEFAULT_INSTANCE: &str = "default";
+
+pub struct InstanceHandler;
+
+impl InstanceHandler {
+    pub fn get_instances(conn: &mut SqliteConnection) -> QueryResult<Vec<Instance>> {
+        instances::dsl::instances.load(conn)
+    }
+    /// The name of the instance chosen with `instance switch`, if there is one.
+    pub fn get_current_name() -> Result<Option<String>, FypmError> {
+        Ok(ConfigHandler::get_settings()?
+            .remove("instance.current")
+            .filter(|name| !name.is_empty() && name != DEFAULT_INSTANCE))
+    }
+    /// The instance to use: the one of `--instance`, or the current one.
+    pub fn select(
+        conn: &mut SqliteConnection,
+        flag: &Option<String>,
+    ) -> Result<Option<Instance>, FypmError> {
+        let name = match flag {
+            Some(name) if name == DEFAULT_INSTANCE => return Ok(None),
+            Some(name) => name.clone(),
+            None => match InstanceHandler::get_current_name()? {
+                Some(name) => name,
+                None => return Ok(None),
+            },
+        };
+
+        let instances = InstanceHandler::get_instances(conn).map_err(FypmError::database)?;
+
+        Ok(Some(InstanceHandler::find(&name, &instances)?.clone()))
+    }
+    /// Where the fypm data of the tasks of an instance is saved (see `DBHandler::instance_path`).
+    /// It follows the id, so it stays in the same place when the instance is renamed.
+    pub fn data_path(base: &Path, instance: &Instance) -> PathBuf {
+        base.join("instances").join(".fypm").join(&instance.id)
+    }
+    /// Make every `task` and `timew` run by fypm (and its children) use the data and the taskrc
+    /// of the instance, and fypm use its own journal, task history and aliases.
+    ///
+    /// The taskrc is mounted by `ConfigHandler::handle_config`, so the context of the worktime
+    /// applied in the instance doesn't leak into the others.
+    pub fn apply(instance: &Instance) -> Result<(), FypmError> {
+        let data_path = InstanceHandler::data_path(Path::new(DB_PATH.as_str()), instance);
+        DBHandler::ensure_instance_db(&data_path)?;
+
+        let taskrc = data_path.join("taskrc");
+        if !taskrc.is_file() {
+            fs::write(&taskrc, "").map_err(|error| FypmError {
+                message: format!("Failed to create {}: {}", taskrc.display(), error),
+                kind: FypmErrorKind::BackendError,
+            })?;
+        }
+
+        env::set_var("TASKRC", &taskrc);
+        env::set_var("TASKDATA", &instance.taskdata);
+        env::set_var("TIMEWARRIORDB", &instance.timewarriordb);
+        env::set_var(INSTANCE_PATH_VAR, &data_path);
+
+        Ok(())
+    }
+
+    fn find<'a>(name: &str, instances: &'a [Instance]) -> Result<&'a Instance, FypmError> {
+        instances
+            .iter()
+            .find(|instance| instance.name == name)
+            .ok_or(FypmError {
+                message: format!("The instance {} doesn't exist!", name),
+                kind: FypmErrorKind::NotFound,
+            })
+    }
+    fn verify_name(name: &str, instances: &[Instance]) -> Result<(), FypmError> {
+        if name.is_empty()
+            || name == DEFAULT_INSTANCE
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(FypmError {
+                message: format!(
+                    "{} is not a valid name! Use only letters, numbers, '_' and '-' (and not '{}').",
+                    name, DEFAULT_INSTANCE
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+        if instances.iter().any(|instance| instance.name == name) {
+            return Err(FypmError {
+                message: format!(
+                    "Instance with name '{}' already exists! Choose a different name.",
+                    name
+                ),
+                kind: FypmErrorKind::AlreadyExists,
+            });
+        }
+
+        Ok(())
+    }
+    /// Create the directory (if needed) and return its absolute path.
+    fn prepare_dir(path: &str) -> Result<String, FypmError> {
+        let path = PathBuf::from(path);
+
+        fs::create_dir_all(&path).map_err(|error| FypmError {
+            message: format!("Failed to create {}: {}", path.display(), error),
+            kind: FypmErrorKind::InvalidInput,
+        })?;
+
+        Ok(path.canonicalize().unwrap().to_string_lossy().into_owned())
+    }
+    /// Like `prepare_dir`, with the `data` directory inside: Timewarrior asks before creating a
+    /// database, but not when it already exists.
+    fn prepare_timewarriordb(path: &str) -> Result<String, FypmError> {
+        let path = InstanceHandler::prepare_dir(path)?;
+        InstanceHandler::prepare_dir(&Path::new(&path).join("data").to_string_lossy())?;
+
+        Ok(path)
+    }
+    /// Where `instance add` puts the data of an instance when no path is given.
+    pub fn default_paths(base: &Path, name: &str) -> (String, String) {
+        let instance_path = base.join("instances").join(name);
+
+        (
+            instance_path.join("task").to_string_lossy().into_owned(),
+            instance_path.join("timew").to_string_lossy().into_owned(),
+        )
+    }
+
+    pub fn add_instance(
+        conn: &mut SqliteConnection,
+        name: &str,
+        description: &str,
+        taskdata: &str,
+        timewarriordb: &str,
+    ) -> Result<Instance, FypmError> {
+        let instances = InstanceHandler::get_instances(conn).map_err(FypmError::database)?;
+        InstanceHandler::verify_name(name, &instances)?;
+
+        let instance = Instance {
+            id: uuid::Uuid::now_v7().to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            taskdata: InstanceHandler::prepare_dir(taskdata)?,
+            timewarriordb: InstanceHandler::prepare_timewarriordb(timewarriordb)?,
+        };
+
+        diesel::insert_into(instances::table)
+            .values(&instance)
+            .execute(conn)
+            .map_err(FypmError::database)?;
+
+        Ok(instance)
+    }
+    /// Change the name, the description or the paths. The data isn't moved.
+    pub fn edit_instance(
+        conn: &mut SqliteConnection,
+        name: &str,
+        new_name: &Option<String>,
+        fields: &InstanceArgs,
+    ) -> Result<Instance, FypmError> {
+        let instances = InstanceHandler::get_instances(conn).map_err(FypmError::database)?;
+        let mut instance = InstanceHandler::find(name, &instances)?.clone();
+
+        if new_name.is_none()
+            && fields.description.is_none()
+            && fields.taskdata.is_none()
+            && fields.timewarriordb.is_none()
+        {
+            return Err(FypmError {
+                message: "You must write something to edit!".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        if let Some(new_name) = new_name {
+            let other_instances: Vec<Instance> = instances
+                .iter()
+                .filter(|other| other.id != instance.id)
+                .cloned()
+                .collect();
+            InstanceHandler::verify_name(new_name, &other_instances)?;
+
+            instance.name = new_name.clone();
+        }
+        if let Some(description) = &fields.description {
+            instance.description = description.clone();
+        }
+        if let Some(taskdata) = &fields.taskdata {
+            instance.taskdata = InstanceHandler::prepare_dir(taskdata)?;
+        }
+        if let Some(timewarriordb) = &fields.timewarriordb {
+            instance.timewarriordb = InstanceHandler::prepare_timewarriordb(timewarriordb)?;
+        }
+
+        diesel::update(instances::table)
+            .filter(instances::id.eq(&instance.id))
+            .set((
+                instances::name.eq(&instance.name),
+                instances::description.eq(&instance.description),
+                instances::taskdata.eq(&instance.taskdata),
+                instances::timewarriordb.eq(&instance.timewarriordb),
+            ))
+            .execute(conn)
+            .map_err(FypmError::database)?;
+
+        Ok(instance)
+    }
+    /// Remove an instance that isn't the current one. Its data is kept.
+    pub fn remove_instance(
+        conn: &mut SqliteConnection,
+        name: &str,
+        current: &Option<String>,
+    ) -> Result<Instance, FypmError> {
+        let instances = InstanceHandler::get_instances(conn).map_err(FypmError::database)?;
+        let instance = InstanceHandler::find(name, &instances)?.clone();
+
+        if current.as_deref() == Some(name) {
+            return Err(FypmError {
+                message: format!(
+                    "The instance {} is the current one! Switch to another first.",
+                    name
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        diesel::delete(instances::table)
+            .filter(instances::id.eq(&instance.id))
+            .execute(conn)
+            .map_err(FypmError::database)?;
+
+        Ok(instance)
+    }
+
+    fn get_name(args: &[String], usage: &str) -> Result<String, FypmError> {
+        match args {
+            [name] => Ok(name.clone()),
+            _ => Err(FypmError {
+                message: format!("Usage: instance {}", usage),
+                kind: FypmErrorKind::InvalidInput,
+            }),
+        }
+    }
+    /// `instance add <name> [--description] [--taskdata] [--timewarriordb]`.
+    pub fn add(
+        conn: &mut SqliteConnection,
+        args: &[String],
+        fields: &InstanceArgs,
+    ) -> Result<(), FypmError> {
+        let name = InstanceHandler::get_name(args, "add <name>")?;
+        let (default_taskdata, default_timewarriordb) =
+            InstanceHandler::default_paths(Path::new(DB_PATH.as_str()), &name);
+
+        let instance = InstanceHandler::add_instance(
+            conn,
+            &name,
+            fields.description.as_deref().unwrap_or_default(),
+            fields.taskdata.as_ref().unwrap_or(&default_taskdata),
+            fields
+                .timewarriordb
+                .as_ref()
+                .unwrap_or(&default_timewarriordb),
+        )?;
+        println!(
+            "Instance {} added! Use it with `fypm instance switch {}` or `--instance {}`.",
+            instance.name, instance.name, instance.name
+        );
+
+        Ok(())
+    }
+    /// `instance edit <name> [--name] [--description] [--taskdata] [--timewarriordb]`.
+    pub fn edit(
+        conn: &mut SqliteConnection,
+        args: &[String],
+        new_name: &Option<String>,
+        fields: &InstanceArgs,
+    ) -> Result<(), FypmError> {
+        let name = InstanceHandler::get_name(args, "edit <name>")?;
+
+        let instance = InstanceHandler::edit_instance(conn, &name, new_name, fields)?;
+        if new_name.is_some() && InstanceHandler::get_current_name()? == Some(name) {
+            ConfigHandler::set_setting("instance.current", &instance.name)?;
+        }
+        println!("Instance {} edited!", instance.name);
+
+        Ok(())
+    }
+    /// `instance remove <name>`.
+    pub fn remove(conn: &mut SqliteConnection, args: &[String]) -> Result<(), FypmError> {
+        let name = InstanceHandler::get_name(args, "remove <name>")?;
+
+        let instance =
+            InstanceHandler::remove_instance(conn, &name, &InstanceHandler::get_current_name()?)?;
+        println!(
+            "Instance {} removed! Its data is still in {}, {} and {}.",
+            instance.name,
+            instance.taskdata,
+            instance.timewarriordb,
+            InstanceHandler::data_path(Path::new(DB_PATH.as_str()), &instance).display()
+        );
+
+        Ok(())
+    }
+    /// `instance switch <name>`. `default` goes back to the usual data.
+    pub fn switch(conn: &mut SqliteConnection, args: &[String]) -> Result<(), FypmError> {
+        let name = InstanceHandler::get_name(args, "switch <name>")?;
+
+        if name != DEFAULT_INSTANCE {
+            InstanceHandler::find(
+                &name,
+                &InstanceHandler::get_instances(conn).map_err(FypmError::database)?,
+            )?;
+        }
+
+        ConfigHandler::set_setting("instance.current", &name)?;
+        println!("Using the instance {} now!", name);
+
+        Ok(())
+    }
+    pub fn list(
+        conn: &mut SqliteConnection,
+        output_format: &OutputFormat,
+    ) -> Result<(), FypmError> {
+        let instances = InstanceHandler::get_instances(conn).map_err(FypmError::database)?;
+
+        if output::print_records(output_format, &instances)? {
+            return Ok(());
+        }
+
+        let current = InstanceHandler::get_current_name()?;
+        let marker = |name: Option<&str>| match current.as_deref() == name {
+            true => "*".bright_green().bold(),
+            false => " ".normal(),
+        };
+
+        println!(
+            "{}",
+            "These are the current instances:".bright_white().bold()
+        );
+        println!(
+            "{} {} -> the usual data",
+            marker(None),
+            DEFAULT_INSTANCE.bright_white()
+        );
+        for instance in instances {
+            println!(
+                "{} {} -> {}",
+                marker(Some(&instance.name)),
+                instance.name.bright_white(),
+                instance.description
+            );
+            println!(
+                "    {} {}",
+                "TASKDATA:".bright_black(),
+                instance.taskdata.bright_black()
+            );
+            println!(
+                "    {} {}",
+                "TIMEWARRIORDB:".bright_black(),
+                instance.timewarriordb.bright_black()
+            );
+        }
+
+        Ok(())
+    }
+}
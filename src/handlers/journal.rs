@@ -0,0 +1,214 @@
+use colored::Colorize;
+use dialoguer::Confirm;
+use diesel::{ExpressionMethods, QueryDsl, QueryResult, RunQueryDsl, SqliteConnection};
+use itertools::Itertools;
+use serde_json::Value;
+
+use crate::{
+    backends,
+    db::{models::JournalEntry, schema::journal},
+    values::err::{FypmError, FypmErrorKind},
+};
+
+/// How many operations are kept, so they can be undone.
+pub const JOURNAL_LIMIT: i64 = 100;
+
+pub struct JournalHandler;
+
+impl JournalHandler {
+    pub fn record(conn: &mut SqliteConnection, entry: &JournalEntry) -> QueryResult<usize> {
+        diesel::insert_into(journal::table)
+            .values(entry.clone())
+            .execute(conn)
+    }
+    pub fn update_after(
+        conn: &mut SqliteConnection,
+        operation_id: &String,
+        task_uuid: &String,
+        after: &Option<String>,
+    ) -> QueryResult<usize> {
+        diesel::update(journal::table)
+            .filter(journal::operation_id.eq(operation_id))
+            .filter(journal::task_uuid.eq(task_uuid))
+            .set(journal::after.eq(after))
+            .execute(conn)
+    }
+    /// Get the last `limit` recorded operations, from the newest to the oldest.
+    /// The entries of each operation are in the order that the tasks were touched.
+    pub fn get_operations(
+        conn: &mut SqliteConnection,
+        limit: usize,
+    ) -> QueryResult<Vec<Vec<JournalEntry>>> {
+        let operation_ids: Vec<String> = journal::table
+            .select(journal::operation_id)
+            .distinct()
+            .order(journal::operation_id.desc())
+            .limit(limit as i64)
+            .load(conn)?;
+
+        let entries: Vec<JournalEntry> = journal::table
+            .filter(journal::operation_id.eq_any(operation_ids))
+            .order((journal::operation_id.desc(), journal::id.asc()))
+            .load(conn)?;
+
+        Ok(entries
+            .into_iter()
+            .chunk_by(|entry| entry.operation_id.clone())
+            .into_iter()
+            .map(|(_, operation)| operation.collect())
+            .collect())
+    }
+    /// Forget the operations older than the last `JOURNAL_LIMIT`.
+    pub fn prune(conn: &mut SqliteConnection) -> QueryResult<usize> {
+        let forgotten: Vec<String> = journal::table
+            .select(journal::operation_id)
+            .distinct()
+            .order(journal::operation_id.desc())
+            .offset(JOURNAL_LIMIT)
+            .load(conn)?;
+
+        diesel::delete(journal::table)
+            .filter(journal::operation_id.eq_any(forgotten))
+            .execute(conn)
+    }
+    pub fn remove_operation(
+        conn: &mut SqliteConnection,
+        operation_id: &String,
+    ) -> QueryResult<usize> {
+        diesel::delete(journal::table)
+            .filter(journal::operation_id.eq(operation_id))
+            .execute(conn)
+    }
+
+    pub fn history(conn: &mut SqliteConnection, limit: &usize) -> Result<(), FypmError> {
+        let operations =
+            JournalHandler::get_operations(conn, *limit).map_err(FypmError::database)?;
+
+        if operations.is_empty() {
+            println!("There are no operations to undo.");
+            return Ok(());
+        }
+
+        println!("{}", "These are the last operations:".bright_white().bold());
+        for (index, operation) in operations.iter().enumerate() {
+            Self::print_operation(index + 1, operation);
+        }
+
+        Ok(())
+    }
+    /// Restore the tasks touched by the last `quantity` operations, from the newest to the oldest.
+    pub fn undo(
+        conn: &mut SqliteConnection,
+        quantity: &usize,
+        skip_confirmation: &bool,
+    ) -> Result<(), FypmError> {
+        let operations =
+            JournalHandler::get_operations(conn, *quantity).map_err(FypmError::database)?;
+
+        if *quantity == 0 || operations.len() < *quantity {
+            return Err(FypmError {
+                message: format!("There are only {} operations to undo!", operations.len()),
+                kind: FypmErrorKind::NotEnoughTasks,
+            });
+        }
+
+        let task_backend = backends::task::current();
+
+        println!(
+            "{}",
+            "These operations will be undone:".bright_white().bold()
+        );
+        for (index, operation) in operations.iter().enumerate() {
+            Self::print_operation(index + 1, operation);
+        }
+
+        // Changes made outside fypm after the operation will be lost too.
+        let touched_uuids = operations
+            .iter()
+            .flatten()
+            .map(|entry| entry.task_uuid.clone())
+            .unique()
+            .collect::<Vec<String>>();
+        let current_tasks = task_backend.export_json(&touched_uuids.join(" "))?;
+
+        for operation in &operations {
+            for entry in operation {
+                let current = current_tasks
+                    .iter()
+                    .find(|task| task["uuid"].as_str() == Some(entry.task_uuid.as_str()))
+                    .map(|task| task["modified"].clone());
+                let after = entry
+                    .after
+                    .as_ref()
+                    .and_then(|after| serde_json::from_str::<Value>(after).ok())
+                    .map(|task| task["modified"].clone());
+
+                if current != after {
+                    println!(
+                        "{} {} was modified after \"{}\"!",
+                        "Warning:".bright_yellow().bold(),
+                        entry.task_uuid,
+                        entry.command
+                    );
+                }
+            }
+        }
+
+        if !*skip_confirmation {
+            let confirmation = Confirm::new()
+                .with_prompt("Do you want to continue?")
+                .interact()
+                .unwrap();
+
+            if !confirmation {
+                return Err(FypmError {
+                    message: "Aborted".to_string(),
+                    kind: FypmErrorKind::Aborted,
+                });
+            }
+        }
+
+        for operation in &operations {
+            let mut restored_tasks = Vec::new();
+
+            for entry in operation {
+                if let Some(before) = &entry.before {
+                    restored_tasks.push(serde_json::from_str::<Value>(before).map_err(|_| {
+                        FypmError {
+                            message: format!("The journal of {} is corrupted!", entry.task_uuid),
+                            kind: FypmErrorKind::ProblemWithStoredTask,
+                        }
+                    })?);
+                } else if task_backend.count(&format!("{} -DELETED", entry.task_uuid))? > 0 {
+                    // The task was created by the operation.
+                    task_backend.delete(&entry.task_uuid)?;
+                }
+            }
+
+            if !restored_tasks.is_empty() {
+                task_backend.import(&restored_tasks)?;
+            }
+
+            JournalHandler::remove_operation(conn, &operation[0].operation_id)
+                .map_err(FypmError::database)?;
+
+            println!(
+                "Undone \"{}\" ({} tasks)",
+                operation[0].command,
+                operation.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn print_operation(index: usize, operation: &[JournalEntry]) {
+        println!(
+            "{:>4}  {}  {} {}",
+            index.to_string().bright_white().bold(),
+            operation[0].created_at.bright_black(),
+            operation[0].command,
+            format!("({} tasks)", operation.len()).bright_black()
+        );
+    }
+}
@@ -3,4 +3,10 @@ pub mod config;
 pub mod database;
 pub mod date;
 pub mod filters;
+pub mod hooks;
+pub mod instance;
+pub mod journal;
+pub mod special_aliases;
+pub mod status;
+pub mod task_history;
 pub mod worktime;
\ No newline at end of file
@@ -0,0 +1,251 @@
+use colored::Colorize;
+use dialoguer::Input;
+use diesel::{
+    ExpressionMethods, OptionalExtension, QueryDsl, QueryResult, RunQueryDsl, SqliteConnection,
+};
+
+use crate::{
+    db::{models::Alias, schema::aliases},
+    func::{
+        action::{verify_if_is_divisory, verify_if_wt_is_allday},
+        parser,
+    },
+    handlers::task_history::TaskHistoryHandler,
+    utils::{get, output},
+    values::{
+        constants::DEFAULT_GET_JSON_OPTIONS,
+        enums::OutputFormat,
+        err::{FypmError, FypmErrorKind},
+        structs::{TaskWarriorExported, TaskWarriorStatus},
+    },
+};
+
+/// Aliases resolved by the parser itself, that can't be saved (with `last`, `last2`...).
+pub const RESERVED_ALIASES: [&str; 1] = ["t"];
+
+pub struct SpecialAliasesHandler;
+
+impl SpecialAliasesHandler {
+    pub fn get_aliases(conn: &mut SqliteConnection) -> QueryResult<Vec<Alias>> {
+        aliases::dsl::aliases.load(conn)
+    }
+    /// The uuid of the task of an alias, if it exists.
+    pub fn resolve(conn: &mut SqliteConnection, name: &str) -> QueryResult<Option<String>> {
+        aliases::table
+            .filter(aliases::name.eq(name))
+            .select(aliases::task_uuid)
+            .first(conn)
+            .optional()
+    }
+    /// If `name` can be an alias. Ids (ex: `12`) and uuids can't, so they still reach the tasks.
+    pub fn is_alias_like(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            && !name.chars().all(|c| c.is_ascii_digit())
+            && uuid::Uuid::parse_str(name).is_err()
+    }
+
+    fn find<'a>(name: &str, aliases: &'a [Alias]) -> Result<&'a Alias, FypmError> {
+        aliases
+            .iter()
+            .find(|alias| alias.name == name)
+            .ok_or(FypmError {
+                message: format!("The alias {} doesn't exist!", name),
+                kind: FypmErrorKind::NotFound,
+            })
+    }
+    fn verify_name(name: &str, aliases: &[Alias]) -> Result<(), FypmError> {
+        if !SpecialAliasesHandler::is_alias_like(name) {
+            return Err(FypmError {
+                message: format!(
+                    "{} is not a valid alias! Use only letters, numbers, '_' and '-' (not an id or uuid).",
+                    name
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+        if RESERVED_ALIASES.contains(&name) || TaskHistoryHandler::parse_last(name).is_some() {
+            return Err(FypmError {
+                message: format!("{} is reserved by fypm! Choose a different name.", name),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+        if aliases.iter().any(|alias| alias.name == name) {
+            return Err(FypmError {
+                message: format!(
+                    "Alias with name '{}' already exists! Choose a different name.",
+                    name
+                ),
+                kind: FypmErrorKind::AlreadyExists,
+            });
+        }
+
+        Ok(())
+    }
+    /// The only task of the filter, if it can be started (pending, not AllDay and not a Divisory).
+    pub fn get_startable_task(filter: &str) -> Result<TaskWarriorExported, FypmError> {
+        let filter = parser::expand_saved_filters(filter)?;
+
+        let task = get::json_by_filter(&filter, DEFAULT_GET_JSON_OPTIONS)?.remove(0);
+
+        if task.status != TaskWarriorStatus::Pending {
+            return Err(FypmError {
+                message: format!(
+                    "The task {} can't be started, because it isn't pending!",
+                    task.uuid
+                ),
+                kind: FypmErrorKind::TaskTypeError,
+            });
+        }
+        verify_if_wt_is_allday(&task)
+            .and_then(|_| verify_if_is_divisory(&task))
+            .map_err(|error| FypmError {
+                message: error.to_string(),
+                kind: FypmErrorKind::TaskTypeError,
+            })?;
+
+        Ok(task)
+    }
+
+    /// Save an alias to the task of the filter. Without description, the task one is used.
+    pub fn add_alias(
+        conn: &mut SqliteConnection,
+        name: &str,
+        filter: &str,
+        description: &Option<String>,
+    ) -> Result<(), FypmError> {
+        let aliases = SpecialAliasesHandler::get_aliases(conn).map_err(FypmError::database)?;
+        SpecialAliasesHandler::verify_name(name, &aliases)?;
+
+        let task = SpecialAliasesHandler::get_startable_task(filter)?;
+
+        diesel::insert_into(aliases::table)
+            .values(Alias {
+                id: uuid::Uuid::now_v7().to_string(),
+                name: name.to_string(),
+                task_uuid: task.uuid,
+                description: Some(description.clone().unwrap_or(task.description)),
+            })
+            .execute(conn)
+            .map_err(FypmError::database)?;
+
+        Ok(())
+    }
+    pub fn remove_alias(conn: &mut SqliteConnection, name: &str) -> Result<(), FypmError> {
+        let aliases = SpecialAliasesHandler::get_aliases(conn).map_err(FypmError::database)?;
+        SpecialAliasesHandler::find(name, &aliases)?;
+
+        diesel::delete(aliases::table)
+            .filter(aliases::name.eq(name))
+            .execute(conn)
+            .map_err(FypmError::database)?;
+
+        Ok(())
+    }
+    pub fn rename_alias(
+        conn: &mut SqliteConnection,
+        name: &str,
+        new_name: &str,
+    ) -> Result<(), FypmError> {
+        let aliases = SpecialAliasesHandler::get_aliases(conn).map_err(FypmError::database)?;
+        let alias = SpecialAliasesHandler::find(name, &aliases)?;
+        SpecialAliasesHandler::verify_name(new_name, &aliases)?;
+
+        diesel::update(aliases::table)
+            .filter(aliases::id.eq(&alias.id))
+            .set(aliases::name.eq(new_name))
+            .execute(conn)
+            .map_err(FypmError::database)?;
+
+        Ok(())
+    }
+
+    /// `alias add <name> <filter...>`. Without arguments, they are asked.
+    pub fn add(
+        conn: &mut SqliteConnection,
+        args: &[String],
+        description: &Option<String>,
+    ) -> Result<(), FypmError> {
+        let (name, filter) = match args.split_first() {
+            Some((name, filter)) if !filter.is_empty() => (name.clone(), filter.join(" ")),
+            Some(_) => {
+                return Err(FypmError {
+                    message: "Usage: alias add <name> <filter...>".to_string(),
+                    kind: FypmErrorKind::InvalidInput,
+                })
+            }
+            None => (
+                Input::<String>::new()
+                    .with_prompt("Write a name for your alias")
+                    .interact_text()
+                    .unwrap()
+                    .trim()
+                    .to_string(),
+                Input::<String>::new()
+                    .with_prompt("Write a filter to the task of the alias")
+                    .interact_text()
+                    .unwrap(),
+            ),
+        };
+
+        SpecialAliasesHandler::add_alias(conn, &name, &filter, description)?;
+        println!("Alias {} added!", name);
+
+        Ok(())
+    }
+    /// `alias remove <name>`.
+    pub fn remove(conn: &mut SqliteConnection, args: &[String]) -> Result<(), FypmError> {
+        let [name] = args else {
+            return Err(FypmError {
+                message: "Usage: alias remove <name>".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        };
+
+        SpecialAliasesHandler::remove_alias(conn, name)?;
+        println!("Alias {} removed!", name);
+
+        Ok(())
+    }
+    /// `alias rename <name> <new name>`.
+    pub fn rename(conn: &mut SqliteConnection, args: &[String]) -> Result<(), FypmError> {
+        let [name, new_name] = args else {
+            return Err(FypmError {
+                message: "Usage: alias rename <name> <new name>".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        };
+
+        SpecialAliasesHandler::rename_alias(conn, name, new_name)?;
+        println!("Alias {} renamed to {}!", name, new_name);
+
+        Ok(())
+    }
+    pub fn list(
+        conn: &mut SqliteConnection,
+        output_format: &OutputFormat,
+    ) -> Result<(), FypmError> {
+        let aliases = SpecialAliasesHandler::get_aliases(conn).map_err(FypmError::database)?;
+
+        if output::print_records(output_format, &aliases)? {
+            return Ok(());
+        }
+
+        println!("{}", "These are the current aliases:".bright_white().bold());
+        for alias in aliases {
+            println!(
+                "-   {} -> {}",
+                alias.name.bright_white(),
+                alias.task_uuid.bright_black()
+            );
+
+            if let Some(description) = &alias.description {
+                println!("    {}", description);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+//#region           Crates
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, Utc};
+use diesel::{Connection, SqliteConnection};
+use serde_json::json;
+
+//#endregion
+//#region           Modules
+use crate::backends;
+use crate::db::models::Worktime;
+use crate::func::list::day_completion_score;
+use crate::handlers::config::ConfigHandler;
+use crate::handlers::worktime::WorktimeHandler;
+use crate::values::enums::StatusFormat;
+use crate::values::err::{FypmError, FypmErrorKind};
+use crate::values::structs::{CompletionScore, StatusInfo, TaskWarriorExported};
+use crate::DATABASE_URL;
+
+//#endregion
+//#region           Structs
+/// Somewhere the status is shown, like a status bar.
+pub trait StatusSink {
+    /// The status in the format of the sink.
+    fn render(&self, status: &StatusInfo) -> String;
+    /// Publish the status, so the bar shows it.
+    fn write(&self, status: &StatusInfo) -> Result<(), FypmError>;
+}
+
+/// Files read by the polybar modules, as `wt-apply` always wrote them.
+pub struct PolybarSink {
+    pub directory: PathBuf,
+}
+pub struct WaybarSink {
+    pub path: PathBuf,
+}
+pub struct I3blocksSink {
+    pub path: PathBuf,
+}
+pub struct PlainSink {
+    pub path: PathBuf,
+}
+pub struct JsonSink {
+    pub path: PathBuf,
+}
+
+pub struct StatusHandler;
+//#endregion
+//#region           Implementation
+fn write_file(path: &Path, content: &str) -> Result<(), FypmError> {
+    fs::write(path, content).map_err(|error| FypmError {
+        message: format!(
+            "Failed to write the status to {}: {}",
+            path.display(),
+            error
+        ),
+        kind: FypmErrorKind::BackendError,
+    })
+}
+/// Ex: `1h05m`, `25m`.
+pub fn format_elapsed(minutes: i64) -> String {
+    if minutes >= 60 {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+fn plain_text(status: &StatusInfo) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(worktime) = &status.worktime {
+        parts.push(worktime.clone());
+    }
+    if let Some(task) = &status.task {
+        match status.elapsed_minutes {
+            Some(minutes) => parts.push(format!("{} ({})", task, format_elapsed(minutes))),
+            None => parts.push(task.clone()),
+        }
+    }
+    parts.push(format!("{}/{}", status.completed_today, status.total_today));
+
+    parts.join(" | ")
+}
+
+impl StatusSink for PolybarSink {
+    fn render(&self, status: &StatusInfo) -> String {
+        match (&status.worktime, &status.worktime_end) {
+            (Some(worktime), Some(end)) => format!("{} -> {}", worktime, end),
+            (Some(worktime), None) => worktime.clone(),
+            _ => String::new(),
+        }
+    }
+    fn write(&self, status: &StatusInfo) -> Result<(), FypmError> {
+        let current_wt = self.render(status);
+
+        write_file(&self.directory.join("current_work_time"), &current_wt)?;
+        write_file(
+            &self.directory.join("current_polybar_b_wt_color"),
+            status.worktime_background.as_deref().unwrap_or_default(),
+        )?;
+        write_file(
+            &self.directory.join("current_polybar_f_wt_color"),
+            status.worktime_foreground.as_deref().unwrap_or_default(),
+        )?;
+        write_file(&self.directory.join(".last_work_time"), &current_wt)?;
+
+        // Polybar may not be running, it's not a problem.
+        Command::new("polybar-msg")
+            .args(["cmd", "restart"])
+            .output()
+            .ok();
+
+        Ok(())
+    }
+}
+impl StatusSink for WaybarSink {
+    fn render(&self, status: &StatusInfo) -> String {
+        let percentage = (status.completed_today * 100)
+            .checked_div(status.total_today)
+            .unwrap_or(0);
+
+        json!({
+            "text": plain_text(status),
+            "tooltip": format!(
+                "Worktime: {}\nTask: {}\nScore: {}/{}",
+                status.worktime.as_deref().unwrap_or("None"),
+                status.task.as_deref().unwrap_or("None"),
+                status.completed_today,
+                status.total_today
+            ),
+            "class": status.worktime.as_deref().unwrap_or("none").to_lowercase(),
+            "percentage": percentage,
+        })
+        .to_string()
+    }
+    fn write(&self, status: &StatusInfo) -> Result<(), FypmError> {
+        write_file(&self.path, &self.render(status))
+    }
+}
+impl StatusSink for I3blocksSink {
+    fn render(&self, status: &StatusInfo) -> String {
+        let short_text = status
+            .task
+            .as_ref()
+            .or(status.worktime.as_ref())
+            .cloned()
+            .unwrap_or_default();
+
+        format!(
+            "{}\n{}\n{}",
+            plain_text(status),
+            short_text,
+            status.worktime_foreground.as_deref().unwrap_or_default()
+        )
+    }
+    fn write(&self, status: &StatusInfo) -> Result<(), FypmError> {
+        write_file(&self.path, &self.render(status))
+    }
+}
+impl StatusSink for PlainSink {
+    fn render(&self, status: &StatusInfo) -> String {
+        plain_text(status)
+    }
+    fn write(&self, status: &StatusInfo) -> Result<(), FypmError> {
+        write_file(&self.path, &self.render(status))
+    }
+}
+impl StatusSink for JsonSink {
+    fn render(&self, status: &StatusInfo) -> String {
+        serde_json::to_string_pretty(status).unwrap()
+    }
+    fn write(&self, status: &StatusInfo) -> Result<(), FypmError> {
+        write_file(&self.path, &self.render(status))
+    }
+}
+
+impl StatusHandler {
+    pub fn get_sink(
+        format: &StatusFormat,
+        settings: &BTreeMap<String, String>,
+    ) -> Box<dyn StatusSink> {
+        let path = PathBuf::from(
+            settings
+                .get(&format!("status.{}.path", format))
+                .cloned()
+                .unwrap_or_default(),
+        );
+
+        match format {
+            StatusFormat::Plain => Box::new(PlainSink { path }),
+            StatusFormat::Waybar => Box::new(WaybarSink { path }),
+            StatusFormat::I3blocks => Box::new(I3blocksSink { path }),
+            StatusFormat::Json => Box::new(JsonSink { path }),
+            StatusFormat::Polybar => Box::new(PolybarSink { directory: path }),
+        }
+    }
+    /// The sinks of `status.sinks`.
+    pub fn get_sinks(
+        settings: &BTreeMap<String, String>,
+    ) -> Result<Vec<Box<dyn StatusSink>>, FypmError> {
+        settings
+            .get("status.sinks")
+            .map(|sinks| sinks.as_str())
+            .unwrap_or_default()
+            .split(',')
+            .map(|sink| sink.trim())
+            .filter(|sink| !sink.is_empty())
+            .map(|sink| {
+                StatusFormat::from_str(sink)
+                    .map(|format| Self::get_sink(&format, settings))
+                    .map_err(|_| FypmError {
+                        message: format!("Unknown status sink in settings config: {}", sink),
+                        kind: FypmErrorKind::InvalidConfig,
+                    })
+            })
+            .collect()
+    }
+
+    pub fn build(
+        worktime: Option<&Worktime>,
+        active_task: Option<&TaskWarriorExported>,
+        score: &CompletionScore,
+        now: &DateTime<Utc>,
+    ) -> StatusInfo {
+        StatusInfo {
+            worktime: worktime.map(|worktime| worktime.name.clone()),
+            worktime_end: worktime.map(|worktime| worktime.end_time.clone()),
+            worktime_background: worktime.map(|worktime| worktime.polybar_background.clone()),
+            worktime_foreground: worktime.map(|worktime| worktime.polybar_foreground.clone()),
+            task: active_task.map(|task| task.description.clone()),
+            task_uuid: active_task.map(|task| task.uuid.clone()),
+            elapsed_minutes: active_task
+                .and_then(|task| task.start)
+                .map(|start| (*now - start).num_minutes()),
+            completed_today: score.completed,
+            total_today: score.total,
+        }
+    }
+    /// The status now: the last applied worktime (or the one of the schedule), the active task
+    /// and the score of today.
+    pub fn gather() -> Result<StatusInfo, FypmError> {
+        let mut conn = SqliteConnection::establish(DATABASE_URL.as_str()).unwrap();
+        let worktimes = WorktimeHandler::get_all(&mut conn)?;
+
+        let worktime = match WorktimeHandler::get_applied() {
+            Some(name) => worktimes.iter().find(|worktime| worktime.name == name),
+            None => WorktimeHandler::get_current(
+                &worktimes,
+                &WorktimeHandler::get_schedules(&mut conn)?,
+                &Local::now().naive_local(),
+            ),
+        };
+        let active_tasks = backends::task::current().export("+ACTIVE", &[])?;
+        let score = day_completion_score(&Local::now().date_naive())?;
+
+        Ok(Self::build(
+            worktime,
+            active_tasks.first(),
+            &score,
+            &Utc::now(),
+        ))
+    }
+    /// Write the status to every sink of the settings.
+    pub fn write_all(status: &StatusInfo) -> Result<(), FypmError> {
+        for sink in Self::get_sinks(&ConfigHandler::get_settings()?)? {
+            sink.write(status)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn status(format: &StatusFormat, write: &bool) -> Result<(), FypmError> {
+        let status = Self::gather()?;
+
+        if *write {
+            return Self::write_all(&status);
+        }
+
+        let settings = ConfigHandler::get_settings()?;
+        println!("{}", Self::get_sink(format, &settings).render(&status));
+
+        Ok(())
+    }
+}
+//#endregion
@@ -0,0 +1,159 @@
+use chrono::Local;
+use colored::Colorize;
+use diesel::{ExpressionMethods, QueryDsl, QueryResult, RunQueryDsl, SqliteConnection};
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    db::{models::TaskHistoryEntry, schema::task_history},
+    utils::{get, output},
+    values::{
+        enums::OutputFormat,
+        err::{FypmError, FypmErrorKind},
+        structs::RecentTaskRecord,
+    },
+};
+
+/// How many starts are kept.
+pub const TASK_HISTORY_LIMIT: i64 = 100;
+/// With the fraction of the second, so the starts are ordered even when they happen in the same
+/// millisecond.
+const STARTED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+lazy_static! {
+    /// The aliases of the previous tasks: `last`, `last2`, `last3`...
+    static ref LAST_ALIAS_REGEX: Regex = Regex::new(r"^last(\d*)$").unwrap();
+}
+
+pub struct TaskHistoryHandler;
+
+impl TaskHistoryHandler {
+    /// Save a start of the task, forgetting the oldest ones after `TASK_HISTORY_LIMIT`.
+    pub fn push(conn: &mut SqliteConnection, task_uuid: &str) -> QueryResult<()> {
+        diesel::insert_into(task_history::table)
+            .values(TaskHistoryEntry {
+                id: uuid::Uuid::now_v7().to_string(),
+                task_uuid: task_uuid.to_string(),
+                started_at: Local::now().format(STARTED_AT_FORMAT).to_string(),
+            })
+            .execute(conn)?;
+
+        let forgotten: Vec<String> = task_history::table
+            .select(task_history::id)
+            .order((task_history::started_at.desc(), task_history::id.desc()))
+            .offset(TASK_HISTORY_LIMIT)
+            .load(conn)?;
+        diesel::delete(task_history::table)
+            .filter(task_history::id.eq_any(forgotten))
+            .execute(conn)?;
+
+        Ok(())
+    }
+    /// The last start of each task, from the most recent.
+    pub fn recent(conn: &mut SqliteConnection) -> QueryResult<Vec<TaskHistoryEntry>> {
+        let entries: Vec<TaskHistoryEntry> = task_history::table
+            .order((task_history::started_at.desc(), task_history::id.desc()))
+            .load(conn)?;
+
+        Ok(entries
+            .into_iter()
+            .unique_by(|entry| entry.task_uuid.clone())
+            .collect())
+    }
+    /// The position of a `last` alias (`last` is 1, `last2` is 2...), if it is one.
+    pub fn parse_last(alias: &str) -> Option<usize> {
+        let captures = LAST_ALIAS_REGEX.captures(alias)?;
+
+        match &captures[1] {
+            "" => Some(1),
+            number => number.parse().ok().filter(|position| *position > 0),
+        }
+    }
+    /// The uuid of the `position`-th recent task that isn't the active one.
+    pub fn get_last(
+        conn: &mut SqliteConnection,
+        position: usize,
+        active_uuid: Option<&str>,
+    ) -> Result<String, FypmError> {
+        TaskHistoryHandler::recent(conn)
+            .unwrap()
+            .into_iter()
+            .filter(|entry| Some(entry.task_uuid.as_str()) != active_uuid)
+            .nth(position - 1)
+            .map(|entry| entry.task_uuid)
+            .ok_or(FypmError {
+                message: format!(
+                    "There is no task #{} in the history! See them with `fypm ta-recent`.",
+                    position
+                ),
+                kind: FypmErrorKind::NotFound,
+            })
+    }
+
+    /// Show the previous tasks with their `last` aliases.
+    pub fn list(
+        conn: &mut SqliteConnection,
+        limit: &usize,
+        active_uuid: Option<&str>,
+        output_format: &OutputFormat,
+    ) -> Result<(), FypmError> {
+        let entries: Vec<TaskHistoryEntry> = TaskHistoryHandler::recent(conn)
+            .unwrap()
+            .into_iter()
+            .filter(|entry| Some(entry.task_uuid.as_str()) != active_uuid)
+            .take(*limit)
+            .collect();
+
+        let tasks = match entries.is_empty() {
+            true => Vec::new(),
+            false => get::json_by_filter(
+                &entries
+                    .iter()
+                    .map(|entry| entry.task_uuid.as_str())
+                    .join(" "),
+                None,
+            )?,
+        };
+
+        let records: Vec<RecentTaskRecord> = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| RecentTaskRecord {
+                alias: match index {
+                    0 => "last".to_string(),
+                    index => format!("last{}", index + 1),
+                },
+                description: tasks
+                    .iter()
+                    .find(|task| task.uuid == entry.task_uuid)
+                    .map(|task| task.description.clone())
+                    .unwrap_or_default(),
+                uuid: entry.task_uuid,
+                started_at: entry.started_at[0..19].to_string(),
+            })
+            .collect();
+
+        if output::print_records(output_format, &records)? {
+            return Ok(());
+        }
+
+        if records.is_empty() {
+            println!("There are no previous tasks.");
+            return Ok(());
+        }
+
+        println!("{}", "These are the previous tasks:".bright_white().bold());
+        for record in records {
+            println!(
+                "{:>6}  {}  {} {}",
+                record.alias.bright_white(),
+                record.started_at.bright_black(),
+                record.description,
+                record.uuid[0..8].bright_black()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1,110 +1,223 @@
-use std::io::Error;
+use std::collections::BTreeMap;
 
-use chrono::NaiveTime;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use colored::Colorize;
 use dialoguer::{console::Term, Input};
-use diesel::{QueryDsl, RunQueryDsl, SqliteConnection, TextExpressionMethods};
+use diesel::{
+    Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection, TextExpressionMethods,
+};
+use itertools::Itertools;
 
 use crate::{
-    db::{models::Worktime, schema::worktimes},
-    utils::verify,
-    values::err::FypmError,
+    db::{
+        models::{Worktime, WorktimeSchedule},
+        schema::{worktime_schedules, worktimes},
+    },
+    handlers::{config::ConfigHandler, database::DBHandler, date::NaiveDateIter},
+    utils::{output, verify},
+    values::{
+        enums::{ImportConflict, OutputFormat, ScheduleDay, WorktimeArgs},
+        err::{FypmError, FypmErrorKind},
+        structs::{
+            HolidayEntry, TrackedInterval, WorktimeEntry, WorktimeReportRecord,
+            WorktimeScheduleRecord, WorktimesFile,
+        },
+    },
 };
 
+/// Name of the report record of the time tracked outside the worktimes.
+pub const OFF_WORKTIME: &str = "OffWorktime";
+
 pub struct WorktimeHandler;
 
 impl WorktimeHandler {
-    pub fn add(conn: &mut SqliteConnection, name: &String) -> Result<(), FypmError> {
-        let date_format = "%H:%M";
-        let term = Term::stdout();
-
-        let description = Input::<String>::new()
-            .with_prompt("Write a description for your worktime")
+    fn parse_time(time: &str) -> Result<NaiveTime, FypmError> {
+        NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| FypmError {
+            message: format!("{} is not a valid time! (format: HH:MM)", time),
+            kind: FypmErrorKind::InvalidInput,
+        })
+    }
+    fn ask(prompt: &str, validate: fn(&String) -> Result<(), FypmError>) -> String {
+        let input = Input::<String>::new()
+            .with_prompt(prompt)
+            .validate_with(|input: &String| -> Result<(), String> {
+                validate(input).map_err(|error| error.message)
+            })
             .interact_text()
             .unwrap();
-        term.clear_last_lines(1).unwrap();
+        Term::stdout().clear_last_lines(1).unwrap();
 
-        let style = Input::<String>::new()
-            .with_prompt("What is the style of your worktime? (make sure the value exists!)")
-            .interact_text()
-            .unwrap();
-        term.clear_last_lines(1).unwrap();
+        input
+    }
+    fn verify_hex(color: &String) -> Result<(), FypmError> {
+        verify::verify_hex(color.to_string())
+            .map(|_| ())
+            .map_err(|error| FypmError {
+                message: format!("{} is not a valid hex color! {}", color, error),
+                kind: FypmErrorKind::InvalidInput,
+            })
+    }
 
-        let start_time = Input::<String>::new()
-            .with_prompt("What time do you want to start this worktime? (format: HH:MM)")
-            .validate_with(|input: &String| -> Result<(), &str> {
-                let time = NaiveTime::parse_from_str(input.as_str(), date_format);
+    /// Check the columns of a worktime. `styles` are the values of the STYLE UDA.
+    pub fn verify(worktime: &Worktime, styles: &[String]) -> Result<(), FypmError> {
+        if worktime.name.trim().is_empty() || worktime.name.contains(char::is_whitespace) {
+            return Err(FypmError {
+                message: "The name of a worktime can't be empty or have spaces!".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+        if !styles.contains(&worktime.style) {
+            return Err(FypmError {
+                message: format!(
+                    "{} is not a style! The styles are: {}",
+                    worktime.style,
+                    styles.join(", ")
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+        if Self::parse_time(&worktime.start_time)? == Self::parse_time(&worktime.end_time)? {
+            return Err(FypmError {
+                message: "A worktime can't start and end at the same time!".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+        Self::verify_hex(&worktime.polybar_background)?;
+        Self::verify_hex(&worktime.polybar_foreground)?;
 
-                match time {
-                    Ok(_) => Ok(()),
-                    Err(_) => Err("Not a valid time!"),
-                }
-            })
-            .interact_text()
-            .unwrap();
-        term.clear_last_lines(1).unwrap();
-
-        let end_time = Input::<String>::new()
-            .with_prompt("What time do you want to end this worktime? (format: HH:MM)")
-            .validate_with(|input: &String| -> Result<(), &str> {
-                let time = NaiveTime::parse_from_str(input.as_str(), date_format);
-
-                match time {
-                    Ok(_) => match time.unwrap()
-                        > NaiveTime::parse_from_str(start_time.as_str(), date_format).unwrap()
-                    {
-                        true => Ok(()),
-                        false => Err("End time must be after start time!"),
-                    },
-                    Err(_) => Err("Not a valid time!"),
-                }
-            })
-            .interact_text()
-            .unwrap();
-        term.clear_last_lines(1).unwrap();
+        Ok(())
+    }
+    /// The minutes of the day in the window of the worktime. A window that crosses midnight is
+    /// split in two.
+    fn get_ranges(worktime: &Worktime) -> Vec<(u32, u32)> {
+        let Some((start, end)) = Self::get_window(worktime) else {
+            return vec![];
+        };
+        let (start, end) = (
+            start.num_seconds_from_midnight() / 60,
+            end.num_seconds_from_midnight() / 60,
+        );
 
-        let polybar_background = Input::<String>::new()
-            .with_prompt(
-                "What color do you want to use for the background of polybar module? (HEX)",
-            )
-            .validate_with(|input: &String| -> Result<(), Error> {
-                let hex = verify::verify_hex(input.to_string());
+        if start < end {
+            vec![(start, end)]
+        } else {
+            vec![(start, 24 * 60), (0, end)]
+        }
+    }
+    /// The other worktimes whose windows overlap the one of `worktime`.
+    pub fn get_overlaps<'a>(worktime: &Worktime, worktimes: &'a [Worktime]) -> Vec<&'a Worktime> {
+        let ranges = Self::get_ranges(worktime);
 
-                match hex {
-                    Ok(_) => Ok(()),
-                    Err(_) => Err(hex.unwrap_err()),
-                }
+        worktimes
+            .iter()
+            .filter(|other| other.id != worktime.id)
+            .filter(|other| {
+                Self::get_ranges(other)
+                    .iter()
+                    .any(|(other_start, other_end)| {
+                        ranges
+                            .iter()
+                            .any(|(start, end)| start < other_end && other_start < end)
+                    })
             })
-            .interact_text()
-            .unwrap();
-        term.clear_last_lines(1).unwrap();
+            .collect()
+    }
+    /// Verify the worktime against the others (the saved ones, or the ones after an import) before
+    /// it's saved.
+    fn verify_with_others(
+        worktime: &Worktime,
+        worktimes: &[Worktime],
+        styles: &[String],
+        allow_overlap: &bool,
+    ) -> Result<(), FypmError> {
+        Self::verify(worktime, styles)?;
 
-        let polybar_foreground = Input::<String>::new()
-            .with_prompt(
-                "What color do you want to use for the foreground of polybar module? (HEX)",
-            )
-            .validate_with(|input: &String| -> Result<(), &str> {
-                let hex = verify::verify_hex(input.to_string());
+        if worktimes.iter().any(|other| {
+            other.id != worktime.id && other.name.to_lowercase() == worktime.name.to_lowercase()
+        }) {
+            return Err(FypmError {
+                message: format!("The worktime {} already exists!", worktime.name),
+                kind: FypmErrorKind::AlreadyExists,
+            });
+        }
 
-                match hex {
-                    Ok(_) => Ok(()),
-                    Err(_) => Err("Not a valid hex color!"),
-                }
-            })
-            .interact_text()
-            .unwrap();
-        term.clear_last_lines(1).unwrap();
+        let overlaps = Self::get_overlaps(worktime, worktimes);
+        if !overlaps.is_empty() && !*allow_overlap {
+            return Err(FypmError {
+                message: format!(
+                    "{} ({} -> {}) overlaps {}! Use --allow-overlap to save it anyway.",
+                    worktime.name,
+                    worktime.start_time,
+                    worktime.end_time,
+                    overlaps
+                        .iter()
+                        .map(|other| {
+                            format!(
+                                "{} ({} -> {})",
+                                other.name, other.start_time, other.end_time
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn add(
+        conn: &mut SqliteConnection,
+        name: &String,
+        fields: &WorktimeArgs,
+    ) -> Result<(), FypmError> {
+        let get_or_ask = |field: &Option<String>,
+                          prompt: &str,
+                          validate: fn(&String) -> Result<(), FypmError>| {
+            field.clone().unwrap_or_else(|| Self::ask(prompt, validate))
+        };
 
         let new_worktime = Worktime {
             id: uuid::Uuid::now_v7().to_string(),
             name: name.to_string(),
-            description,
-            style,
-            start_time,
-            end_time,
-            polybar_background,
-            polybar_foreground,
+            description: get_or_ask(
+                &fields.description,
+                "Write a description for your worktime",
+                |_| Ok(()),
+            ),
+            style: get_or_ask(&fields.style, "What is the style of your worktime?", |_| {
+                Ok(())
+            }),
+            start_time: get_or_ask(
+                &fields.start,
+                "What time do you want to start this worktime? (format: HH:MM)",
+                |input| Self::parse_time(input).map(|_| ()),
+            ),
+            end_time: get_or_ask(
+                &fields.end,
+                "What time do you want to end this worktime? (format: HH:MM)",
+                |input| Self::parse_time(input).map(|_| ()),
+            ),
+            polybar_background: get_or_ask(
+                &fields.background,
+                "What color do you want to use for the background of polybar module? (HEX)",
+                Self::verify_hex,
+            ),
+            polybar_foreground: get_or_ask(
+                &fields.foreground,
+                "What color do you want to use for the foreground of polybar module? (HEX)",
+                Self::verify_hex,
+            ),
         };
 
+        Self::verify_with_others(
+            &new_worktime,
+            &Self::get_all(conn)?,
+            &ConfigHandler::get_uda_values("STYLE")?,
+            &fields.allow_overlap,
+        )?;
+
         diesel::insert_into(worktimes::table)
             .values(&new_worktime)
             .execute(conn)
@@ -112,7 +225,71 @@ impl WorktimeHandler {
 
         Ok(())
     }
+    /// Change the given columns of a worktime. The other ones are kept.
+    pub fn edit(
+        conn: &mut SqliteConnection,
+        name: &String,
+        new_name: &Option<String>,
+        fields: &WorktimeArgs,
+    ) -> Result<(), FypmError> {
+        let old_worktime = Self::get(conn, name)?;
+        let mut worktime = old_worktime.clone();
+
+        let columns = [
+            (new_name, &mut worktime.name),
+            (&fields.description, &mut worktime.description),
+            (&fields.style, &mut worktime.style),
+            (&fields.start, &mut worktime.start_time),
+            (&fields.end, &mut worktime.end_time),
+            (&fields.background, &mut worktime.polybar_background),
+            (&fields.foreground, &mut worktime.polybar_foreground),
+        ];
+        let mut changed = false;
+        for (value, column) in columns {
+            if let Some(value) = value {
+                *column = value.clone();
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return Err(FypmError {
+                message: "Nothing to edit! Pass the columns to change, like --start 08:00."
+                    .to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        Self::verify_with_others(
+            &worktime,
+            &Self::get_all(conn)?,
+            &ConfigHandler::get_uda_values("STYLE")?,
+            &fields.allow_overlap,
+        )?;
+
+        diesel::update(worktimes::table.filter(worktimes::dsl::id.eq(&worktime.id)))
+            .set(&worktime)
+            .execute(conn)
+            .unwrap();
+
+        if Self::get_applied() == Some(old_worktime.name) {
+            Self::set_applied(&worktime.name)?;
+        }
+
+        Ok(())
+    }
     pub fn remove(conn: &mut SqliteConnection, name: &String) -> Result<(), FypmError> {
+        let ids: Vec<String> = worktimes::dsl::worktimes
+            .filter(worktimes::dsl::name.like(name))
+            .select(worktimes::dsl::id)
+            .load(conn)
+            .unwrap();
+
+        diesel::delete(
+            worktime_schedules::table.filter(worktime_schedules::dsl::worktime_id.eq_any(&ids)),
+        )
+        .execute(conn)
+        .unwrap();
         diesel::delete(worktimes::table.filter(worktimes::dsl::name.like(name)))
             .execute(conn)
             .unwrap();
@@ -120,16 +297,728 @@ impl WorktimeHandler {
         Ok(())
     }
     pub fn get(conn: &mut SqliteConnection, name: &String) -> Result<Worktime, FypmError> {
-        let worktime: Worktime = worktimes::dsl::worktimes
+        worktimes::dsl::worktimes
             .filter(worktimes::dsl::name.like(name))
             .first::<Worktime>(conn)
-            .unwrap();
+            .map_err(|error| match error {
+                diesel::result::Error::NotFound => FypmError {
+                    message: format!("The worktime {} doesn't exist!", name),
+                    kind: FypmErrorKind::NotFound,
+                },
+                error => FypmError {
+                    message: format!("Failed to get the worktime {}: {}", name, error),
+                    kind: FypmErrorKind::BackendError,
+                },
+            })
+    }
+    /// Each instance has its own applied worktime, because it's applied to its tasks.
+    fn applied_path() -> std::path::PathBuf {
+        DBHandler::instance_path().join("applied_worktime")
+    }
+    /// Remember the worktime applied by `wt-apply`, so the status can show it.
+    pub fn set_applied(name: &String) -> Result<(), FypmError> {
+        std::fs::write(Self::applied_path(), name).map_err(|error| FypmError {
+            message: format!("Failed to save the applied worktime: {}", error),
+            kind: FypmErrorKind::BackendError,
+        })
+    }
+    pub fn get_applied() -> Option<String> {
+        std::fs::read_to_string(Self::applied_path())
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    }
+    pub fn get_all(conn: &mut SqliteConnection) -> Result<Vec<Worktime>, FypmError> {
+        worktimes::dsl::worktimes
+            .load(conn)
+            .map_err(FypmError::database)
+    }
+    /// Start and end of the worktime, or None if they aren't valid times.
+    fn get_window(worktime: &Worktime) -> Option<(NaiveTime, NaiveTime)> {
+        Some((
+            NaiveTime::parse_from_str(&worktime.start_time, "%H:%M").ok()?,
+            NaiveTime::parse_from_str(&worktime.end_time, "%H:%M").ok()?,
+        ))
+    }
+    /// The worktime whose window contains `time`. Windows that end before they start cross
+    /// midnight. If windows overlap, the one that started last is used.
+    pub fn get_active<'a>(
+        worktimes: impl IntoIterator<Item = &'a Worktime>,
+        time: &NaiveTime,
+    ) -> Option<&'a Worktime> {
+        worktimes
+            .into_iter()
+            .filter_map(|worktime| {
+                let (start, end) = Self::get_window(worktime)?;
+
+                let contains = if start <= end {
+                    start <= *time && *time < end
+                } else {
+                    start <= *time || *time < end
+                };
+                // Started yesterday if the window crossed midnight.
+                let elapsed = (*time - start).num_seconds().rem_euclid(86400);
+
+                contains.then_some((elapsed, worktime))
+            })
+            .min_by_key(|(elapsed, _)| *elapsed)
+            .map(|(_, worktime)| worktime)
+    }
+    /// The first start or end of a worktime after `time`, looking into the next day if needed.
+    pub fn next_boundary(worktimes: &[Worktime], time: &NaiveTime) -> Option<NaiveTime> {
+        worktimes
+            .iter()
+            .flat_map(|worktime| [&worktime.start_time, &worktime.end_time])
+            .filter_map(|boundary| NaiveTime::parse_from_str(boundary, "%H:%M").ok())
+            .min_by_key(|boundary| {
+                let until = (*boundary - *time).num_seconds().rem_euclid(86400);
+
+                if until == 0 {
+                    86400
+                } else {
+                    until
+                }
+            })
+    }
+
+    /// Parse the days of `wt-schedule`: weekdays (mon, monday...), `weekdays`, `weekend` or dates.
+    pub fn parse_schedule_days(args: &[String]) -> Result<Vec<ScheduleDay>, FypmError> {
+        let mut days = Vec::new();
+
+        for arg in args {
+            match arg.to_lowercase().as_str() {
+                "weekdays" => days.extend(
+                    [
+                        Weekday::Mon,
+                        Weekday::Tue,
+                        Weekday::Wed,
+                        Weekday::Thu,
+                        Weekday::Fri,
+                    ]
+                    .map(ScheduleDay::Weekday),
+                ),
+                "weekend" => days.extend([Weekday::Sat, Weekday::Sun].map(ScheduleDay::Weekday)),
+                lowercase => {
+                    if let Ok(weekday) = lowercase.parse::<Weekday>() {
+                        days.push(ScheduleDay::Weekday(weekday));
+                    } else if let Ok(date) = NaiveDate::parse_from_str(lowercase, "%Y-%m-%d") {
+                        days.push(ScheduleDay::Date(date));
+                    } else {
+                        return Err(FypmError {
+                            message: format!(
+                                "{} is not a weekday, weekdays, weekend or a date (YYYY-MM-DD)!",
+                                arg
+                            ),
+                            kind: FypmErrorKind::InvalidInput,
+                        });
+                    }
+                }
+            }
+        }
+
+        if days.is_empty() {
+            return Err(FypmError {
+                message: "You must specify at least one day!".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        Ok(days)
+    }
+    pub fn get_schedules(conn: &mut SqliteConnection) -> Result<Vec<WorktimeSchedule>, FypmError> {
+        worktime_schedules::dsl::worktime_schedules
+            .load(conn)
+            .map_err(FypmError::database)
+    }
+    /// The worktimes of `date`. If the date has its own schedule (a holiday, for example), only it
+    /// is used. Otherwise, the worktimes of its weekday and the ones that were never scheduled are.
+    pub fn get_scheduled<'a>(
+        worktimes: &'a [Worktime],
+        schedules: &[WorktimeSchedule],
+        date: &NaiveDate,
+    ) -> Vec<&'a Worktime> {
+        let date_string = date.format("%Y-%m-%d").to_string();
+        let weekday_string = date.weekday().to_string();
 
-        Ok(worktime)
+        let overrides: Vec<&WorktimeSchedule> = schedules
+            .iter()
+            .filter(|schedule| schedule.date.as_ref() == Some(&date_string))
+            .collect();
+        let days: Vec<&WorktimeSchedule> = if overrides.is_empty() {
+            schedules
+                .iter()
+                .filter(|schedule| schedule.weekday.as_ref() == Some(&weekday_string))
+                .collect()
+        } else {
+            overrides.clone()
+        };
+
+        worktimes
+            .iter()
+            .filter(|worktime| {
+                let is_scheduled = |schedule: &&WorktimeSchedule| {
+                    schedule.worktime_id.as_ref() == Some(&worktime.id)
+                };
+
+                days.iter().any(is_scheduled)
+                    || (overrides.is_empty()
+                        && !schedules.iter().any(|schedule| is_scheduled(&schedule)))
+            })
+            .collect()
     }
-    pub fn list(conn: &mut SqliteConnection) -> Result<(), FypmError> {
+    /// The worktime of `now`, following the schedule. A window that crosses midnight belongs to the
+    /// day it started.
+    pub fn get_current<'a>(
+        worktimes: &'a [Worktime],
+        schedules: &[WorktimeSchedule],
+        now: &NaiveDateTime,
+    ) -> Option<&'a Worktime> {
+        let time = now.time();
+        let started_yesterday = |worktime: &Worktime| {
+            Self::get_window(worktime).is_some_and(|(start, end)| start > end && time < end)
+        };
+
+        let today = Self::get_scheduled(worktimes, schedules, &now.date())
+            .into_iter()
+            .filter(|worktime| !started_yesterday(worktime));
+        let yesterday = now
+            .date()
+            .pred_opt()
+            .map(|yesterday| Self::get_scheduled(worktimes, schedules, &yesterday))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|worktime| started_yesterday(worktime));
+
+        Self::get_active(today.chain(yesterday), &time)
+    }
+
+    /// The windows of the scheduled worktimes of each day in `[start, end)`, as
+    /// `(start, end, day, worktime)`. Windows that cross midnight end in the next day.
+    fn get_windows<'a>(
+        worktimes: &'a [Worktime],
+        schedules: &[WorktimeSchedule],
+        start: &NaiveDate,
+        end: &NaiveDate,
+    ) -> Vec<(NaiveDateTime, NaiveDateTime, NaiveDate, &'a Worktime)> {
+        NaiveDateIter::new(*start, *end)
+            .flat_map(|date| {
+                Self::get_scheduled(worktimes, schedules, &date)
+                    .into_iter()
+                    .filter_map(move |worktime| {
+                        let (window_start, window_end) = Self::get_window(worktime)?;
+                        let end_date = match window_start > window_end {
+                            true => date.succ_opt()?,
+                            false => date,
+                        };
+
+                        Some((
+                            date.and_time(window_start),
+                            end_date.and_time(window_end),
+                            date,
+                            worktime,
+                        ))
+                    })
+            })
+            .filter(|(window_start, window_end, _, _)| window_start < window_end)
+            .sorted_by_key(|(window_start, _, _, _)| *window_start)
+            .collect()
+    }
+    /// Compare each worktime window of the days in `[start, end)` with the tracked intervals,
+    /// by minute. Time tracked outside the windows goes to an `OffWorktime` record.
+    ///
+    /// The range is split where a window, an interval or a day begins or ends, so each piece has
+    /// only one worktime and one interval, and is counted at once.
+    pub fn report(
+        worktimes: &[Worktime],
+        schedules: &[WorktimeSchedule],
+        intervals: &[TrackedInterval],
+        start: &NaiveDate,
+        end: &NaiveDate,
+    ) -> Vec<WorktimeReportRecord> {
+        let mut records: BTreeMap<(NaiveDate, String), WorktimeReportRecord> = BTreeMap::new();
+
+        // Minutes are counted when they begin, so every boundary goes to the next whole minute.
+        let to_minute = |time: NaiveDateTime| match time.second() == 0 && time.nanosecond() == 0 {
+            true => time,
+            false => {
+                time.with_second(0).unwrap().with_nanosecond(0).unwrap() + Duration::minutes(1)
+            }
+        };
+
+        // A day before the range and a day after it, for the windows that cross its midnights.
+        let range_start = start.and_time(NaiveTime::MIN);
+        let range_end = (*end + Duration::days(1)).and_time(NaiveTime::MIN);
+        let windows = Self::get_windows(
+            worktimes,
+            schedules,
+            &(*start - Duration::days(1)),
+            &(*end + Duration::days(1)),
+        );
+
+        // Like `find`, the first interval wins where they overlap.
+        let mut intervals = intervals.to_vec();
+        intervals.sort_by_key(|interval| interval.start);
+        let mut tracked: Vec<(NaiveDateTime, NaiveDateTime, Option<String>)> = Vec::new();
+        let mut tracked_until = range_start;
+        for interval in intervals {
+            let interval_start = to_minute(interval.start).max(tracked_until);
+            let interval_end = to_minute(interval.end).min(range_end);
+
+            if interval_start < interval_end {
+                tracked.push((interval_start, interval_end, interval.worktime));
+                tracked_until = interval_end;
+            }
+        }
+
+        let mut boundaries: Vec<NaiveDateTime> =
+            NaiveDateIter::new(*start, *end + Duration::days(2))
+                .map(|date| date.and_time(NaiveTime::MIN))
+                .chain(windows.iter().flat_map(|window| [window.0, window.1]))
+                .chain(tracked.iter().flat_map(|interval| [interval.0, interval.1]))
+                .filter(|boundary| range_start <= *boundary && *boundary <= range_end)
+                .collect();
+        boundaries.sort();
+        boundaries.dedup();
+
+        let mut next_window = 0;
+        let mut open_windows: Vec<&(NaiveDateTime, NaiveDateTime, NaiveDate, &Worktime)> =
+            Vec::new();
+        let mut next_interval = 0;
+        for (piece_start, piece_end) in boundaries.iter().tuple_windows() {
+            let minutes = (*piece_end - *piece_start).num_minutes();
+
+            while windows
+                .get(next_window)
+                .is_some_and(|window| window.0 <= *piece_start)
+            {
+                open_windows.push(&windows[next_window]);
+                next_window += 1;
+            }
+            open_windows.retain(|window| *piece_start < window.1);
+            // If windows overlap, the one that started last is used.
+            let window = open_windows.iter().rev().max_by_key(|window| window.0);
+
+            while tracked
+                .get(next_interval)
+                .is_some_and(|interval| interval.1 <= *piece_start)
+            {
+                next_interval += 1;
+            }
+            let interval = tracked
+                .get(next_interval)
+                .filter(|interval| interval.0 <= *piece_start);
+
+            let (day, name) = match window {
+                Some((_, _, day, worktime)) => (*day, worktime.name.clone()),
+                None if interval.is_some() => (piece_start.date(), OFF_WORKTIME.to_string()),
+                None => continue,
+            };
+            if day < *start || day >= *end {
+                continue;
+            }
+
+            let record =
+                records
+                    .entry((day, name.clone()))
+                    .or_insert_with(|| WorktimeReportRecord {
+                        date: day.format("%Y-%m-%d").to_string(),
+                        worktime: name.clone(),
+                        planned: 0,
+                        tracked: 0,
+                        other: 0,
+                        untracked: 0,
+                    });
+
+            if window.is_some() {
+                record.planned += minutes;
+            }
+            match interval.map(|interval| interval.2.as_deref()) {
+                Some(_) if window.is_none() => record.tracked += minutes,
+                Some(Some(wt)) if wt == name || wt == "AllDay" => record.tracked += minutes,
+                Some(_) => record.other += minutes,
+                None => record.untracked += minutes,
+            }
+        }
+
+        records.into_values().collect()
+    }
+
+    fn find_by_name(conn: &mut SqliteConnection, name: &String) -> Result<Worktime, FypmError> {
+        Self::get_all(conn)?
+            .into_iter()
+            .find(|worktime| worktime.name == *name)
+            .ok_or(FypmError {
+                message: format!("The worktime {} doesn't exist!", name),
+                kind: FypmErrorKind::NotFound,
+            })
+    }
+    fn day_filter(
+        day: &ScheduleDay,
+    ) -> Box<
+        dyn diesel::BoxableExpression<
+            worktime_schedules::table,
+            diesel::sqlite::Sqlite,
+            SqlType = diesel::sql_types::Nullable<diesel::sql_types::Bool>,
+        >,
+    > {
+        match day {
+            ScheduleDay::Weekday(weekday) => {
+                Box::new(worktime_schedules::dsl::weekday.eq(weekday.to_string()))
+            }
+            ScheduleDay::Date(date) => {
+                Box::new(worktime_schedules::dsl::date.eq(date.format("%Y-%m-%d").to_string()))
+            }
+        }
+    }
+    fn new_schedule(
+        worktime_id: Option<String>,
+        day: &ScheduleDay,
+        description: Option<String>,
+    ) -> WorktimeSchedule {
+        let (weekday, date) = match day {
+            ScheduleDay::Weekday(weekday) => (Some(weekday.to_string()), None),
+            ScheduleDay::Date(date) => (None, Some(date.format("%Y-%m-%d").to_string())),
+        };
+
+        WorktimeSchedule {
+            id: uuid::Uuid::now_v7().to_string(),
+            worktime_id,
+            weekday,
+            date,
+            description,
+        }
+    }
+    /// Assign the worktime to the days. A date stops being a holiday when a worktime is added to it.
+    pub fn schedule_add(
+        conn: &mut SqliteConnection,
+        name: &String,
+        days: &Vec<ScheduleDay>,
+    ) -> Result<(), FypmError> {
+        let worktime = Self::find_by_name(conn, name)?;
+
+        for day in days {
+            if let ScheduleDay::Date(_) = day {
+                diesel::delete(
+                    worktime_schedules::table
+                        .filter(Self::day_filter(day))
+                        .filter(worktime_schedules::dsl::worktime_id.is_null()),
+                )
+                .execute(conn)
+                .unwrap();
+            }
+
+            let exists = worktime_schedules::table
+                .filter(Self::day_filter(day))
+                .filter(worktime_schedules::dsl::worktime_id.eq(&worktime.id))
+                .count()
+                .get_result::<i64>(conn)
+                .unwrap()
+                > 0;
+
+            if !exists {
+                diesel::insert_into(worktime_schedules::table)
+                    .values(Self::new_schedule(Some(worktime.id.clone()), day, None))
+                    .execute(conn)
+                    .unwrap();
+            }
+        }
+
+        Ok(())
+    }
+    /// Remove the schedule of the days, only of the worktime if it's received.
+    pub fn schedule_remove(
+        conn: &mut SqliteConnection,
+        name: &Option<String>,
+        days: &Vec<ScheduleDay>,
+    ) -> Result<usize, FypmError> {
+        let worktime_id = match name {
+            Some(name) => Some(Self::find_by_name(conn, name)?.id),
+            None => None,
+        };
+        let mut removed = 0;
+
+        for day in days {
+            let mut query = diesel::delete(worktime_schedules::table)
+                .filter(Self::day_filter(day))
+                .into_boxed();
+            if let Some(worktime_id) = &worktime_id {
+                query = query.filter(worktime_schedules::dsl::worktime_id.eq(worktime_id));
+            }
+
+            removed += query.execute(conn).unwrap();
+        }
+
+        Ok(removed)
+    }
+    /// Mark the dates as holidays, without any worktime.
+    pub fn schedule_holiday(
+        conn: &mut SqliteConnection,
+        days: &Vec<ScheduleDay>,
+        description: &Option<String>,
+    ) -> Result<(), FypmError> {
+        if days
+            .iter()
+            .any(|day| matches!(day, ScheduleDay::Weekday(_)))
+        {
+            return Err(FypmError {
+                message: "Holidays must be dates (YYYY-MM-DD)!".to_string(),
+                kind: FypmErrorKind::InvalidInput,
+            });
+        }
+
+        for day in days {
+            diesel::delete(worktime_schedules::table.filter(Self::day_filter(day)))
+                .execute(conn)
+                .unwrap();
+
+            diesel::insert_into(worktime_schedules::table)
+                .values(Self::new_schedule(None, day, description.clone()))
+                .execute(conn)
+                .unwrap();
+        }
+
+        Ok(())
+    }
+    /// Every worktime with its days, and the holidays.
+    pub fn export(conn: &mut SqliteConnection) -> Result<WorktimesFile, FypmError> {
+        let schedules = Self::get_schedules(conn)?;
+        let day_of = |schedule: &WorktimeSchedule| {
+            schedule
+                .weekday
+                .clone()
+                .or(schedule.date.clone())
+                .unwrap_or_default()
+        };
+
+        Ok(WorktimesFile {
+            worktimes: Self::get_all(conn)?
+                .into_iter()
+                .map(|worktime| WorktimeEntry {
+                    schedule: schedules
+                        .iter()
+                        .filter(|schedule| schedule.worktime_id.as_ref() == Some(&worktime.id))
+                        .map(day_of)
+                        .collect(),
+                    name: worktime.name,
+                    description: worktime.description,
+                    style: worktime.style,
+                    start_time: worktime.start_time,
+                    end_time: worktime.end_time,
+                    polybar_background: worktime.polybar_background,
+                    polybar_foreground: worktime.polybar_foreground,
+                })
+                .collect(),
+            holidays: schedules
+                .iter()
+                .filter(|schedule| schedule.worktime_id.is_none())
+                .map(|schedule| HolidayEntry {
+                    date: day_of(schedule),
+                    description: schedule.description.clone(),
+                })
+                .collect(),
+        })
+    }
+    /// Add the worktimes of the file. Everything is verified before the first change, and the
+    /// changes are made in a transaction, so a wrong file doesn't import half of its worktimes.
+    /// Returns the names of the imported worktimes.
+    pub fn import(
+        conn: &mut SqliteConnection,
+        file: &WorktimesFile,
+        on_conflict: &ImportConflict,
+        styles: &[String],
+        allow_overlap: &bool,
+    ) -> Result<Vec<String>, FypmError> {
+        let existing = Self::get_all(conn)?;
+        let find_existing = |name: &String| {
+            existing
+                .iter()
+                .find(|worktime| worktime.name.to_lowercase() == name.to_lowercase())
+        };
+
+        let mut to_import = Vec::new();
+        for entry in &file.worktimes {
+            if file
+                .worktimes
+                .iter()
+                .filter(|other| other.name.to_lowercase() == entry.name.to_lowercase())
+                .count()
+                > 1
+            {
+                return Err(FypmError {
+                    message: format!("The worktime {} is twice in the file!", entry.name),
+                    kind: FypmErrorKind::InvalidInput,
+                });
+            }
+
+            let worktime = Worktime {
+                id: find_existing(&entry.name)
+                    .map(|worktime| worktime.id.clone())
+                    .unwrap_or(uuid::Uuid::now_v7().to_string()),
+                name: entry.name.clone(),
+                description: entry.description.clone(),
+                style: entry.style.clone(),
+                start_time: entry.start_time.clone(),
+                end_time: entry.end_time.clone(),
+                polybar_background: entry.polybar_background.clone(),
+                polybar_foreground: entry.polybar_foreground.clone(),
+            };
+            Self::verify(&worktime, styles)?;
+
+            // Without a schedule, the worktime applies every day.
+            let days = match entry.schedule.is_empty() {
+                true => vec![],
+                false => Self::parse_schedule_days(&entry.schedule)?,
+            };
+
+            to_import.push((worktime, days));
+        }
+        let holidays = file
+            .holidays
+            .iter()
+            .map(|holiday| {
+                Ok((
+                    Self::parse_schedule_days(std::slice::from_ref(&holiday.date))?,
+                    holiday.description.clone(),
+                ))
+            })
+            .collect::<Result<Vec<_>, FypmError>>()?;
+
+        let conflicts: Vec<String> = to_import
+            .iter()
+            .filter(|(worktime, _)| find_existing(&worktime.name).is_some())
+            .map(|(worktime, _)| worktime.name.clone())
+            .collect();
+        if !conflicts.is_empty() && *on_conflict == ImportConflict::Abort {
+            return Err(FypmError {
+                message: format!(
+                    "These worktimes already exist: {}! Use --on-conflict skip or replace.",
+                    conflicts.join(", ")
+                ),
+                kind: FypmErrorKind::AlreadyExists,
+            });
+        }
+        if *on_conflict == ImportConflict::Skip {
+            to_import.retain(|(worktime, _)| !conflicts.contains(&worktime.name));
+        }
+
+        // The worktimes as they will be after the import, to find the overlaps.
+        let mut after_import: Vec<Worktime> = existing
+            .iter()
+            .filter(|worktime| {
+                !to_import
+                    .iter()
+                    .any(|(imported, _)| imported.id == worktime.id)
+            })
+            .cloned()
+            .collect();
+        after_import.extend(to_import.iter().map(|(worktime, _)| worktime.clone()));
+        for (worktime, _) in &to_import {
+            Self::verify_with_others(worktime, &after_import, styles, allow_overlap)?;
+        }
+
+        conn.transaction(|conn| {
+            let mut imported = Vec::new();
+            for (worktime, days) in to_import {
+                if conflicts.contains(&worktime.name) {
+                    diesel::update(worktimes::table.filter(worktimes::dsl::id.eq(&worktime.id)))
+                        .set(&worktime)
+                        .execute(conn)
+                        .map_err(FypmError::database)?;
+                    diesel::delete(
+                        worktime_schedules::table
+                            .filter(worktime_schedules::dsl::worktime_id.eq(&worktime.id)),
+                    )
+                    .execute(conn)
+                    .map_err(FypmError::database)?;
+                } else {
+                    diesel::insert_into(worktimes::table)
+                        .values(&worktime)
+                        .execute(conn)
+                        .map_err(FypmError::database)?;
+                }
+
+                Self::schedule_add(conn, &worktime.name, &days)?;
+                imported.push(worktime.name);
+            }
+            for (days, description) in holidays {
+                Self::schedule_holiday(conn, &days, &description)?;
+            }
+
+            Ok(imported)
+        })
+    }
+    /// The schedule, from monday to sunday and then by date.
+    pub fn get_schedule_records(
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<WorktimeScheduleRecord>, FypmError> {
+        let worktimes = Self::get_all(conn)?;
+        let mut schedules = Self::get_schedules(conn)?;
+
+        schedules.sort_by_key(|schedule| {
+            (
+                schedule.date.clone(),
+                schedule
+                    .weekday
+                    .as_ref()
+                    .and_then(|weekday| weekday.parse::<Weekday>().ok())
+                    .map(|weekday| weekday.num_days_from_monday()),
+            )
+        });
+
+        Ok(schedules
+            .into_iter()
+            .map(|schedule| WorktimeScheduleRecord {
+                day: schedule.date.or(schedule.weekday).unwrap_or_default(),
+                worktime: schedule.worktime_id.map(|worktime_id| {
+                    worktimes
+                        .iter()
+                        .find(|worktime| worktime.id == worktime_id)
+                        .map(|worktime| worktime.name.clone())
+                        .unwrap_or(worktime_id)
+                }),
+                description: schedule.description,
+            })
+            .collect())
+    }
+    pub fn schedule_list(
+        conn: &mut SqliteConnection,
+        output_format: &OutputFormat,
+    ) -> Result<(), FypmError> {
+        let records = Self::get_schedule_records(conn)?;
+
+        if output::print_records(output_format, &records)? {
+            return Ok(());
+        }
+
+        if records.is_empty() {
+            println!("No schedule found! Every worktime can be used in any day.");
+        }
+
+        for record in records {
+            match record.worktime {
+                Some(worktime) => println!("{:<10} {}", record.day.bright_white().bold(), worktime),
+                None => println!(
+                    "{:<10} {} {}",
+                    record.day.bright_white().bold(),
+                    "Holiday".bright_green(),
+                    record.description.unwrap_or_default()
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn list(
+        conn: &mut SqliteConnection,
+        output_format: &OutputFormat,
+    ) -> Result<(), FypmError> {
         let worktimes: Vec<Worktime> = worktimes::dsl::worktimes.load(conn).unwrap();
 
+        if output::print_records(output_format, &worktimes)? {
+            return Ok(());
+        }
+
         if worktimes.is_empty() {
             println!("No worktimes found!");
         } else {
@@ -1,12 +1,14 @@
 //#region           Crates
 use lazy_static::lazy_static;
 use clap::Parser;
+use diesel::{Connection, SqliteConnection};
 use std::env;
 //#endregion
 //#region           Modules
 mod func;
 mod handlers;
 mod commands;
+mod backends;
 mod tests;
 mod utils;
 mod db;
@@ -38,10 +40,52 @@ fn main() {
     handlers::config::ConfigHandler::ensure_config_path().unwrap();
     handlers::config::ConfigHandler::ensure_config_files().unwrap();
 
-    handlers::config::ConfigHandler::handle_config().unwrap();
-
     let cli = values::enums::Cli::parse();
 
-    commands::matching(&cli.commands).unwrap();
+    // Hooks run with the data of the Taskwarrior that called them
+    if !matches!(cli.commands, values::enums::Commands::Hook { .. }) {
+        if let Some(instance) = handlers::instance::InstanceHandler::select(
+            &mut SqliteConnection::establish(DATABASE_URL.as_str()).unwrap(),
+            &cli.instance,
+        )
+        .unwrap()
+        {
+            handlers::instance::InstanceHandler::apply(&instance).unwrap();
+        }
+    }
+
+    // After the instance, that has its own taskrc and applied worktime
+    if !cli.dry_run {
+        handlers::config::ConfigHandler::handle_config().unwrap();
+    }
+
+    if !cli.dry_run
+        && !matches!(
+            cli.commands,
+            values::enums::Commands::Undo { .. }
+                | values::enums::Commands::History { .. }
+                | values::enums::Commands::Hook { .. }
+                | values::enums::Commands::Hooks { .. }
+                | values::enums::Commands::WtDaemon { .. }
+        )
+    {
+        backends::journal::enable(
+            SqliteConnection::establish(&handlers::database::DBHandler::instance_url()).unwrap(),
+            &env::args().skip(1).collect::<Vec<String>>().join(" "),
+        );
+    }
+
+    if cli.dry_run {
+        let recorder = backends::dry_run::enable();
+
+        commands::matching(&cli.commands).unwrap();
+
+        println!(
+            "Dry run: {} mutation(s) recorded, nothing was executed.",
+            recorder.mutations().len()
+        );
+    } else {
+        commands::matching(&cli.commands).unwrap();
+    }
 }
 //#endregion
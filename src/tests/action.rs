@@ -3,6 +3,8 @@ use crate::values;
 #[cfg(test)]
 use crate::func;
 #[cfg(test)]
+use crate::values::enums::{TaskState, TaskType};
+#[cfg(test)]
 use crate::values::structs::TaskWarriorStatus;
 
 #[test]
@@ -11,26 +13,12 @@ fn verify_if_wt_is_allday() {
         id: 1,
         description: "test".to_string(),
         tags: Some(vec!["Test_Tag".to_string()]),
-        project: None,
-        state: "Time".to_string(),
-        r#type: "Task".to_string(),
-        wt: "AllDay".to_string(),
-        inforelat: None,
-        seq_current: None,
-        seq_next: None,
-        seq_prev: None,
-        alias: None,
-        style: None,
-        entry: "2023-08-22T00:00:00Z".to_string(),
-        modified: "2023-08-22T00:00:00Z".to_string(),
-        due: None,
-        parent: None,
+        state: Some(TaskState::Time),
+        r#type: Some(TaskType::Other("Task".to_string())),
+        wt: Some("AllDay".to_string()),
         status: TaskWarriorStatus::Pending,
         uuid: "1".to_string(),
-        annotations: None,
-        urgency: 0.0,
-        effort: None,
-        quadrant: None
+        ..Default::default()
     };
 
     let exec = func::action::verify_if_wt_is_allday(&json);
@@ -0,0 +1,263 @@
+#[cfg(test)]
+pub mod special {
+    use std::rc::Rc;
+
+    use diesel::RunQueryDsl;
+    use serde_json::json;
+
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task::{self as task_backend, TaskBackend};
+    use crate::db::models::Alias;
+    use crate::db::schema::aliases;
+    use crate::handlers::special_aliases::SpecialAliasesHandler;
+    use crate::tests::TestDatabase;
+    use crate::values::err::FypmErrorKind;
+
+    const REST: &str = "309d9b37-cd99-4b2c-b3c7-a9c60cb1754f";
+
+    /// Create a database with a startable task (`Rest`), a completed one, an AllDay one and a
+    /// Divisory one.
+    fn database() -> TestDatabase {
+        let backend = Rc::new(MemoryTaskBackend::new());
+
+        backend.insert(json!({
+            "uuid": REST,
+            "description": "Rest and breaks",
+            "TYPE": "Continuous",
+            "WT": "NonSched!",
+        }));
+        backend
+            .add(&["Done".to_string(), "TYPE:Objective".to_string()])
+            .unwrap();
+        backend.done("2").unwrap();
+        backend
+            .add(&["Birthday".to_string(), "WT:AllDay".to_string()])
+            .unwrap();
+        backend
+            .add(&["Morning".to_string(), "+Divisory".to_string()])
+            .unwrap();
+
+        task_backend::set_current(backend);
+
+        TestDatabase::new()
+    }
+
+    #[test]
+    pub fn add_rename_remove() {
+        let mut database = database();
+        let conn = database.conn();
+
+        SpecialAliasesHandler::add_alias(conn, "d", "description.startswith:Rest", &None).unwrap();
+
+        let aliases = SpecialAliasesHandler::get_aliases(conn).unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].task_uuid, REST);
+        assert_eq!(aliases[0].description.as_deref(), Some("Rest and breaks"));
+        assert_eq!(
+            SpecialAliasesHandler::resolve(conn, "d")
+                .unwrap()
+                .as_deref(),
+            Some(REST)
+        );
+
+        assert_eq!(
+            SpecialAliasesHandler::add_alias(conn, "d", "description.startswith:Rest", &None)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::AlreadyExists
+        );
+
+        SpecialAliasesHandler::rename_alias(conn, "d", "rest").unwrap();
+        assert_eq!(SpecialAliasesHandler::resolve(conn, "d").unwrap(), None);
+        assert_eq!(
+            SpecialAliasesHandler::resolve(conn, "rest")
+                .unwrap()
+                .as_deref(),
+            Some(REST)
+        );
+
+        assert_eq!(
+            SpecialAliasesHandler::remove_alias(conn, "d")
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+        SpecialAliasesHandler::remove_alias(conn, "rest").unwrap();
+        assert!(SpecialAliasesHandler::get_aliases(conn).unwrap().is_empty());
+    }
+    #[test]
+    pub fn unique_names() {
+        let mut database = database();
+        let conn = database.conn();
+
+        SpecialAliasesHandler::add_alias(conn, "d", "description.startswith:Rest", &None).unwrap();
+
+        // Even without the validation of the handler
+        assert!(diesel::insert_into(aliases::table)
+            .values(Alias {
+                id: uuid::Uuid::now_v7().to_string(),
+                name: "d".to_string(),
+                task_uuid: REST.to_string(),
+                description: None,
+            })
+            .execute(conn)
+            .is_err());
+    }
+    #[test]
+    pub fn validation() {
+        let mut database = database();
+        let conn = database.conn();
+
+        for name in ["12", "t", "last", "a b", "%d", "", REST] {
+            assert_eq!(
+                SpecialAliasesHandler::add_alias(conn, name, "description.startswith:Rest", &None)
+                    .unwrap_err()
+                    .kind,
+                FypmErrorKind::InvalidInput,
+                "{}",
+                name
+            );
+        }
+
+        // Only one task, that can be started
+        assert_eq!(
+            SpecialAliasesHandler::add_alias(conn, "x", "description.startswith:Nothing", &None)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NoTasksFound
+        );
+        assert_eq!(
+            SpecialAliasesHandler::add_alias(conn, "x", "status:pending", &None)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::TooMuchTasks
+        );
+        for filter in [
+            "description.startswith:Done",
+            "description.startswith:Birthday",
+            "description.startswith:Morning",
+        ] {
+            assert_eq!(
+                SpecialAliasesHandler::add_alias(conn, "x", filter, &None)
+                    .unwrap_err()
+                    .kind,
+                FypmErrorKind::TaskTypeError,
+                "{}",
+                filter
+            );
+        }
+
+        assert!(SpecialAliasesHandler::get_aliases(conn).unwrap().is_empty());
+    }
+}
+#[cfg(test)]
+pub mod uda {
+    use std::rc::Rc;
+
+    use serde_json::json;
+
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task::{self as task_backend, TaskBackend};
+    use crate::handlers::aliases::AliasesHandler;
+    use crate::utils::get::json_by_filter;
+    use crate::values::err::FypmErrorKind;
+
+    /// Create a backend with 2 Continuous tasks, `Rest and breaks` (`(1)`, alias `rb`) and
+    /// `Singing` (`(2)`, without alias).
+    fn mock_backend() -> Rc<MemoryTaskBackend> {
+        let backend = Rc::new(MemoryTaskBackend::new());
+
+        backend.insert(json!({
+            "uuid": "309d9b37-cd99-4b2c-b3c7-a9c60cb1754f",
+            "description": "Rest and breaks",
+            "TYPE": "Continuous",
+            "ALIAS": "rb",
+        }));
+        backend
+            .add(&["Singing".to_string(), "TYPE:Continuous".to_string()])
+            .unwrap();
+
+        task_backend::set_current(backend.clone());
+
+        backend
+    }
+    fn alias(filter: &str) -> Option<String> {
+        json_by_filter(filter, None).unwrap()[0].alias.clone()
+    }
+
+    #[test]
+    pub fn suggestions() {
+        assert_eq!(
+            AliasesHandler::suggest("Rest and breaks", &[])[0..8],
+            ["rab", "re", "res", "rest", "rea", "resa", "resta", "rab1"]
+        );
+        assert_eq!(
+            AliasesHandler::suggest("Singing!", &["si".to_string(), "s".to_string()])[0..3],
+            ["sin", "sing", "singi"]
+        );
+        // Only aliases that can be used in a filter
+        assert!(AliasesHandler::suggest("1 2 3", &[])
+            .iter()
+            .all(|alias| AliasesHandler::is_valid(alias)));
+    }
+    #[test]
+    pub fn add_change_remove() {
+        mock_backend();
+
+        assert_eq!(
+            AliasesHandler::add("1", &Some("rest".to_string()))
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            AliasesHandler::change("2", &Some("sing".to_string()))
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+
+        AliasesHandler::add("2", &Some("sing".to_string())).unwrap();
+        assert_eq!(alias("2"), Some("sing".to_string()));
+        assert_eq!(
+            AliasesHandler::find_by_alias("sing").unwrap(),
+            Some(json_by_filter("2", None).unwrap()[0].uuid.clone())
+        );
+
+        AliasesHandler::change("1", &Some("rest".to_string())).unwrap();
+        assert_eq!(alias("1"), Some("rest".to_string()));
+        assert_eq!(AliasesHandler::find_by_alias("rb").unwrap(), None);
+
+        AliasesHandler::remove("2").unwrap();
+        assert_eq!(alias("2"), None);
+        assert_eq!(
+            AliasesHandler::remove("2").unwrap_err().kind,
+            FypmErrorKind::NotFound
+        );
+    }
+    #[test]
+    pub fn uniqueness() {
+        mock_backend();
+
+        assert_eq!(
+            AliasesHandler::add("2", &Some("rb".to_string()))
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::AlreadyExists
+        );
+        for invalid in ["", "12", "too_long", "a b"] {
+            assert_eq!(
+                AliasesHandler::add("2", &Some(invalid.to_string()))
+                    .unwrap_err()
+                    .kind,
+                FypmErrorKind::InvalidInput,
+                "{}",
+                invalid
+            );
+        }
+        // Keeping the same alias is fine
+        AliasesHandler::change("1", &Some("rb".to_string())).unwrap();
+
+        assert_eq!(alias("2"), None);
+    }
+}
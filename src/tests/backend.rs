@@ -0,0 +1,216 @@
+#[cfg(test)]
+pub mod memory {
+    use std::rc::Rc;
+
+    use serde_json::json;
+
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task::{self as task_backend, TaskBackend};
+    use crate::commands::task;
+    use crate::utils::get::json_by_filter;
+    use crate::values::enums::{TaskState, TaskStyle};
+    use crate::values::structs::TaskWarriorStatus;
+
+    /// A task that is always there, like the control task of fypm.
+    pub const CONTROL_TASK: &str = "5c847c7e-c7eb-44f6-ad7e-29cc989c8854";
+
+    /// Create a backend with the CONTROL_TASK `(1)` and 3 equal tasks named 'Test' `(2,3,4)`.
+    fn mock_backend() -> Rc<MemoryTaskBackend> {
+        let backend = Rc::new(MemoryTaskBackend::new());
+
+        backend.insert(json!({
+            "uuid": CONTROL_TASK,
+            "description": "CONTROL_TASK",
+            "TYPE": "Objective",
+            "STATE": "Time",
+            "WT": "NonSched!",
+        }));
+        for _ in 0..3 {
+            backend
+                .add(&["Test".to_string(), "TYPE:Objective".to_string()])
+                .unwrap();
+        }
+
+        task_backend::set_current(backend.clone());
+
+        backend
+    }
+
+    #[test]
+    pub fn filters() {
+        let backend = mock_backend();
+
+        backend
+            .modify(
+                "2",
+                &["+Tagged".to_string(), "project:Fypm.Tests".to_string()],
+            )
+            .unwrap();
+        backend.done("3").unwrap();
+
+        assert_eq!(backend.count("").unwrap(), 4);
+        assert_eq!(backend.count("+PENDING").unwrap(), 3);
+        assert_eq!(backend.count("+Tagged").unwrap(), 1);
+        assert_eq!(backend.count("-Tagged and status:pending").unwrap(), 2);
+        assert_eq!(backend.count("project:Fypm").unwrap(), 1);
+        assert_eq!(backend.count("(+COMPLETED or +Tagged)").unwrap(), 2);
+        assert_eq!(backend.count("1 2").unwrap(), 2);
+        assert_eq!(backend.count("1-2").unwrap(), 2);
+        assert_eq!(backend.count(&CONTROL_TASK[0..8]).unwrap(), 1);
+        assert_eq!(backend.count("description.is:'Test'").unwrap(), 3);
+        assert_eq!(backend.count("description.startswith:CONTROL").unwrap(), 1);
+        assert_eq!(backend.count("project.none:").unwrap(), 3);
+    }
+    #[test]
+    pub fn show_config() {
+        let backend = MemoryTaskBackend::new().with_config("report.next.filter", "+PENDING");
+
+        assert_eq!(
+            backend.show_config("report.next.filter").unwrap(),
+            "+PENDING"
+        );
+        assert_eq!(backend.show_config("report.list.filter").unwrap(), "");
+    }
+    #[test]
+    pub fn add_and_modify() {
+        let backend = mock_backend();
+
+        let uuid = backend
+            .add(&[
+                "Write backend tests".to_string(),
+                "STYLE:Apollonian".to_string(),
+                "TYPE:Objective".to_string(),
+                "due:2024-01-01T12:00".to_string(),
+            ])
+            .unwrap();
+
+        let task = &json_by_filter(&uuid, None).unwrap()[0];
+        assert_eq!(task.id, 5);
+        assert_eq!(task.state, Some(TaskState::Time));
+        assert_eq!(task.style, Some(TaskStyle::Apollonian));
+        assert!(task.due.is_some());
+
+        backend.modify(&uuid, &["due:".to_string()]).unwrap();
+        backend.annotate(&uuid, "Annotated!").unwrap();
+
+        let task = &json_by_filter(&uuid, None).unwrap()[0];
+        assert!(task.due.is_none());
+        assert_eq!(task.annotations.as_ref().unwrap().len(), 1);
+        assert_eq!(backend.count("due.before:2024-01-02").unwrap(), 0);
+    }
+    #[test]
+    pub fn simple_done() {
+        let backend = mock_backend();
+        backend.start("1").unwrap();
+
+        task::update::done(
+            &Some("2,3,4".to_string()),
+            &None,
+            &None,
+            &true,
+            &false,
+            &false,
+        )
+        .unwrap();
+
+        let tasks = json_by_filter("description.is:'Test'", None).unwrap();
+
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(
+            tasks
+                .iter()
+                .filter(|task| task.status == TaskWarriorStatus::Completed)
+                .count(),
+            3
+        );
+        assert_eq!(backend.count("+ACTIVE").unwrap(), 1);
+    }
+}
+#[cfg(test)]
+pub mod dry_run {
+    use std::rc::Rc;
+
+    use serde_json::json;
+
+    use crate::backends::dry_run;
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task::{self as task_backend, TaskBackend};
+    use crate::commands::task;
+    use super::memory::CONTROL_TASK;
+
+    #[test]
+    pub fn records_without_executing() {
+        let backend = Rc::new(MemoryTaskBackend::new());
+        let control_task = backend.insert(json!({
+            "uuid": CONTROL_TASK,
+            "description": "CONTROL_TASK",
+            "TYPE": "Objective",
+            "STATE": "Time",
+            "WT": "NonSched!",
+        }));
+        let uuids = (0..3)
+            .map(|_| {
+                backend
+                    .add(&["Test".to_string(), "TYPE:Objective".to_string()])
+                    .unwrap()
+            })
+            .collect::<Vec<String>>();
+        backend.start(&control_task).unwrap();
+
+        task_backend::set_current(backend.clone());
+        let recorder = dry_run::enable();
+
+        task::update::done(
+            &Some("2,3,4".to_string()),
+            &None,
+            &Some("Previewed".to_string()),
+            &true,
+            &true,
+            &false,
+        )
+        .unwrap();
+
+        let mutations = recorder.mutations();
+        let join_uuids = uuids.join(" ");
+
+        assert_eq!(mutations.len(), 3);
+        assert_eq!(
+            mutations[0].command,
+            vec![
+                "task",
+                "rc.confirmation=0",
+                "rc.recurrence.confirmation=0",
+                "rc.verbose=0",
+                &join_uuids,
+                "annotate",
+                "Previewed"
+            ]
+        );
+        assert_eq!(
+            mutations[1].command,
+            vec![
+                "task",
+                "rc.confirmation=0",
+                "rc.recurrence.confirmation=0",
+                "rc.verbose=0",
+                &join_uuids,
+                "modify",
+                "+NotNecessary"
+            ]
+        );
+        assert_eq!(
+            mutations[2].command,
+            vec![
+                "task",
+                "rc.confirmation=0",
+                "rc.recurrence.confirmation=0",
+                &join_uuids,
+                "done"
+            ]
+        );
+        assert_eq!(mutations[2].uuids, uuids);
+
+        assert_eq!(backend.count("+PENDING").unwrap(), 4);
+        assert_eq!(backend.count("+NotNecessary or +ANNOTATED").unwrap(), 0);
+    }
+}
@@ -0,0 +1,338 @@
+#[cfg(test)]
+pub mod expand {
+    use crate::db::models::Filter;
+    use crate::handlers::filters::FiltersHandler;
+    use crate::values::err::FypmErrorKind;
+
+    pub fn filter(name: &str, filter: &str) -> Filter {
+        Filter {
+            id: name.to_string(),
+            name: name.to_string(),
+            filter: filter.to_string(),
+            description: None,
+            last_used: None,
+        }
+    }
+
+    #[test]
+    pub fn saved_filters() {
+        let filters = vec![
+            filter("late_alarm", "ALARM.before:now -COMPLETED"),
+            filter("work", "WT:Work and %late_alarm"),
+        ];
+
+        assert_eq!(
+            FiltersHandler::expand("+TODAY", &filters).unwrap(),
+            "+TODAY"
+        );
+        assert_eq!(
+            FiltersHandler::expand("%late_alarm or +OVERDUE", &filters).unwrap(),
+            "(ALARM.before:now -COMPLETED) or +OVERDUE"
+        );
+        assert_eq!(
+            FiltersHandler::expand("%work", &filters).unwrap(),
+            "(WT:Work and (ALARM.before:now -COMPLETED))"
+        );
+        assert_eq!(
+            FiltersHandler::expand("%missing", &filters)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+    }
+    #[test]
+    pub fn cycles() {
+        let filters = vec![
+            filter("a", "+A or %b"),
+            filter("b", "+B or %c"),
+            filter("c", "%a"),
+            filter("self", "+S or %self"),
+            filter("twice", "%c_free and %c_free"),
+            filter("c_free", "+FREE"),
+        ];
+
+        let error = FiltersHandler::expand("%a", &filters).unwrap_err();
+        assert_eq!(error.kind, FypmErrorKind::InvalidInput);
+        assert!(error.message.contains("a -> b -> c -> a"));
+
+        assert!(FiltersHandler::expand("%self", &filters).is_err());
+        // Using a filter twice isn't a cycle
+        assert_eq!(
+            FiltersHandler::expand("%twice", &filters).unwrap(),
+            "((+FREE) and (+FREE))"
+        );
+    }
+}
+#[cfg(test)]
+pub mod run {
+    use std::collections::HashMap;
+
+    use super::expand::filter;
+    use crate::handlers::filters::FiltersHandler;
+    use crate::values::err::FypmErrorKind;
+
+    #[test]
+    pub fn render_placeholders() {
+        let filters = vec![
+            filter("by_project", "project:{project} and %due_on"),
+            filter("due_on", "due:{date}"),
+        ];
+        let params = HashMap::from([
+            ("project".to_string(), "Fypm".to_string()),
+            ("date".to_string(), "2024-01-01".to_string()),
+        ]);
+
+        assert_eq!(
+            FiltersHandler::render("by_project", &params, &filters).unwrap(),
+            "(project:Fypm and (due:2024-01-01))"
+        );
+        assert_eq!(
+            FiltersHandler::render("due_on", &HashMap::new(), &filters)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::InvalidInput
+        );
+        assert_eq!(
+            FiltersHandler::render("missing", &params, &filters)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+    }
+}
+#[cfg(test)]
+pub mod manage {
+    use std::rc::Rc;
+
+    use diesel::SqliteConnection;
+    use serde_json::json;
+
+    use crate::backends::dry_run;
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task as task_backend;
+    use crate::handlers::filters::FiltersHandler;
+    use crate::tests::TestDatabase;
+    use crate::values::enums::ImportConflict;
+    use crate::values::err::FypmErrorKind;
+    use crate::values::structs::{FilterEntry, FiltersFile};
+
+    fn database() -> TestDatabase {
+        task_backend::set_current(Rc::new(MemoryTaskBackend::new()));
+
+        TestDatabase::new()
+    }
+    fn saved(conn: &mut SqliteConnection, name: &str) -> String {
+        FiltersHandler::get_filters(conn)
+            .unwrap()
+            .into_iter()
+            .find(|filter| filter.name == name)
+            .map(|filter| filter.filter)
+            .unwrap_or_default()
+    }
+    fn text(value: &str) -> String {
+        value.to_string()
+    }
+
+    #[test]
+    pub fn add_edit_remove() {
+        let mut database = database();
+        let conn = database.conn();
+
+        FiltersHandler::add_filter(conn, &text("today"), &text("+TODAY"), &None).unwrap();
+        FiltersHandler::add_filter(conn, &text("work"), &text("WT:Work and %today"), &None)
+            .unwrap();
+
+        let error_kind =
+            |result: Result<(), crate::values::err::FypmError>| result.unwrap_err().kind;
+        assert_eq!(
+            error_kind(FiltersHandler::add_filter(
+                conn,
+                &text("today"),
+                &text("+A"),
+                &None
+            )),
+            FypmErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            error_kind(FiltersHandler::add_filter(
+                conn,
+                &text("bad"),
+                &text("(+A"),
+                &None
+            )),
+            FypmErrorKind::InvalidInput
+        );
+        assert_eq!(
+            error_kind(FiltersHandler::add_filter(
+                conn,
+                &text("my filter"),
+                &text("+A"),
+                &None
+            )),
+            FypmErrorKind::InvalidInput
+        );
+        // Placeholders are only filled by `filter run`
+        FiltersHandler::add_filter(conn, &text("project"), &text("project:{name}"), &None).unwrap();
+
+        // A new filter is checked as a filter, not as a name
+        FiltersHandler::edit_filter(conn, &text("today"), &None, &Some(text("work")), &None)
+            .unwrap();
+        assert_eq!(saved(conn, "today"), "work");
+        assert_eq!(
+            error_kind(FiltersHandler::edit_filter(
+                conn,
+                &text("today"),
+                &Some(text("work")),
+                &None,
+                &None
+            )),
+            FypmErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            error_kind(FiltersHandler::edit_filter(
+                conn,
+                &text("today"),
+                &None,
+                &Some(text("%work")),
+                &None
+            )),
+            FypmErrorKind::InvalidInput
+        );
+
+        // The references follow a new name
+        FiltersHandler::edit_filter(
+            conn,
+            &text("today"),
+            &Some(text("now")),
+            &Some(text("+TODAY")),
+            &Some(text("Tasks of today")),
+        )
+        .unwrap();
+        assert_eq!(saved(conn, "work"), "WT:Work and %now");
+
+        assert_eq!(
+            error_kind(FiltersHandler::remove_filter(conn, &text("now"))),
+            FypmErrorKind::InvalidInput
+        );
+        FiltersHandler::remove_filter(conn, &text("work")).unwrap();
+        FiltersHandler::remove_filter(conn, &text("now")).unwrap();
+        assert_eq!(
+            error_kind(FiltersHandler::remove_filter(conn, &text("now"))),
+            FypmErrorKind::NotFound
+        );
+    }
+    #[test]
+    pub fn usage() {
+        let mut database = database();
+        let conn = database.conn();
+
+        FiltersHandler::add_filter(conn, &text("today"), &text("+TODAY"), &None).unwrap();
+        FiltersHandler::mark_used(conn, "%today or +OVERDUE").unwrap();
+
+        assert!(FiltersHandler::get_filters(conn).unwrap()[0]
+            .last_used
+            .is_some());
+    }
+    #[test]
+    pub fn dry_run_usage() {
+        let mut database = database();
+        let conn = database.conn();
+
+        FiltersHandler::add_filter(conn, &text("today"), &text("+TODAY"), &None).unwrap();
+        dry_run::enable();
+        FiltersHandler::mark_used(conn, "%today").unwrap();
+
+        assert!(FiltersHandler::get_filters(conn).unwrap()[0]
+            .last_used
+            .is_none());
+    }
+    #[test]
+    pub fn import_export() {
+        let mut database = database();
+        let conn = database.conn();
+
+        FiltersHandler::add_filter(conn, &text("today"), &text("+TODAY"), &Some(text("Today")))
+            .unwrap();
+
+        let content = toml::to_string_pretty(&FiltersHandler::export(conn).unwrap()).unwrap();
+        let mut file: FiltersFile = toml::from_str(&content).unwrap();
+        assert_eq!(file.filters[0].description.as_deref(), Some("Today"));
+
+        file.filters[0].filter = text("+TODAY and +PENDING");
+        file.filters.push(FilterEntry {
+            name: text("work"),
+            filter: text("WT:Work and %today"),
+            description: None,
+        });
+
+        assert_eq!(
+            FiltersHandler::import(conn, &file, &ImportConflict::Abort)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            FiltersHandler::import(conn, &file, &ImportConflict::Skip).unwrap(),
+            vec!["work"]
+        );
+        assert_eq!(saved(conn, "today"), "+TODAY");
+
+        FiltersHandler::import(conn, &file, &ImportConflict::Replace).unwrap();
+        assert_eq!(saved(conn, "today"), "+TODAY and +PENDING");
+        assert_eq!(FiltersHandler::export(conn).unwrap(), file);
+
+        file.filters.push(FilterEntry {
+            name: text("broken"),
+            filter: text("%missing"),
+            description: None,
+        });
+        assert_eq!(
+            FiltersHandler::import(conn, &file, &ImportConflict::Replace)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+        assert_eq!(saved(conn, "broken"), "");
+    }
+    #[test]
+    pub fn example_file() {
+        let mut database = database();
+        let conn = database.conn();
+
+        let content = std::fs::read_to_string(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("config/filters.example.toml"),
+        )
+        .unwrap();
+        let file: FiltersFile = toml::from_str(&content).unwrap();
+
+        assert_eq!(
+            FiltersHandler::import(conn, &file, &ImportConflict::Abort).unwrap(),
+            vec!["dio_mains", "overdue_objectives"]
+        );
+    }
+    #[test]
+    pub fn run_report() {
+        let mut database = database();
+        let conn = database.conn();
+        let backend = Rc::new(MemoryTaskBackend::new());
+        task_backend::set_current(backend.clone());
+
+        let work = backend.insert(json!({"description": "Work", "project": "Fypm"}));
+        backend.insert(json!({"description": "Other", "project": "Other"}));
+
+        FiltersHandler::add_filter(conn, &text("by_project"), &text("project:{name}"), &None)
+            .unwrap();
+        FiltersHandler::run(conn, &[text("by_project"), text("next"), text("name=Fypm")]).unwrap();
+
+        assert_eq!(backend.reports(), vec![(text("next"), vec![work])]);
+        assert!(FiltersHandler::get_filters(conn).unwrap()[0]
+            .last_used
+            .is_some());
+        assert_eq!(
+            FiltersHandler::run(conn, &[text("by_project"), text("name=Fypm"), text("nope")])
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::InvalidInput
+        );
+    }
+}
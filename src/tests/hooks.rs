@@ -0,0 +1,651 @@
+#[cfg(test)]
+pub fn default_rules() -> Vec<crate::values::structs::TaskRule> {
+    use crate::handlers::config::ConfigHandler;
+
+    ConfigHandler::parse_rules(&ConfigHandler::create_rules_defaults()).unwrap()
+}
+
+#[cfg(test)]
+pub mod on_add {
+    use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+    use serde_json::{json, Value};
+
+    use super::default_rules;
+    use crate::func::hooks;
+    use crate::values::err::FypmErrorKind;
+    use crate::values::structs::TaskWarriorExported;
+
+    /// 2024-01-10 20:00 in UTC-3 (2024-01-10 23:00 UTC).
+    fn now() -> DateTime<FixedOffset> {
+        FixedOffset::west_opt(3 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, 10, 20, 0, 0)
+            .unwrap()
+    }
+    fn task(attributes: Value) -> TaskWarriorExported {
+        let mut task = json!({
+            "uuid": "0191c8a4-0000-7000-8000-000000000001",
+            "description": "Test",
+            "status": "pending",
+            "entry": "20240110T230000Z",
+            "STATE": "Time",
+            "WT": "NonSched!",
+        });
+        for (key, value) in attributes.as_object().unwrap() {
+            task[key] = value.clone();
+        }
+
+        serde_json::from_value(task).unwrap()
+    }
+    fn type_error(attributes: Value) -> String {
+        let error = hooks::on_add(task(attributes), &now(), &default_rules()).unwrap_err();
+        assert_eq!(error.kind, FypmErrorKind::TaskTypeError);
+
+        error.message
+    }
+
+    #[test]
+    pub fn habit() {
+        assert_eq!(
+            type_error(json!({ "TYPE": "Habit" })),
+            "Habit tasks must have a recur!"
+        );
+        assert_eq!(
+            type_error(json!({ "TYPE": "Habit", "recur": "daily", "STATE": "Info" })),
+            "Habit tasks must have STATE set to 'Time'!"
+        );
+        assert!(hooks::on_add(task(json!({ "TYPE": "Habit", "recur": "daily" })), &now(), &default_rules()).is_ok());
+    }
+    #[test]
+    pub fn eventual_without_due() {
+        let output = hooks::on_add(task(json!({ "TYPE": "Eventual" })), &now(), &default_rules()).unwrap();
+
+        // 23:59:59 in UTC-3 is 02:59:59 of the next day in UTC.
+        assert_eq!(
+            output.task.due,
+            Some(Utc.with_ymd_and_hms(2024, 1, 11, 2, 59, 59).unwrap())
+        );
+        assert_eq!(output.feedback.len(), 1);
+    }
+    #[test]
+    pub fn eventual_with_due() {
+        // 2024-01-10 23:30 in UTC-3.
+        assert!(hooks::on_add(
+            task(json!({ "TYPE": "Eventual", "due": "20240111T023000Z" })),
+            &now(),
+            &default_rules()
+        )
+        .is_ok());
+        // 2024-01-11 00:00 in UTC-3.
+        assert_eq!(
+            type_error(json!({ "TYPE": "Eventual", "due": "20240111T030000Z" })),
+            "Eventual tasks must be completed in the same day! You can use 'Objective' instead."
+        );
+    }
+    #[test]
+    pub fn event() {
+        type_error(json!({ "TYPE": "Event", "due": "20240115T150000Z" }));
+
+        let output = hooks::on_add(
+            task(json!({ "TYPE": "Event", "WT": "AllDay!", "due": "20240115T150000Z" })),
+            &now(),
+            &default_rules(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.task.due,
+            Some(Utc.with_ymd_and_hms(2024, 1, 16, 2, 59, 59).unwrap())
+        );
+    }
+    #[test]
+    pub fn check_and_continuous() {
+        assert_eq!(
+            type_error(json!({ "TYPE": "Check" })),
+            "Check tasks must have STATE set to 'Info'!"
+        );
+        assert_eq!(
+            type_error(json!({ "TYPE": "Continuous" })),
+            "Continuous tasks must have an ALIAS!"
+        );
+        assert!(hooks::on_add(
+            task(json!({ "TYPE": "Continuous", "ALIAS": "minecraft" })),
+            &now(),
+            &default_rules()
+        )
+        .is_ok());
+    }
+}
+#[cfg(test)]
+pub mod recurrence {
+    use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+    use serde_json::json;
+
+    use super::default_rules;
+    use crate::func::hooks::{self, RecurrencePeriod};
+    use crate::values::structs::TaskWarriorExported;
+
+    fn utc_minus_3() -> FixedOffset {
+        FixedOffset::west_opt(3 * 3600).unwrap()
+    }
+    fn utc(date: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+    fn shift(date: &str, recur: &str, times: u32) -> DateTime<Utc> {
+        hooks::shift_date(
+            utc(date),
+            hooks::parse_recurrence(recur).unwrap(),
+            times,
+            &utc_minus_3(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn parse() {
+        assert_eq!(
+            hooks::parse_recurrence("daily"),
+            Some(RecurrencePeriod::Days(1))
+        );
+        assert_eq!(
+            hooks::parse_recurrence("yearly"),
+            Some(RecurrencePeriod::Months(12))
+        );
+        assert_eq!(
+            hooks::parse_recurrence("3d"),
+            Some(RecurrencePeriod::Days(3))
+        );
+        assert_eq!(
+            hooks::parse_recurrence("2w"),
+            Some(RecurrencePeriod::Days(14))
+        );
+        assert_eq!(hooks::parse_recurrence("d"), None);
+        assert_eq!(hooks::parse_recurrence("fortnight"), None);
+    }
+    #[test]
+    pub fn shift_dates() {
+        let alarm = "2024-01-31T10:00:00Z";
+
+        assert_eq!(shift(alarm, "daily", 2), utc("2024-02-02T10:00:00Z"));
+        assert_eq!(shift(alarm, "weekly", 1), utc("2024-02-07T10:00:00Z"));
+        assert_eq!(shift(alarm, "3d", 2), utc("2024-02-06T10:00:00Z"));
+        assert_eq!(shift(alarm, "2w", 1), utc("2024-02-14T10:00:00Z"));
+        // The month is clamped to its last day.
+        assert_eq!(shift(alarm, "monthly", 1), utc("2024-02-29T10:00:00Z"));
+        assert_eq!(shift(alarm, "yearly", 1), utc("2025-01-31T10:00:00Z"));
+        // 2024-01-31 is a wednesday: thursday, friday, monday.
+        assert_eq!(shift(alarm, "weekdays", 3), utc("2024-02-05T10:00:00Z"));
+        // 01:00 UTC is still the day before in UTC-3.
+        assert_eq!(
+            shift("2024-02-01T01:00:00Z", "monthly", 1),
+            utc("2024-03-01T01:00:00Z")
+        );
+    }
+    #[test]
+    pub fn on_add_instance() {
+        let instance: TaskWarriorExported = serde_json::from_value(json!({
+            "uuid": "0191c8a4-0000-7000-8000-000000000002",
+            "description": "Read",
+            "status": "pending",
+            "entry": "20240110T230000Z",
+            "due": "20240113T020000Z",
+            "ALARM": "20240110T220000Z",
+            "GOAL": "20240110T230000Z",
+            "scheduled": "20240110T120000Z",
+            "recur": "daily",
+            "imask": 2,
+            "parent": "0191c8a4-0000-7000-8000-000000000001",
+            "TYPE": "Habit",
+            "STATE": "Time",
+        }))
+        .unwrap();
+        let now = utc_minus_3()
+            .with_ymd_and_hms(2024, 1, 10, 20, 0, 0)
+            .unwrap();
+
+        let output = hooks::on_add(instance.clone(), &now, &default_rules()).unwrap();
+
+        assert_eq!(output.task.alarm, Some(utc("2024-01-12T22:00:00Z")));
+        assert_eq!(output.task.goal, Some(utc("2024-01-12T23:00:00Z")));
+        assert_eq!(output.task.scheduled, Some(utc("2024-01-12T12:00:00Z")));
+        assert_eq!(output.task.due, instance.due);
+
+        let unsupported = TaskWarriorExported {
+            recur: Some("quarterly".to_string()),
+            ..instance.clone()
+        };
+        let output = hooks::on_add(unsupported, &now, &default_rules()).unwrap();
+
+        assert_eq!(output.task.alarm, instance.alarm);
+        assert_eq!(output.feedback.len(), 1);
+    }
+}
+#[cfg(test)]
+pub mod on_modify {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use serde_json::{json, Value};
+
+    use crate::backends::time as time_backend;
+    use crate::commands::hook::bridge_timewarrior;
+    use super::default_rules;
+    use crate::func::hooks;
+    use crate::tests::timew::data::{fixture_reader, FixtureTimeBackend};
+    use crate::values::structs::TaskWarriorExported;
+
+    const UUID: &str = "0191c8a4-0000-7000-8000-000000000001";
+
+    fn task(attributes: Value) -> TaskWarriorExported {
+        let mut task = json!({
+            "uuid": UUID,
+            "description": "A description longer than twenty five chars",
+            "status": "pending",
+            "entry": "20240110T090000Z",
+            "STATE": "Time",
+            "TYPE": "Objective",
+            "WT": "General",
+            "project": "Fypm",
+        });
+        for (key, value) in attributes.as_object().unwrap() {
+            task[key] = value.clone();
+        }
+
+        serde_json::from_value(task).unwrap()
+    }
+    fn mock_time_backend() -> Rc<FixtureTimeBackend> {
+        let time = Rc::new(FixtureTimeBackend {
+            reader: fixture_reader(),
+            calls: RefCell::new(Vec::new()),
+        });
+        time_backend::set_current(time.clone());
+
+        time
+    }
+
+    #[test]
+    pub fn type_rules() {
+        let continuous = json!({ "TYPE": "Continuous", "ALIAS": "minecraft" });
+        let mut done = continuous.clone();
+        done["status"] = json!("completed");
+
+        assert_eq!(
+            hooks::on_modify(&task(continuous), task(done), &default_rules())
+                .unwrap_err()
+                .message,
+            "You cannot done a continuous task!"
+        );
+        assert!(hooks::on_modify(
+            &task(json!({ "STATE": "Info" })),
+            task(json!({ "STATE": "Info", "start": "20240110T100000Z" })),
+            &default_rules()
+        )
+        .is_err());
+        // Changing TYPE checks its attributes, other changes don't.
+        assert!(hooks::on_modify(&task(json!({})), task(json!({ "TYPE": "Habit" })), &default_rules()).is_err());
+        assert!(hooks::on_modify(
+            &task(json!({ "TYPE": "Habit" })),
+            task(json!({ "TYPE": "Habit", "project": "Other" })),
+            &default_rules()
+        )
+        .is_ok());
+    }
+    #[test]
+    pub fn start_and_stop() {
+        let time = mock_time_backend();
+        let stopped = task(json!({}));
+        let started = task(json!({ "start": "20240110T100000Z" }));
+
+        bridge_timewarrior(&stopped, &started);
+        bridge_timewarrior(&started, &stopped);
+
+        let tags = vec![
+            UUID,
+            "A description longer t...",
+            "General",
+            "Objective",
+            "Fypm",
+            ":yes",
+        ];
+        let calls = time.calls.borrow();
+        assert_eq!(calls[0][0], "start");
+        assert_eq!(calls[0][1..], tags);
+        assert_eq!(calls[1][0], "stop");
+        assert_eq!(calls[1][1..], tags);
+    }
+    #[test]
+    pub fn stop_with_changes() {
+        let time = mock_time_backend();
+        let started = task(json!({ "start": "20240110T100000Z" }));
+        let stopped = task(json!({
+            "description": "Renamed",
+            "project": "Other",
+            "WT": "NonSched!",
+        }));
+
+        bridge_timewarrior(&started, &stopped);
+
+        // The open interval has the old tags
+        let calls = time.calls.borrow();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0][0], "stop");
+        assert!(calls[0].contains(&"Fypm".to_string()));
+        assert!(calls[0].contains(&"General".to_string()));
+        assert!(!calls[0].contains(&"Renamed".to_string()));
+    }
+    #[test]
+    pub fn retag_and_annotate() {
+        let time = mock_time_backend();
+        let old = task(json!({ "start": "20240110T100000Z" }));
+        let new = task(json!({
+            "start": "20240110T100000Z",
+            "project": "Other",
+            "annotations": [{ "entry": "20240110T103000Z", "description": "Note" }],
+        }));
+
+        bridge_timewarrior(&old, &new);
+
+        let calls = time.calls.borrow();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0][..2], ["untag", "@1"]);
+        assert!(calls[0].contains(&"Fypm".to_string()));
+        assert_eq!(calls[1][..2], ["tag", "@1"]);
+        assert!(calls[1].contains(&"Other".to_string()));
+        assert_eq!(calls[2], ["annotate", "@1", "Note"]);
+    }
+}
+#[cfg(test)]
+pub mod install {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    use crate::handlers::config::ConfigHandler;
+    use crate::handlers::hooks::{HookStatus, HooksHandler};
+    use crate::values::enums::HookEvent;
+
+    fn hooks_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("fypm-hooks-{}", uuid::Uuid::now_v7()))
+    }
+
+    #[test]
+    pub fn resolve_location() {
+        let home = Path::new("/home/user");
+        let mut configs = BTreeMap::new();
+
+        assert_eq!(
+            ConfigHandler::resolve_hooks_location(&configs, None, home),
+            PathBuf::from("/home/user/.task/hooks")
+        );
+
+        configs.insert("data.location".to_string(), "~/tasks".to_string());
+        assert_eq!(
+            ConfigHandler::resolve_hooks_location(&configs, None, home),
+            PathBuf::from("/home/user/tasks/hooks")
+        );
+        assert_eq!(
+            ConfigHandler::resolve_hooks_location(&configs, Some("/data".to_string()), home),
+            PathBuf::from("/data/hooks")
+        );
+
+        configs.insert("hooks.location".to_string(), "/hooks".to_string());
+        assert_eq!(
+            ConfigHandler::resolve_hooks_location(&configs, Some("/data".to_string()), home),
+            PathBuf::from("/hooks")
+        );
+    }
+    #[test]
+    pub fn install_and_drift() {
+        let dir = hooks_dir();
+        let binary = Path::new("/usr/bin/fypm");
+        let on_add = dir.join("on-add.fypm");
+
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnAdd, binary),
+            HookStatus::Missing
+        );
+        assert_eq!(
+            HooksHandler::install_in(&dir, binary, &true).unwrap(),
+            vec!["on-add.fypm", "on-modify.fypm"]
+        );
+        for event in HooksHandler::events() {
+            assert_eq!(
+                HooksHandler::check(&dir, &event, binary),
+                HookStatus::Installed
+            );
+        }
+        assert!(fs::read_to_string(&on_add)
+            .unwrap()
+            .contains("exec \"/usr/bin/fypm\" hook on-add"));
+
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnAdd, Path::new("/opt/fypm")),
+            HookStatus::Modified
+        );
+
+        fs::set_permissions(&on_add, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnAdd, binary),
+            HookStatus::NotExecutable
+        );
+
+        fs::write(
+            &on_add,
+            "#!/bin/sh\n# fypm-hook-version: 0.1.0\nexec fypm hook on-add\n",
+        )
+        .unwrap();
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnAdd, binary),
+            HookStatus::Outdated("0.1.0".to_string())
+        );
+
+        assert_eq!(
+            HooksHandler::install_in(&dir, binary, &true).unwrap(),
+            vec!["on-add.fypm"]
+        );
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnAdd, binary),
+            HookStatus::Installed
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    pub fn legacy_and_foreign_hooks() {
+        let dir = hooks_dir();
+        let binary = Path::new("/usr/bin/fypm");
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("on-modify.timewarrior"), "#!/usr/bin/env python3\n").unwrap();
+        fs::write(dir.join("on-modify.fypm"), "#!/bin/sh\nexec my-hook\n").unwrap();
+        fs::write(dir.join("on-exit.other"), "#!/bin/sh\n").unwrap();
+
+        assert_eq!(
+            HooksHandler::legacy_hooks(&dir),
+            vec!["on-modify.timewarrior"]
+        );
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnModify, binary),
+            HookStatus::Foreign
+        );
+
+        HooksHandler::install_in(&dir, binary, &true).unwrap();
+        assert!(HooksHandler::legacy_hooks(&dir).is_empty());
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnModify, binary),
+            HookStatus::Installed
+        );
+
+        fs::write(dir.join("on-add.fypm"), "#!/bin/sh\nexec my-hook\n").unwrap();
+        assert_eq!(
+            HooksHandler::uninstall_from(&dir, binary).unwrap(),
+            vec!["on-modify.fypm"]
+        );
+        assert!(dir.join("on-add.fypm").exists());
+        assert!(dir.join("on-exit.other").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    pub fn broken_hook() {
+        let dir = hooks_dir();
+        let binary = Path::new("/usr/bin/fypm");
+        let on_add = dir.join("on-add.fypm");
+
+        fs::create_dir_all(&dir).unwrap();
+        std::os::unix::fs::symlink(dir.join("nowhere"), &on_add).unwrap();
+
+        assert!(matches!(
+            HooksHandler::check(&dir, &HookEvent::OnAdd, binary),
+            HookStatus::Broken(_)
+        ));
+        assert!(HooksHandler::uninstall_from(&dir, binary)
+            .unwrap()
+            .is_empty());
+
+        HooksHandler::install_in(&dir, binary, &true).unwrap();
+        assert_eq!(
+            HooksHandler::check(&dir, &HookEvent::OnAdd, binary),
+            HookStatus::Installed
+        );
+        assert!(!dir.join("nowhere").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+#[cfg(test)]
+pub mod rules {
+    use std::collections::BTreeMap;
+
+    use serde_json::{json, Value};
+
+    use super::default_rules;
+    use crate::func::hooks;
+    use crate::handlers::config::ConfigHandler;
+    use crate::values::err::FypmErrorKind;
+    use crate::values::structs::{TaskRuleCondition, TaskWarriorExported};
+
+    fn task(attributes: Value) -> TaskWarriorExported {
+        let mut task = json!({
+            "uuid": "0191c8a4-0000-7000-8000-000000000001",
+            "description": "Test",
+            "status": "pending",
+            "entry": "20240110T090000Z",
+            "STATE": "Time",
+            "TYPE": "Objective",
+            "WT": "General",
+        });
+        for (key, value) in attributes.as_object().unwrap() {
+            task[key] = value.clone();
+        }
+
+        serde_json::from_value(task).unwrap()
+    }
+    fn configs(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        let mut configs = ConfigHandler::create_rules_defaults();
+        for (key, value) in entries {
+            configs.insert(key.to_string(), value.to_string());
+        }
+
+        configs
+    }
+
+    #[test]
+    pub fn parse() {
+        let rules = ConfigHandler::parse_rules(&configs(&[
+            ("rule.project_style.when", "TYPE:Objective"),
+            ("rule.project_style.require", "project STYLE"),
+            ("rule.project_style.forbid", "+Abandoned"),
+            ("rule.habit_state.enabled", "false"),
+            ("rule.check_state.message", "Check is Info!"),
+        ]))
+        .unwrap();
+
+        let project_style = rules.iter().find(|rule| rule.name == "project_style").unwrap();
+        assert_eq!(
+            project_style.require,
+            vec![
+                TaskRuleCondition::Present("project".to_string()),
+                TaskRuleCondition::Present("STYLE".to_string()),
+            ]
+        );
+        assert_eq!(
+            project_style.forbid,
+            vec![TaskRuleCondition::Tag("Abandoned".to_string())]
+        );
+        assert!(!rules.iter().any(|rule| rule.name == "habit_state"));
+
+        let check_state = rules.iter().find(|rule| rule.name == "check_state").unwrap();
+        assert_eq!(check_state.message, "Check is Info!");
+        assert_eq!(
+            check_state.when,
+            vec![TaskRuleCondition::Equals(
+                "TYPE".to_string(),
+                "Check".to_string()
+            )]
+        );
+
+        for invalid in [
+            ("rule.habit_state.required", "recur"),
+            ("rule.habit_state.enabled", "no"),
+            ("rule.empty.when", "TYPE:Habit"),
+        ] {
+            assert_eq!(
+                ConfigHandler::parse_rules(&configs(&[invalid]))
+                    .unwrap_err()
+                    .kind,
+                FypmErrorKind::InvalidConfig
+            );
+        }
+    }
+    #[test]
+    pub fn broken_rules() {
+        let rules = ConfigHandler::parse_rules(&configs(&[
+            ("rule.project_style.when", "TYPE:Objective"),
+            ("rule.project_style.require", "project"),
+            ("rule.project_style.forbid", "+Abandoned STYLE:Idle"),
+        ]))
+        .unwrap();
+        let names = |task: &TaskWarriorExported| {
+            ConfigHandler::broken_rules(&rules, task)
+                .iter()
+                .map(|rule| rule.name.clone())
+                .collect::<Vec<String>>()
+        };
+
+        assert!(names(&task(json!({ "project": "Fypm" }))).is_empty());
+        assert_eq!(names(&task(json!({}))), vec!["project_style"]);
+        assert_eq!(
+            names(&task(json!({ "project": "Fypm", "tags": ["Abandoned"] }))),
+            vec!["project_style"]
+        );
+        assert_eq!(
+            names(&task(json!({ "project": "Fypm", "STYLE": "Idle" }))),
+            vec!["project_style"]
+        );
+        assert_eq!(
+            names(&task(json!({ "TYPE": "Check", "tags": ["MOTHER"] }))),
+            vec!["check_state", "mother_objective"]
+        );
+    }
+    #[test]
+    pub fn mother_on_modify() {
+        let error = hooks::on_modify(
+            &task(json!({ "TYPE": "Habit", "recur": "daily" })),
+            task(json!({ "TYPE": "Habit", "recur": "daily", "tags": ["MOTHER"] })),
+            &default_rules(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.message, "Only Objective tasks can be a MOTHER!");
+        assert!(hooks::on_modify(
+            &task(json!({})),
+            task(json!({ "tags": ["MOTHER"] })),
+            &default_rules()
+        )
+        .is_ok());
+    }
+}
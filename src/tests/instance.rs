@@ -0,0 +1,163 @@
+#[cfg(test)]
+pub mod manage {
+    use std::path::{Path, PathBuf};
+
+    use diesel::SqliteConnection;
+
+    use crate::handlers::instance::InstanceHandler;
+    use crate::tests::TestDatabase;
+    use crate::values::enums::InstanceArgs;
+    use crate::values::err::FypmErrorKind;
+
+    /// A database and a directory for the data of the instances.
+    fn database() -> (TestDatabase, PathBuf) {
+        let database = TestDatabase::new();
+        let base = database.directory();
+
+        (database, base)
+    }
+    fn add(conn: &mut SqliteConnection, base: &Path, name: &str) -> Result<(), FypmErrorKind> {
+        let (taskdata, timewarriordb) = InstanceHandler::default_paths(base, name);
+
+        InstanceHandler::add_instance(conn, name, "", &taskdata, &timewarriordb)
+            .map(|_| ())
+            .map_err(|error| error.kind)
+    }
+
+    #[test]
+    pub fn add_edit_remove() {
+        let (mut database, base) = database();
+        let (conn, base) = (database.conn(), &base);
+
+        add(conn, base, "work").unwrap();
+
+        let instance = &InstanceHandler::get_instances(conn).unwrap()[0];
+        assert!(Path::new(&instance.taskdata).is_dir());
+        assert!(Path::new(&instance.timewarriordb).join("data").is_dir());
+        assert!(instance.taskdata.ends_with("instances/work/task"));
+
+        assert_eq!(add(conn, base, "work"), Err(FypmErrorKind::AlreadyExists));
+        for name in ["default", "", "a b"] {
+            assert_eq!(
+                add(conn, base, name),
+                Err(FypmErrorKind::InvalidInput),
+                "{}",
+                name
+            );
+        }
+
+        let edited = InstanceHandler::edit_instance(
+            conn,
+            "work",
+            &Some("job".to_string()),
+            &InstanceArgs {
+                description: Some("Work tasks".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(edited.name, "job");
+        assert_eq!(edited.description, "Work tasks");
+        // The data stays in the same place
+        assert_eq!(edited.taskdata, instance.taskdata);
+        assert_eq!(
+            InstanceHandler::edit_instance(conn, "job", &None, &InstanceArgs::default())
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::InvalidInput
+        );
+
+        assert_eq!(
+            InstanceHandler::remove_instance(conn, "job", &Some("job".to_string()))
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::InvalidInput
+        );
+        InstanceHandler::remove_instance(conn, "job", &None).unwrap();
+        assert!(InstanceHandler::get_instances(conn).unwrap().is_empty());
+        // The data is kept
+        assert!(Path::new(&instance.taskdata).is_dir());
+    }
+    #[test]
+    pub fn select() {
+        let (mut database, base) = database();
+        let (conn, base) = (database.conn(), &base);
+
+        add(conn, base, "personal").unwrap();
+
+        assert_eq!(
+            InstanceHandler::select(conn, &Some("personal".to_string()))
+                .unwrap()
+                .map(|instance| instance.name),
+            Some("personal".to_string())
+        );
+        assert!(InstanceHandler::select(conn, &Some("default".to_string()))
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            InstanceHandler::select(conn, &Some("missing".to_string()))
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+    }
+}
+#[cfg(test)]
+pub mod isolation {
+    use std::rc::Rc;
+
+    use crate::backends::journal;
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task as task_backend;
+    use crate::handlers::database::DBHandler;
+    use crate::handlers::instance::InstanceHandler;
+    use crate::handlers::journal::JournalHandler;
+    use crate::handlers::task_history::TaskHistoryHandler;
+    use crate::tests::TestDatabase;
+    use crate::values::enums::InstanceArgs;
+
+    #[test]
+    pub fn undo_and_last() {
+        let mut database = TestDatabase::new();
+        let base = database.directory();
+        let conn = database.conn();
+
+        let mut data_paths = Vec::new();
+        for name in ["work", "personal"] {
+            let (taskdata, timewarriordb) = InstanceHandler::default_paths(&base, name);
+            let instance =
+                InstanceHandler::add_instance(conn, name, "", &taskdata, &timewarriordb).unwrap();
+
+            data_paths.push(InstanceHandler::data_path(&base, &instance));
+        }
+        assert_ne!(data_paths[0], data_paths[1]);
+
+        // A task added and started in work
+        task_backend::set_current(Rc::new(MemoryTaskBackend::new()));
+        journal::enable(
+            DBHandler::ensure_instance_db(&data_paths[0]).unwrap(),
+            "ta-add Work",
+        );
+        let task = task_backend::current().add(&["Work".to_string()]).unwrap();
+        let work = &mut DBHandler::ensure_instance_db(&data_paths[0]).unwrap();
+        TaskHistoryHandler::push(work, &task).unwrap();
+
+        let personal = &mut DBHandler::ensure_instance_db(&data_paths[1]).unwrap();
+        assert!(JournalHandler::get_operations(personal, 10).unwrap().is_empty());
+        assert!(JournalHandler::undo(personal, &1, &true).is_err());
+        assert!(TaskHistoryHandler::get_last(personal, 1, None).is_err());
+
+        assert_eq!(JournalHandler::get_operations(work, 10).unwrap().len(), 1);
+        assert_eq!(TaskHistoryHandler::get_last(work, 1, None).unwrap(), task);
+
+        // The data stays with the instance when it's renamed
+        let renamed = InstanceHandler::edit_instance(
+            conn,
+            "work",
+            &Some("job".to_string()),
+            &InstanceArgs::default(),
+        )
+        .unwrap();
+        assert_eq!(InstanceHandler::data_path(&base, &renamed), data_paths[0]);
+    }
+}
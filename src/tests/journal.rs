@@ -0,0 +1,120 @@
+#[cfg(test)]
+pub mod undo {
+    use std::rc::Rc;
+
+    use serde_json::json;
+
+    use crate::backends::journal;
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task::{self as task_backend, TaskBackend};
+    use crate::commands::task;
+    use crate::db::models::JournalEntry;
+    use crate::handlers::journal::{JournalHandler, JOURNAL_LIMIT};
+    use crate::tests::TestDatabase;
+    use crate::utils::get::json_by_filter;
+    use crate::values::enums::TaskState;
+
+    #[test]
+    pub fn undo_subtask() {
+        let mut database = TestDatabase::new();
+
+        let backend = Rc::new(MemoryTaskBackend::new());
+        let mother = backend.insert(json!({
+            "description": "Mother",
+            "project": "Fypm",
+            "TYPE": "Objective",
+            "STATE": "Time",
+            "WT": "NonSched!",
+        }));
+        task_backend::set_current(backend.clone());
+
+        journal::enable(database.connect(), "ta-add-sub Mother");
+
+        let subtask = task::add::subtask(
+            &mother,
+            &vec![
+                "Child".to_string(),
+                "Apollonian".to_string(),
+                "Objective".to_string(),
+            ],
+            &true,
+        )
+        .unwrap();
+
+        let mother_json = &json_by_filter(&mother, None).unwrap()[0];
+        assert_eq!(mother_json.state, Some(TaskState::Info));
+        assert_eq!(backend.count("+PENDING").unwrap(), 2);
+
+        task_backend::set_current(backend.clone());
+        let conn = database.conn();
+
+        let operations = JournalHandler::get_operations(conn, 10).unwrap();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(
+            operations[0]
+                .iter()
+                .map(|entry| entry.task_uuid.clone())
+                .collect::<Vec<String>>(),
+            vec![subtask.clone(), mother.clone()]
+        );
+        assert!(operations[0][0].before.is_none());
+        assert!(operations[0].iter().all(|entry| entry.after.is_some()));
+
+        JournalHandler::history(conn, &10).unwrap();
+        JournalHandler::undo(conn, &1, &true).unwrap();
+
+        let mother_json = &json_by_filter(&mother, None).unwrap()[0];
+        assert_eq!(mother_json.state, Some(TaskState::Time));
+        assert_eq!(mother_json.tags, None);
+        assert_eq!(backend.count("+PENDING").unwrap(), 1);
+        assert_eq!(backend.count(&format!("{} +DELETED", subtask)).unwrap(), 1);
+        assert!(JournalHandler::get_operations(conn, 10).unwrap().is_empty());
+        assert!(JournalHandler::undo(conn, &1, &true).is_err());
+    }
+    #[test]
+    pub fn last_operations() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        let total = JOURNAL_LIMIT as usize + 5;
+        for operation in 0..total {
+            for task in ["first", "second"] {
+                JournalHandler::record(
+                    conn,
+                    &JournalEntry {
+                        id: format!("{:04}-{}", operation, task),
+                        operation_id: format!("{:04}", operation),
+                        command: format!("ta-done {}", operation),
+                        created_at: "2024-01-01 08:00:00".to_string(),
+                        task_uuid: task.to_string(),
+                        before: None,
+                        after: None,
+                    },
+                )
+                .unwrap();
+            }
+        }
+
+        let operations = JournalHandler::get_operations(conn, 3).unwrap();
+        assert_eq!(
+            operations
+                .iter()
+                .map(|operation| operation[0].operation_id.clone())
+                .collect::<Vec<String>>(),
+            vec![
+                format!("{:04}", total - 1),
+                format!("{:04}", total - 2),
+                format!("{:04}", total - 3)
+            ]
+        );
+        assert!(operations.iter().all(|operation| operation
+            .iter()
+            .map(|entry| entry.task_uuid.as_str())
+            .eq(["first", "second"])));
+
+        assert_eq!(JournalHandler::prune(conn).unwrap(), 10);
+        let operations = JournalHandler::get_operations(conn, total).unwrap();
+        assert_eq!(operations.len(), JOURNAL_LIMIT as usize);
+        assert_eq!(operations.last().unwrap()[0].operation_id, "0005");
+    }
+}
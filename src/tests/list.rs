@@ -0,0 +1,143 @@
+#[cfg(test)]
+pub mod output {
+    use std::rc::Rc;
+
+    use clap::Parser;
+    use serde_json::json;
+
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task as task_backend;
+    use crate::commands::task::list::completion_score_records;
+    use crate::func::list;
+    use crate::utils::output;
+    use crate::values::enums::{Cli, TaskEffort, TaskStyle, TaskType};
+    use crate::values::structs::{CompletionScore, TaskInfo};
+
+    /// Create a backend with instances due in 2024-01-06 (Saturday) and 2024-01-07 (Sunday).
+    fn mock_backend() {
+        let backend = Rc::new(MemoryTaskBackend::new());
+
+        for (due, status, tags) in [
+            ("20240106T120000Z", "pending", json!([])),
+            ("20240106T130000Z", "completed", json!([])),
+            ("20240107T120000Z", "deleted", json!(["Failed"])),
+            ("20240107T130000Z", "completed", json!([])),
+        ] {
+            backend.insert(json!({
+                "uuid": uuid::Uuid::now_v7().to_string(),
+                "description": "Habit",
+                "status": status,
+                "due": due,
+                "parent": "0191c8a4-0000-7000-8000-000000000000",
+                "STYLE": "Necessity",
+                "TYPE": "Habit",
+                "STATE": "Time",
+                "WT": "General",
+                "tags": tags,
+            }));
+        }
+
+        task_backend::set_current(backend);
+    }
+
+    #[test]
+    pub fn completion_score() {
+        mock_backend();
+
+        let records = completion_score_records(&vec![
+            "2024-01-05".to_string(),
+            "-".to_string(),
+            "2024-01-08".to_string(),
+        ])
+        .unwrap();
+
+        let record = |kind: &str, date: &str, pending, completed, deleted| CompletionScore {
+            kind: kind.to_string(),
+            date: date.to_string(),
+            pending,
+            completed,
+            deleted,
+            total: pending + completed + deleted,
+        };
+
+        assert_eq!(
+            records,
+            vec![
+                record("day", "2024-01-06", 1, 1, 0),
+                record("day", "2024-01-07", 0, 1, 1),
+                record("week", "2024-01-07", 1, 2, 1),
+            ]
+        );
+    }
+    #[test]
+    pub fn deleted_statistic() {
+        mock_backend();
+
+        let statistic = list::deleted_tasks(&false).unwrap();
+
+        assert_eq!(statistic.failed, 1);
+        assert_eq!(statistic.archived, 0);
+    }
+    #[test]
+    pub fn csv() {
+        let info = TaskInfo {
+            id: 2,
+            uuid: "0191c8a4-0000-7000-8000-000000000001".to_string(),
+            description: "Write \"tests\"".to_string(),
+            project: None,
+            style: Some(TaskStyle::Apollonian),
+            wt: Some("General".to_string()),
+            r#type: Some(TaskType::Objective),
+            quadrant: None,
+            effort: Some(TaskEffort::One),
+            tags: vec!["Tested".to_string(), "Fypm".to_string()],
+        };
+
+        assert_eq!(
+            output::to_csv(&vec![info]).unwrap(),
+            "id,uuid,description,project,style,wt,type,quadrant,effort,tags\n\
+             2,0191c8a4-0000-7000-8000-000000000001,\"Write \"\"tests\"\"\",,Apollonian,General,Objective,,One,\"Tested,Fypm\"\n"
+        );
+    }
+    #[test]
+    pub fn csv_columns() {
+        #[derive(serde::Serialize, Default)]
+        struct Record {
+            name: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            extra: Option<String>,
+            count: u32,
+        }
+
+        // The header is printed without records
+        assert_eq!(
+            output::to_csv::<CompletionScore>(&[]).unwrap(),
+            "kind,date,pending,completed,deleted,total\n"
+        );
+        // A field that only some records have doesn't move the others
+        assert_eq!(
+            output::to_csv(&[
+                Record {
+                    name: "a".to_string(),
+                    extra: None,
+                    count: 1,
+                },
+                Record {
+                    name: "b".to_string(),
+                    extra: Some("x".to_string()),
+                    count: 2,
+                },
+            ])
+            .unwrap(),
+            "name,count,extra\na,1,\nb,2,x\n"
+        );
+    }
+    #[test]
+    pub fn json_and_format() {
+        let parse = |args: &[&str]| Cli::try_parse_from([&["fypm", "ta-info", "1"], args].concat());
+
+        assert!(parse(&["--json", "--format", "csv"]).is_err());
+        assert!(parse(&["--json"]).is_ok());
+        assert!(parse(&["--format", "csv"]).is_ok());
+    }
+}
@@ -1,4 +1,64 @@
 mod action;
+mod backend;
+mod journal;
 mod aliases;
 mod task;
-mod lib;
\ No newline at end of file
+mod timew;
+mod list;
+mod lib;
+mod structs;
+mod hooks;
+pub mod worktime;
+mod status;
+mod filters;
+mod task_history;
+mod instance;
+
+#[cfg(test)]
+use std::path::PathBuf;
+
+#[cfg(test)]
+use diesel::{Connection, SqliteConnection};
+#[cfg(test)]
+use diesel_migrations::MigrationHarness;
+
+#[cfg(test)]
+use crate::db::models::MIGRATIONS;
+
+/// A temporary fypm database with every migration. It's removed with the `drop` implementation.
+#[cfg(test)]
+pub struct TestDatabase {
+    path: PathBuf,
+    conn: SqliteConnection,
+}
+
+#[cfg(test)]
+impl TestDatabase {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("fypm-test-{}.db", uuid::Uuid::now_v7()));
+
+        let mut conn = SqliteConnection::establish(&path.to_string_lossy()).unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        Self { path, conn }
+    }
+    pub fn conn(&mut self) -> &mut SqliteConnection {
+        &mut self.conn
+    }
+    /// Open another connection, for what needs to own one (ex: the journal).
+    pub fn connect(&self) -> SqliteConnection {
+        SqliteConnection::establish(&self.path.to_string_lossy()).unwrap()
+    }
+    /// A directory next to the database, for other data of the test. Also removed when dropped.
+    pub fn directory(&self) -> PathBuf {
+        self.path.with_extension("")
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        let _ = std::fs::remove_dir_all(self.directory());
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+pub mod sinks {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use chrono::{TimeZone, Utc};
+    use serde_json::{json, Value};
+
+    use crate::handlers::config::ConfigHandler;
+    use crate::handlers::status::{format_elapsed, StatusHandler};
+    use crate::tests::worktime::daemon::worktime;
+    use crate::values::enums::StatusFormat;
+    use crate::values::err::FypmErrorKind;
+    use crate::values::structs::{CompletionScore, StatusInfo, TaskWarriorExported};
+
+    fn status() -> StatusInfo {
+        let task: TaskWarriorExported = serde_json::from_value(json!({
+            "uuid": "0191c8a4-0000-7000-8000-000000000001",
+            "description": "Write the report",
+            "status": "pending",
+            "entry": "20240110T090000Z",
+            "start": "20240110T100000Z",
+        }))
+        .unwrap();
+        let score = CompletionScore {
+            kind: "day".to_string(),
+            date: "2024-01-10".to_string(),
+            pending: 1,
+            completed: 3,
+            deleted: 0,
+            total: 4,
+        };
+
+        StatusHandler::build(
+            Some(&worktime("General", "08:00", "18:00")),
+            Some(&task),
+            &score,
+            &Utc.with_ymd_and_hms(2024, 1, 10, 11, 5, 0).unwrap(),
+        )
+    }
+    fn render(format: StatusFormat) -> String {
+        StatusHandler::get_sink(&format, &ConfigHandler::create_settings_defaults())
+            .render(&status())
+    }
+
+    #[test]
+    pub fn build() {
+        let status = status();
+
+        assert_eq!(status.worktime.as_deref(), Some("General"));
+        assert_eq!(status.worktime_end.as_deref(), Some("18:00"));
+        assert_eq!(status.elapsed_minutes, Some(65));
+        assert_eq!(format_elapsed(65), "1h05m");
+        assert_eq!(format_elapsed(5), "5m");
+        assert_eq!((status.completed_today, status.total_today), (3, 4));
+    }
+    #[test]
+    pub fn render_formats() {
+        assert_eq!(
+            render(StatusFormat::Plain),
+            "General | Write the report (1h05m) | 3/4"
+        );
+        assert_eq!(render(StatusFormat::Polybar), "General -> 18:00");
+        assert_eq!(
+            render(StatusFormat::I3blocks),
+            "General | Write the report (1h05m) | 3/4\nWrite the report\n#fff"
+        );
+
+        let waybar: Value = serde_json::from_str(&render(StatusFormat::Waybar)).unwrap();
+        assert_eq!(waybar["class"], "general");
+        assert_eq!(waybar["percentage"], 75);
+
+        let status_json: Value = serde_json::from_str(&render(StatusFormat::Json)).unwrap();
+        assert_eq!(status_json["task"], "Write the report");
+
+        assert_eq!(
+            StatusHandler::get_sink(&StatusFormat::Plain, &BTreeMap::new())
+                .render(&StatusInfo::default()),
+            "0/0"
+        );
+    }
+    #[test]
+    pub fn write_sinks() {
+        let directory = std::env::temp_dir().join(format!("fypm-status-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = |name: &str| directory.join(name).to_string_lossy().to_string();
+
+        let mut settings = ConfigHandler::create_settings_defaults();
+        settings.insert("status.sinks".to_string(), "json, plain".to_string());
+        settings.insert("status.json.path".to_string(), path("status.json"));
+        settings.insert("status.plain.path".to_string(), path("status.txt"));
+
+        for sink in StatusHandler::get_sinks(&settings).unwrap() {
+            sink.write(&status()).unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(PathBuf::from(path("status.txt"))).unwrap(),
+            "General | Write the report (1h05m) | 3/4"
+        );
+        assert!(std::fs::read_to_string(PathBuf::from(path("status.json")))
+            .unwrap()
+            .contains("\"elapsed_minutes\": 65"));
+
+        settings.insert("status.sinks".to_string(), "lemonbar".to_string());
+        assert_eq!(
+            StatusHandler::get_sinks(&settings).err().unwrap().kind,
+            FypmErrorKind::InvalidConfig
+        );
+        settings.insert("status.sinks".to_string(), "".to_string());
+        assert!(StatusHandler::get_sinks(&settings).unwrap().is_empty());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(test)]
+pub mod exported {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    use crate::values::enums::{TaskEffort, TaskQuadrant, TaskState, TaskStyle, TaskType};
+    use crate::values::structs::{TaskWarriorExported, TaskWarriorStatus};
+
+    #[test]
+    pub fn full_task() {
+        let task: TaskWarriorExported = serde_json::from_value(json!({
+            "id": 3,
+            "uuid": "0191c8a4-0000-7000-8000-000000000001",
+            "description": "Read a chapter",
+            "status": "pending",
+            "entry": "20240110T090000Z",
+            "modified": "20240110T093000Z",
+            "due": "20240112T030000Z",
+            "scheduled": "20240111T120000Z",
+            "ALARM": "20240111T180000Z",
+            "GOAL": "20240111T200000Z",
+            "recur": "daily",
+            "imask": 4,
+            "parent": "0191c8a4-0000-7000-8000-000000000000",
+            "depends": ["0191c8a4-0000-7000-8000-000000000002"],
+            "MOTHER": "0191c8a4-0000-7000-8000-000000000003",
+            "CHAIN": "Reading",
+            "STATE": "Time",
+            "STYLE": "Apollonian",
+            "TYPE": "Habit",
+            "WT": "General",
+            "effort": "Two",
+            "quadrant": "None",
+            "estimate": "30min",
+            "annotations": [{ "entry": "20240110T091500Z", "description": "Page 30" }],
+            "urgency": 4.5,
+        }))
+        .unwrap();
+
+        assert_eq!(task.status, TaskWarriorStatus::Pending);
+        assert_eq!(
+            task.due,
+            Some(Utc.with_ymd_and_hms(2024, 1, 12, 3, 0, 0).unwrap())
+        );
+        assert_eq!(
+            task.alarm,
+            Some(Utc.with_ymd_and_hms(2024, 1, 11, 18, 0, 0).unwrap())
+        );
+        assert_eq!(task.imask, Some(4));
+        assert_eq!(task.depends.as_ref().unwrap().len(), 1);
+        assert_eq!(
+            task.mother.as_deref(),
+            Some("0191c8a4-0000-7000-8000-000000000003")
+        );
+        assert_eq!(task.state, Some(TaskState::Time));
+        assert_eq!(task.style, Some(TaskStyle::Apollonian));
+        assert_eq!(task.r#type, Some(TaskType::Habit));
+        assert_eq!(task.effort, Some(TaskEffort::Two));
+        assert_eq!(task.quadrant, Some(TaskQuadrant::None));
+        assert_eq!(
+            task.annotations.as_ref().unwrap()[0].entry,
+            Utc.with_ymd_and_hms(2024, 1, 10, 9, 15, 0).unwrap()
+        );
+        assert!(task.extra.is_empty());
+    }
+    #[test]
+    pub fn legacy_task() {
+        let task: TaskWarriorExported = serde_json::from_value(json!({
+            "id": 0,
+            "uuid": "0191c8a4-0000-7000-8000-000000000001",
+            "description": "Old task",
+            "status": "completed",
+            "entry": "20200101T000000Z",
+            "end": "20200102T000000Z",
+            "depends": "0191c8a4-0000-7000-8000-000000000002,0191c8a4-0000-7000-8000-000000000003",
+            "imask": "2.000000",
+            "TYPE": "Task",
+        }))
+        .unwrap();
+
+        assert_eq!(task.state, None);
+        assert_eq!(task.wt, None);
+        assert_eq!(task.r#type, Some(TaskType::Other("Task".to_string())));
+        assert_eq!(task.depends.as_ref().unwrap().len(), 2);
+        assert_eq!(task.imask, Some(2));
+        assert_eq!(task.modified, None);
+    }
+    #[test]
+    pub fn preserve_unknown_fields() {
+        let raw = json!({
+            "uuid": "0191c8a4-0000-7000-8000-000000000001",
+            "description": "Hook task",
+            "status": "pending",
+            "entry": "20240110T090000Z",
+            "modified": "20240110T090000Z",
+            "TYPE": "Objective",
+            "WT": "General",
+            "priority": "H",
+            "mask": "--+",
+            "UNKNOWN_UDA": "kept",
+        });
+
+        let task: TaskWarriorExported = serde_json::from_value(raw.clone()).unwrap();
+
+        assert_eq!(task.extra["priority"], "H");
+        assert_eq!(serde_json::to_value(&task).unwrap(), raw);
+    }
+    #[test]
+    pub fn untyped_task() {
+        let raw = json!({
+            "uuid": "0191c8a4-0000-7000-8000-000000000001",
+            "description": "Untyped task",
+            "status": "pending",
+            "entry": "20240110T090000Z",
+            "modified": "20240110T090000Z",
+        });
+
+        let task: TaskWarriorExported = serde_json::from_value(raw.clone()).unwrap();
+
+        // No `"TYPE": null` is written back to Taskwarrior
+        assert_eq!(serde_json::to_value(&task).unwrap(), raw);
+    }
+}
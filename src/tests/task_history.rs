@@ -0,0 +1,96 @@
+#[cfg(test)]
+pub mod stack {
+    use crate::handlers::task_history::{TaskHistoryHandler, TASK_HISTORY_LIMIT};
+    use crate::tests::TestDatabase;
+    use crate::values::err::FypmErrorKind;
+
+    #[test]
+    pub fn last_aliases() {
+        assert_eq!(TaskHistoryHandler::parse_last("last"), Some(1));
+        assert_eq!(TaskHistoryHandler::parse_last("last2"), Some(2));
+        assert_eq!(TaskHistoryHandler::parse_last("last12"), Some(12));
+        assert_eq!(TaskHistoryHandler::parse_last("last0"), None);
+        assert_eq!(TaskHistoryHandler::parse_last("lastly"), None);
+        assert_eq!(TaskHistoryHandler::parse_last("t"), None);
+    }
+    #[test]
+    pub fn recent_tasks() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        for task in ["control", "work", "control", "rest", "work"] {
+            TaskHistoryHandler::push(conn, task).unwrap();
+        }
+
+        // Each task once, from the most recent
+        assert_eq!(
+            TaskHistoryHandler::recent(conn)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.task_uuid)
+                .collect::<Vec<String>>(),
+            ["work", "rest", "control"]
+        );
+
+        // The active task is skipped
+        assert_eq!(
+            TaskHistoryHandler::get_last(conn, 1, Some("work")).unwrap(),
+            "rest"
+        );
+        assert_eq!(
+            TaskHistoryHandler::get_last(conn, 2, Some("work")).unwrap(),
+            "control"
+        );
+        assert_eq!(TaskHistoryHandler::get_last(conn, 1, None).unwrap(), "work");
+        assert_eq!(
+            TaskHistoryHandler::get_last(conn, 3, Some("work"))
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+    }
+    #[test]
+    pub fn limit() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        for index in 0..TASK_HISTORY_LIMIT + 5 {
+            TaskHistoryHandler::push(conn, &format!("task-{}", index)).unwrap();
+        }
+
+        let recent = TaskHistoryHandler::recent(conn).unwrap();
+        assert_eq!(recent.len() as i64, TASK_HISTORY_LIMIT);
+        assert_eq!(
+            recent[0].task_uuid,
+            format!("task-{}", TASK_HISTORY_LIMIT + 4)
+        );
+        assert_eq!(recent.last().unwrap().task_uuid, "task-5");
+    }
+}
+#[cfg(test)]
+pub mod control {
+    use crate::handlers::config::ConfigHandler;
+
+    #[test]
+    pub fn replace_setting() {
+        let content = "; comment\nstatus.sinks = polybar\n";
+
+        assert_eq!(
+            ConfigHandler::replace_setting(content, "control.task", "abc"),
+            "; comment\nstatus.sinks = polybar\ncontrol.task = abc\n"
+        );
+        assert_eq!(
+            ConfigHandler::replace_setting(
+                "control.task = abc\nstatus.sinks = polybar\n",
+                "control.task",
+                "def"
+            ),
+            "control.task = def\nstatus.sinks = polybar\n"
+        );
+        // Only the same key
+        assert_eq!(
+            ConfigHandler::replace_setting("control.tasks = abc", "control.task", "def"),
+            "control.tasks = abc\ncontrol.task = def\n"
+        );
+    }
+}
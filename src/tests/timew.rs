@@ -0,0 +1,314 @@
+#[cfg(test)]
+pub mod data {
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use serde_json::json;
+
+    use crate::backends::dry_run;
+    use crate::backends::memory::MemoryTaskBackend;
+    use crate::backends::task as task_backend;
+    use crate::backends::time::{
+        self as time_backend, DataFilter, TimeBackend, TimewarriorDataBackend,
+    };
+    use crate::commands::timew;
+    use crate::values::enums::{OutputFormat, TimewAction};
+    use crate::values::err::FypmError;
+    use crate::values::structs::TimeWarriorExported;
+
+    const WRITE_TESTS_TASK: &str = "0191c8a4-0000-7000-8000-000000000001";
+    const QUOTED_BUG_TASK: &str = "0191c8a4-0000-7000-8000-000000000002";
+
+    /// Reads the fixture database and records the mutations instead of running `timew`.
+    pub struct FixtureTimeBackend {
+        pub reader: TimewarriorDataBackend,
+        pub calls: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl TimeBackend for FixtureTimeBackend {
+        fn export(&self, filter: &[String]) -> Result<Vec<TimeWarriorExported>, FypmError> {
+            self.reader.export(filter)
+        }
+        fn modify(&self, action: &TimewAction, id: &str, time: &str) -> Result<String, FypmError> {
+            let action = match action {
+                TimewAction::Start => "start",
+                TimewAction::End => "end",
+            };
+            self.calls.borrow_mut().push(
+                ["modify", action, id, time]
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect(),
+            );
+            Ok(String::new())
+        }
+        fn track(&self, start: &str, end: &str, tags: &[String]) -> Result<String, FypmError> {
+            let mut call = vec!["track".to_string(), start.to_string(), end.to_string()];
+            call.extend(tags.iter().cloned());
+            self.calls.borrow_mut().push(call);
+            Ok(String::new())
+        }
+        fn continue_interval(&self, id: &str, start: &str, end: &str) -> Result<String, FypmError> {
+            self.calls.borrow_mut().push(
+                ["continue", id, start, end]
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect(),
+            );
+            Ok(String::new())
+        }
+        fn annotate(&self, ids: &str, annotation: &str) -> Result<String, FypmError> {
+            self.calls.borrow_mut().push(vec![
+                "annotate".to_string(),
+                ids.to_string(),
+                annotation.to_string(),
+            ]);
+            Ok(String::new())
+        }
+        fn start(&self, tags: &[String]) -> Result<String, FypmError> {
+            self.calls
+                .borrow_mut()
+                .push(time_backend::interval_args("start", tags));
+            Ok(String::new())
+        }
+        fn stop(&self, tags: &[String]) -> Result<String, FypmError> {
+            self.calls
+                .borrow_mut()
+                .push(time_backend::interval_args("stop", tags));
+            Ok(String::new())
+        }
+        fn tag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+            self.calls
+                .borrow_mut()
+                .push(time_backend::tag_args("tag", id, tags));
+            Ok(String::new())
+        }
+        fn untag(&self, id: &str, tags: &[String]) -> Result<String, FypmError> {
+            self.calls
+                .borrow_mut()
+                .push(time_backend::tag_args("untag", id, tags));
+            Ok(String::new())
+        }
+    }
+
+    pub fn fixture_reader() -> TimewarriorDataBackend {
+        TimewarriorDataBackend::new(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures/timewarrior"),
+        )
+    }
+    fn mock_backends() -> Rc<FixtureTimeBackend> {
+        let time = Rc::new(FixtureTimeBackend {
+            reader: fixture_reader(),
+            calls: RefCell::new(Vec::new()),
+        });
+        let tasks = Rc::new(MemoryTaskBackend::new());
+
+        for (uuid, description) in [
+            (WRITE_TESTS_TASK, "Write tests"),
+            (QUOTED_BUG_TASK, "Fix \"quoted\" bug"),
+        ] {
+            tasks.insert(json!({
+                "uuid": uuid,
+                "description": description,
+                "project": "Fypm",
+                "STYLE": "Apollonian",
+                "TYPE": "Objective",
+                "STATE": "Time",
+                "WT": "General",
+                "tags": ["Tested"],
+            }));
+        }
+
+        time_backend::set_current(time.clone());
+        task_backend::set_current(tasks);
+
+        time
+    }
+
+    #[test]
+    pub fn read_intervals() {
+        let intervals = fixture_reader().export(&[]).unwrap();
+
+        assert_eq!(intervals.len(), 4);
+        assert_eq!(
+            intervals
+                .iter()
+                .map(|interval| interval.id)
+                .collect::<Vec<i32>>(),
+            vec![4, 3, 2, 1]
+        );
+        assert_eq!(
+            intervals[0].tags,
+            Some(vec![
+                WRITE_TESTS_TASK.to_string(),
+                "Write tests".to_string(),
+                "Objective".to_string()
+            ])
+        );
+        assert_eq!(intervals[1].annotation, Some("Lost some time".to_string()));
+        assert_eq!(
+            intervals[2].tags.as_ref().unwrap()[1],
+            "Fix \"quoted\" bug".to_string()
+        );
+        assert_eq!(intervals[3].start, "20240201T100000Z");
+        assert_eq!(intervals[3].end, None);
+    }
+    #[test]
+    pub fn export_filters() {
+        let reader = fixture_reader();
+        let export = |filter: &[&str]| {
+            reader
+                .export(
+                    &filter
+                        .iter()
+                        .map(|word| word.to_string())
+                        .collect::<Vec<String>>(),
+                )
+                .unwrap()
+                .iter()
+                .map(|interval| interval.id)
+                .collect::<Vec<i32>>()
+        };
+
+        assert_eq!(export(&[]), vec![4, 3, 2, 1]);
+        assert_eq!(export(&["@1", "@3"]), vec![1, 3]);
+        assert_eq!(export(&["2024-01-01", "-", "2024-01-31"]), vec![4, 3]);
+        assert_eq!(export(&["20240201T090000Z"]), vec![2, 1]);
+        assert_eq!(export(&["Lost"]), vec![3]);
+        assert!(reader.export(&["2024-13-01".to_string()]).is_err());
+    }
+    #[test]
+    pub fn export_hints() {
+        let parse = |filter: &[&str]| {
+            TimewarriorDataBackend::parse_filter(
+                &filter
+                    .iter()
+                    .map(|word| word.to_string())
+                    .collect::<Vec<String>>(),
+            )
+            .unwrap()
+        };
+
+        // Left to `timew`, instead of being read as tags
+        for hint in [
+            ":week",
+            "yesterday",
+            "sod",
+            "monday",
+            "2024-W01",
+            "2024-13-01",
+            "1h",
+        ] {
+            assert_eq!(parse(&["Lost", hint]), None, "{}", hint);
+        }
+
+        assert_eq!(
+            parse(&["@2", "Lost", "from", "2024-01-01"]).map(|filter| (filter.ids, filter.tags)),
+            Some((vec![2], vec!["Lost".to_string()]))
+        );
+        assert_eq!(parse(&[]), Some(DataFilter::default()));
+        assert!(TimewarriorDataBackend::parse_filter(&["@x".to_string()]).is_err());
+    }
+    #[test]
+    pub fn invalid_data() {
+        let database = std::env::temp_dir().join(format!("fypm-timew-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(database.join("data")).unwrap();
+        let reader = TimewarriorDataBackend::new(database.clone());
+
+        std::fs::write(
+            database.join("data/2024-01.data"),
+            "inc 20240110T080000Z - 20240110T090000Z # Good\n",
+        )
+        .unwrap();
+        assert_eq!(reader.export(&[]).unwrap().len(), 1);
+
+        // The cache is read again when a file changes
+        std::fs::write(
+            database.join("data/2024-01.data"),
+            "inc 20240110T080000Z - 20240110T090000Z # Good\ninc yesterday - 20240110T100000Z # Bad\n",
+        )
+        .unwrap();
+        assert!(reader.export(&[]).is_err());
+
+        std::fs::remove_dir_all(database).unwrap();
+    }
+    #[test]
+    pub fn replace() {
+        let time = mock_backends();
+
+        timew::replace(&"@3".to_string(), &QUOTED_BUG_TASK.to_string()).unwrap();
+
+        assert_eq!(
+            time.calls.borrow()[0],
+            vec![
+                "track",
+                "20240110T090000Z",
+                "20240110T093000Z",
+                QUOTED_BUG_TASK,
+                "Fix \"quoted\" bug",
+                "General",
+                "Objective",
+                "Apollonian",
+                "Fypm",
+                "Tested",
+            ]
+        );
+    }
+    #[test]
+    pub fn start_correction() {
+        let time = mock_backends();
+
+        timew::move_log(&TimewAction::Start, &"@2".to_string(), &None).unwrap();
+        timew::move_log(
+            &TimewAction::End,
+            &"@4".to_string(),
+            &Some("@2".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            time.calls.borrow()[0],
+            vec!["modify", "start", "@2", "20240110T090000Z"]
+        );
+        assert_eq!(
+            time.calls.borrow()[1],
+            vec!["modify", "end", "@4", "20240201T080000Z"]
+        );
+    }
+    #[test]
+    pub fn list() {
+        mock_backends();
+
+        for format in [OutputFormat::Pretty, OutputFormat::Json, OutputFormat::Csv] {
+            timew::list(
+                &"2024-01-01".to_string(),
+                &Some("2024-03-01".to_string()),
+                &format,
+            )
+            .unwrap();
+        }
+    }
+    #[test]
+    pub fn dry_run_replace() {
+        mock_backends();
+        time_backend::set_current(Rc::new(fixture_reader()));
+        let recorder = dry_run::enable();
+
+        timew::replace(&"@4".to_string(), &QUOTED_BUG_TASK.to_string()).unwrap();
+
+        let mutations = recorder.mutations();
+        assert_eq!(mutations.len(), 1);
+        assert_eq!(
+            mutations[0].command[..5],
+            [
+                "timew",
+                "track",
+                "20240110T080000Z",
+                "-",
+                "20240110T090000Z"
+            ]
+        );
+        assert_eq!(mutations[0].command.last().unwrap(), ":adjust");
+    }
+}
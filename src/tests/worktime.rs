@@ -0,0 +1,581 @@
+#[cfg(test)]
+pub mod daemon {
+    use chrono::NaiveTime;
+
+    use crate::db::models::Worktime;
+    use crate::handlers::worktime::WorktimeHandler;
+
+    pub fn worktime(name: &str, start_time: &str, end_time: &str) -> Worktime {
+        Worktime {
+            id: name.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            style: "Necessity".to_string(),
+            start_time: start_time.to_string(),
+            end_time: end_time.to_string(),
+            polybar_background: "#000".to_string(),
+            polybar_foreground: "#fff".to_string(),
+        }
+    }
+    fn time(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+    fn active(worktimes: &[Worktime], at: &str) -> Option<String> {
+        WorktimeHandler::get_active(worktimes, &time(at)).map(|worktime| worktime.name.clone())
+    }
+
+    #[test]
+    pub fn active_worktime() {
+        let worktimes = vec![
+            worktime("General", "08:00", "18:00"),
+            worktime("Lunch", "12:00", "13:00"),
+            worktime("Night", "22:00", "02:00"),
+        ];
+
+        assert_eq!(active(&worktimes, "07:59"), None);
+        assert_eq!(active(&worktimes, "08:00").as_deref(), Some("General"));
+        assert_eq!(active(&worktimes, "12:30").as_deref(), Some("Lunch"));
+        assert_eq!(active(&worktimes, "13:00").as_deref(), Some("General"));
+        assert_eq!(active(&worktimes, "18:00"), None);
+        assert_eq!(active(&worktimes, "23:00").as_deref(), Some("Night"));
+        assert_eq!(active(&worktimes, "01:00").as_deref(), Some("Night"));
+    }
+    #[test]
+    pub fn next_boundary() {
+        let worktimes = vec![
+            worktime("General", "08:00", "18:00"),
+            worktime("Lunch", "12:00", "13:00"),
+        ];
+
+        assert_eq!(
+            WorktimeHandler::next_boundary(&worktimes, &time("09:00")),
+            Some(time("12:00"))
+        );
+        assert_eq!(
+            WorktimeHandler::next_boundary(&worktimes, &time("12:00")),
+            Some(time("13:00"))
+        );
+        assert_eq!(
+            WorktimeHandler::next_boundary(&worktimes, &time("20:00")),
+            Some(time("08:00"))
+        );
+        assert_eq!(WorktimeHandler::next_boundary(&[], &time("20:00")), None);
+    }
+}
+#[cfg(test)]
+pub mod schedule {
+    use chrono::{NaiveDate, NaiveDateTime, Weekday};
+    use diesel::RunQueryDsl;
+
+    use super::daemon::worktime;
+    use crate::db::models::WorktimeSchedule;
+    use crate::db::schema::worktimes;
+    use crate::handlers::worktime::WorktimeHandler;
+    use crate::tests::TestDatabase;
+    use crate::values::enums::ScheduleDay;
+
+    pub fn schedule(worktime_id: Option<&str>, day: &str) -> WorktimeSchedule {
+        let is_date = day.contains('-');
+
+        WorktimeSchedule {
+            id: uuid::Uuid::now_v7().to_string(),
+            worktime_id: worktime_id.map(|id| id.to_string()),
+            weekday: (!is_date).then(|| day.to_string()),
+            date: is_date.then(|| day.to_string()),
+            description: None,
+        }
+    }
+    fn at(datetime: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M").unwrap()
+    }
+    fn days(args: &[&str]) -> Vec<ScheduleDay> {
+        WorktimeHandler::parse_schedule_days(
+            &args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn parse_days() {
+        assert_eq!(days(&["weekdays"]).len(), 5);
+        assert_eq!(
+            days(&["Sat", "sunday", "2024-12-25"]),
+            vec![
+                ScheduleDay::Weekday(Weekday::Sat),
+                ScheduleDay::Weekday(Weekday::Sun),
+                ScheduleDay::Date(NaiveDate::from_ymd_opt(2024, 12, 25).unwrap()),
+            ]
+        );
+        assert!(WorktimeHandler::parse_schedule_days(&["someday".to_string()]).is_err());
+        assert!(WorktimeHandler::parse_schedule_days(&[]).is_err());
+    }
+    #[test]
+    pub fn current_worktime() {
+        let worktimes = vec![
+            worktime("Work", "08:00", "18:00"),
+            worktime("Rest", "10:00", "20:00"),
+            worktime("Party", "22:00", "03:00"),
+            worktime("Reading", "06:00", "07:00"),
+        ];
+        let schedules = vec![
+            schedule(Some("Work"), "Mon"),
+            schedule(Some("Work"), "Fri"),
+            schedule(Some("Rest"), "Sat"),
+            schedule(Some("Party"), "Fri"),
+            // Christmas on a wednesday, a new year's eve party on a tuesday.
+            schedule(None, "2024-12-25"),
+            schedule(Some("Party"), "2024-12-31"),
+        ];
+        let current = |datetime: &str| {
+            WorktimeHandler::get_current(&worktimes, &schedules, &at(datetime))
+                .map(|worktime| worktime.name.clone())
+        };
+
+        // 2024-12-23 is a monday.
+        assert_eq!(current("2024-12-23 09:00").as_deref(), Some("Work"));
+        assert_eq!(current("2024-12-23 19:00"), None);
+        // Never scheduled, so it's used in any day without a date schedule.
+        assert_eq!(current("2024-12-24 06:30").as_deref(), Some("Reading"));
+        assert_eq!(current("2024-12-25 06:30"), None);
+        assert_eq!(current("2024-12-27 23:00").as_deref(), Some("Party"));
+        // The party of friday goes on in saturday.
+        assert_eq!(current("2024-12-28 02:00").as_deref(), Some("Party"));
+        assert_eq!(current("2024-12-28 23:00"), None);
+        assert_eq!(current("2024-12-28 11:00").as_deref(), Some("Rest"));
+        assert_eq!(current("2024-12-31 09:00"), None);
+        assert_eq!(current("2025-01-01 01:00").as_deref(), Some("Party"));
+    }
+    #[test]
+    pub fn manage_schedule() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        diesel::insert_into(worktimes::table)
+            .values(vec![
+                worktime("Work", "08:00", "18:00"),
+                worktime("Rest", "10:00", "20:00"),
+            ])
+            .execute(conn)
+            .unwrap();
+
+        WorktimeHandler::schedule_add(conn, &"Work".to_string(), &days(&["weekdays"])).unwrap();
+        WorktimeHandler::schedule_add(conn, &"Work".to_string(), &days(&["mon"])).unwrap();
+        WorktimeHandler::schedule_add(conn, &"Rest".to_string(), &days(&["weekend", "mon"]))
+            .unwrap();
+        assert!(
+            WorktimeHandler::schedule_add(conn, &"Nothing".to_string(), &days(&["mon"])).is_err()
+        );
+        assert_eq!(WorktimeHandler::get_schedules(conn).unwrap().len(), 8);
+
+        WorktimeHandler::schedule_holiday(
+            conn,
+            &days(&["2024-12-25"]),
+            &Some("Christmas".to_string()),
+        )
+        .unwrap();
+        assert!(WorktimeHandler::schedule_holiday(conn, &days(&["fri"]), &None).is_err());
+
+        assert_eq!(
+            WorktimeHandler::schedule_remove(conn, &Some("Rest".to_string()), &days(&["mon"]))
+                .unwrap(),
+            1
+        );
+
+        let records = WorktimeHandler::get_schedule_records(conn).unwrap();
+        assert_eq!(records.len(), 8);
+        assert_eq!(records[0].day, "Mon");
+        assert_eq!(records[0].worktime.as_deref(), Some("Work"));
+        assert_eq!(records[7].day, "2024-12-25");
+        assert_eq!(records[7].worktime, None);
+        assert_eq!(records[7].description.as_deref(), Some("Christmas"));
+
+        // Adding a worktime to a holiday makes it a normal date.
+        WorktimeHandler::schedule_add(conn, &"Rest".to_string(), &days(&["2024-12-25"])).unwrap();
+        let records = WorktimeHandler::get_schedule_records(conn).unwrap();
+        assert_eq!(records[7].worktime.as_deref(), Some("Rest"));
+
+        WorktimeHandler::remove(conn, &"Work".to_string()).unwrap();
+        assert_eq!(WorktimeHandler::get_schedules(conn).unwrap().len(), 3);
+    }
+}
+#[cfg(test)]
+pub mod contexts {
+    use crate::handlers::config::ConfigHandler;
+
+    use super::daemon::worktime;
+
+    #[test]
+    pub fn worktime_filter() {
+        let settings = ConfigHandler::create_settings_defaults();
+
+        assert_eq!(
+            ConfigHandler::get_worktime_filter("General", &settings),
+            "((+ACTIVE or +OVERDUE or +Divisory or (((due:today or due.after:today) and due.before:tomorrow) and (WT:Quantify or WT:NonSched)) or (WT:General or WT:AllDay) and ((+TODAY and +INSTANCE) or ((scheduled.after:today or scheduled:today) and scheduled.before:tomorrow))) and status:pending) or (((due:today or due.after:today) and due.before:tomorrow) and WT:AllDay and (status.not:recurring and status.not:waiting))"
+        );
+    }
+    #[test]
+    pub fn worktime_configs() {
+        let settings = ConfigHandler::create_settings_defaults();
+        let worktimes = vec![
+            worktime("General", "08:00", "18:00"),
+            worktime("Night", "22:00", "02:00"),
+        ];
+
+        let configs = ConfigHandler::get_worktime_configs(&worktimes, &None, &settings);
+        assert!(configs.contains_key("context.General.read"));
+        assert!(configs.contains_key("context.Night.read"));
+        assert!(!configs.contains_key("context"));
+
+        let configs =
+            ConfigHandler::get_worktime_configs(&worktimes, &Some("Night".to_string()), &settings);
+        assert_eq!(
+            configs.get("context").map(|name| name.as_str()),
+            Some("Night")
+        );
+        assert_eq!(
+            configs.get("uda.taskwarrior-tui.task-report.next.filter"),
+            Some(&ConfigHandler::get_worktime_filter("Night", &settings))
+        );
+    }
+}
+#[cfg(test)]
+pub mod edit {
+    use diesel::RunQueryDsl;
+
+    use super::daemon::worktime;
+    use crate::db::models::Worktime;
+    use crate::db::schema::worktimes;
+    use crate::handlers::worktime::WorktimeHandler;
+    use crate::tests::TestDatabase;
+    use crate::values::err::FypmErrorKind;
+
+    fn styles() -> Vec<String> {
+        vec!["Necessity".to_string(), "Creative".to_string()]
+    }
+    fn overlaps(worktime: &Worktime, worktimes: &[Worktime]) -> Vec<String> {
+        WorktimeHandler::get_overlaps(worktime, worktimes)
+            .iter()
+            .map(|other| other.name.clone())
+            .collect()
+    }
+
+    #[test]
+    pub fn verify_worktime() {
+        assert!(WorktimeHandler::verify(&worktime("Night", "22:00", "02:00"), &styles()).is_ok());
+
+        let same_time = worktime("Zero", "08:00", "08:00");
+        let bad_time = worktime("Late", "25:00", "08:00");
+        let mut bad_style = worktime("Work", "08:00", "18:00");
+        bad_style.style = "Idle".to_string();
+        let mut bad_color = worktime("Work", "08:00", "18:00");
+        bad_color.polybar_background = "#zzz".to_string();
+
+        for worktime in [same_time, bad_time, bad_style, bad_color] {
+            assert_eq!(
+                WorktimeHandler::verify(&worktime, &styles())
+                    .unwrap_err()
+                    .kind,
+                FypmErrorKind::InvalidInput
+            );
+        }
+    }
+    #[test]
+    pub fn overlapping_windows() {
+        let worktimes = vec![
+            worktime("General", "08:00", "18:00"),
+            worktime("Night", "22:00", "02:00"),
+        ];
+
+        assert_eq!(
+            overlaps(&worktime("Lunch", "12:00", "13:00"), &worktimes),
+            vec!["General"]
+        );
+        assert_eq!(
+            overlaps(&worktime("Evening", "18:00", "22:00"), &worktimes),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            overlaps(&worktime("Dawn", "01:00", "08:00"), &worktimes),
+            vec!["Night"]
+        );
+        assert_eq!(
+            overlaps(&worktime("Late", "23:00", "09:00"), &worktimes),
+            vec!["General", "Night"]
+        );
+        // A worktime doesn't overlap itself
+        assert_eq!(
+            overlaps(&worktime("General", "09:00", "17:00"), &worktimes),
+            Vec::<String>::new()
+        );
+    }
+    #[test]
+    pub fn get_missing_worktime() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        diesel::insert_into(worktimes::table)
+            .values(worktime("Work", "08:00", "18:00"))
+            .execute(conn)
+            .unwrap();
+
+        assert_eq!(
+            WorktimeHandler::get(conn, &"Work".to_string())
+                .unwrap()
+                .name,
+            "Work"
+        );
+        assert_eq!(
+            WorktimeHandler::get(conn, &"Rest".to_string())
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::NotFound
+        );
+    }
+}
+#[cfg(test)]
+pub mod report {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use super::daemon::worktime;
+    use super::schedule::schedule;
+    use crate::handlers::worktime::{WorktimeHandler, OFF_WORKTIME};
+    use crate::values::structs::{TrackedInterval, WorktimeReportRecord};
+
+    fn interval(start: &str, end: &str, worktime: Option<&str>) -> TrackedInterval {
+        let parse = |date| NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap();
+
+        TrackedInterval {
+            start: parse(start),
+            end: parse(end),
+            worktime: worktime.map(|worktime| worktime.to_string()),
+        }
+    }
+    fn record(
+        date: &str,
+        worktime: &str,
+        [planned, tracked, other, untracked]: [i64; 4],
+    ) -> WorktimeReportRecord {
+        WorktimeReportRecord {
+            date: date.to_string(),
+            worktime: worktime.to_string(),
+            planned,
+            tracked,
+            other,
+            untracked,
+        }
+    }
+
+    #[test]
+    pub fn tracked_minutes() {
+        let worktimes = vec![
+            worktime("Work", "08:00", "12:00"),
+            worktime("Night", "23:00", "01:00"),
+        ];
+        let intervals = vec![
+            interval("2024-01-01 07:30", "2024-01-01 09:00", Some("Work")),
+            interval("2024-01-01 10:00", "2024-01-01 10:30", Some("Night")),
+            interval("2024-01-01 11:00", "2024-01-01 11:15", Some("AllDay")),
+            interval("2024-01-01 15:00", "2024-01-01 15:20", None),
+            interval("2024-01-01 23:30", "2024-01-02 00:30", Some("Night")),
+        ];
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        assert_eq!(
+            WorktimeHandler::report(
+                &worktimes,
+                &[],
+                &intervals,
+                &date,
+                &date.succ_opt().unwrap()
+            ),
+            vec![
+                record("2024-01-01", "Night", [120, 60, 0, 60]),
+                record("2024-01-01", OFF_WORKTIME, [0, 50, 0, 0]),
+                record("2024-01-01", "Work", [240, 75, 30, 135]),
+            ]
+        );
+    }
+    #[test]
+    pub fn multiple_weeks() {
+        let worktimes = vec![
+            worktime("Work", "08:00", "12:00"),
+            worktime("Night", "23:00", "01:00"),
+        ];
+        let mut schedules: Vec<_> = ["Mon", "Tue", "Wed", "Thu", "Fri"]
+            .iter()
+            .map(|weekday| schedule(Some("Work"), weekday))
+            .collect();
+        schedules.push(schedule(None, "2024-01-10"));
+        let intervals = vec![interval(
+            "2024-01-15 08:00",
+            "2024-01-16 00:00",
+            Some("Work"),
+        )];
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 22).unwrap();
+
+        let records = WorktimeHandler::report(&worktimes, &schedules, &intervals, &start, &end);
+        let of = |name: &str| {
+            records
+                .iter()
+                .filter(|record| record.worktime == name)
+                .collect::<Vec<_>>()
+        };
+
+        // 15 weekdays and 21 nights, without the holiday
+        assert_eq!(of("Work").len(), 14);
+        assert_eq!(of("Night").len(), 20);
+        assert!(records.iter().all(|record| record.date != "2024-01-10"));
+        assert_eq!(
+            of("Work").iter().map(|record| record.planned).sum::<i64>(),
+            14 * 240
+        );
+        assert_eq!(
+            records
+                .iter()
+                .filter(|record| record.date == "2024-01-15")
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![
+                record("2024-01-15", "Night", [120, 0, 60, 60]),
+                record("2024-01-15", OFF_WORKTIME, [0, 660, 0, 0]),
+                record("2024-01-15", "Work", [240, 240, 0, 0]),
+            ]
+        );
+    }
+}
+#[cfg(test)]
+pub mod import {
+    use diesel::RunQueryDsl;
+
+    use super::daemon::worktime;
+    use crate::db::schema::worktimes;
+    use crate::handlers::worktime::WorktimeHandler;
+    use crate::tests::TestDatabase;
+    use crate::values::enums::ImportConflict;
+    use crate::values::err::FypmErrorKind;
+    use crate::values::structs::{HolidayEntry, WorktimesFile};
+
+    fn styles() -> Vec<String> {
+        vec!["Necessity".to_string()]
+    }
+
+    #[test]
+    pub fn round_trip() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        diesel::insert_into(worktimes::table)
+            .values(vec![
+                worktime("Work", "08:00", "18:00"),
+                worktime("Night", "22:00", "02:00"),
+            ])
+            .execute(conn)
+            .unwrap();
+        let days = |args: &[&str]| {
+            WorktimeHandler::parse_schedule_days(
+                &args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>(),
+            )
+            .unwrap()
+        };
+        WorktimeHandler::schedule_add(conn, &"Work".to_string(), &days(&["weekdays"])).unwrap();
+        WorktimeHandler::schedule_holiday(
+            conn,
+            &days(&["2024-12-25"]),
+            &Some("Christmas".to_string()),
+        )
+        .unwrap();
+
+        let content = toml::to_string_pretty(&WorktimeHandler::export(conn).unwrap()).unwrap();
+        let file: WorktimesFile = toml::from_str(&content).unwrap();
+
+        let mut other_database = TestDatabase::new();
+        let other_conn = other_database.conn();
+        let imported =
+            WorktimeHandler::import(other_conn, &file, &ImportConflict::Abort, &styles(), &false)
+                .unwrap();
+        assert_eq!(imported, vec!["Work", "Night"]);
+        assert_eq!(WorktimeHandler::export(other_conn).unwrap(), file);
+        assert_eq!(file.worktimes[0].schedule.len(), 5);
+        assert_eq!(file.holidays[0].date, "2024-12-25");
+    }
+    #[test]
+    pub fn conflicts() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        diesel::insert_into(worktimes::table)
+            .values(worktime("Work", "08:00", "18:00"))
+            .execute(conn)
+            .unwrap();
+
+        let mut file = WorktimeHandler::export(conn).unwrap();
+        file.worktimes[0].end_time = "17:00".to_string();
+        file.worktimes[0].schedule = vec!["Mon".to_string()];
+
+        assert_eq!(
+            WorktimeHandler::import(conn, &file, &ImportConflict::Abort, &styles(), &false)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::AlreadyExists
+        );
+        assert!(
+            WorktimeHandler::import(conn, &file, &ImportConflict::Skip, &styles(), &false)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            WorktimeHandler::get(conn, &"Work".to_string())
+                .unwrap()
+                .end_time,
+            "18:00"
+        );
+
+        WorktimeHandler::import(conn, &file, &ImportConflict::Replace, &styles(), &false).unwrap();
+        assert_eq!(WorktimeHandler::export(conn).unwrap(), file);
+
+        file.worktimes[0].style = "Idle".to_string();
+        assert_eq!(
+            WorktimeHandler::import(conn, &file, &ImportConflict::Replace, &styles(), &false)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::InvalidInput
+        );
+    }
+    #[test]
+    pub fn overlaps_and_rollback() {
+        let mut database = TestDatabase::new();
+        let conn = database.conn();
+
+        diesel::insert_into(worktimes::table)
+            .values(worktime("Work", "08:00", "18:00"))
+            .execute(conn)
+            .unwrap();
+
+        let mut file = WorktimeHandler::export(conn).unwrap();
+        file.worktimes[0].name = "Lunch".to_string();
+        file.worktimes[0].start_time = "12:00".to_string();
+        file.worktimes[0].end_time = "13:00".to_string();
+
+        assert_eq!(
+            WorktimeHandler::import(conn, &file, &ImportConflict::Abort, &styles(), &false)
+                .unwrap_err()
+                .kind,
+            FypmErrorKind::InvalidInput
+        );
+
+        // The holiday fails after the worktime is saved, so nothing is kept
+        file.holidays.push(HolidayEntry {
+            date: "Mon".to_string(),
+            description: None,
+        });
+        assert!(
+            WorktimeHandler::import(conn, &file, &ImportConflict::Abort, &styles(), &true).is_err()
+        );
+        assert_eq!(WorktimeHandler::get_all(conn).unwrap().len(), 1);
+
+        file.holidays.clear();
+        WorktimeHandler::import(conn, &file, &ImportConflict::Abort, &styles(), &true).unwrap();
+        assert_eq!(WorktimeHandler::get_all(conn).unwrap().len(), 2);
+    }
+}
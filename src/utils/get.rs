@@ -1,30 +1,25 @@
-use std::{process::Command, str, vec};
+use std::{str, vec};
 
+use crate::backends;
 use crate::values::structs::{GetJsonByFilterOptions, TaskWarriorExported, TimeWarriorExported};
 use crate::values::{
     enums::TimewAction,
     err::{FypmError, FypmErrorKind},
-    structs,
 };
 
 pub fn json_by_filter(
     filter: &str,
     options: Option<GetJsonByFilterOptions>,
 ) -> Result<Vec<TaskWarriorExported>, FypmError> {
-    let mut args = Vec::new();
+    let mut overrides = Vec::new();
 
     if let Some(options) = &options {
-        if let Some(overrides) = &options.aditional_overrides {
-            args.extend(overrides.clone());
+        if let Some(aditional_overrides) = &options.aditional_overrides {
+            overrides.extend(aditional_overrides.clone());
         }
     }
-    args.extend(vec![filter.to_string(), "export".to_string()]);
 
-    let get_json = Command::new("task").args(args).output().unwrap().stdout;
-
-    let parsed_json =
-        serde_json::from_str::<Vec<TaskWarriorExported>>(str::from_utf8(&get_json).unwrap())
-            .unwrap();
+    let parsed_json = backends::task::current().export(filter, &overrides)?;
 
     if let Some(options) = options {
         if let Some(quantity) = options.quantity {
@@ -59,18 +54,8 @@ pub fn get_current_task_json() -> Result<TaskWarriorExported, FypmError> {
 }
 pub fn get_timew_json_by_filter(
     filter: &Vec<String>,
-) -> Result<Vec<TimeWarriorExported>, serde_json::Error> {
-    let mut args = vec!["export"];
-    args.extend(filter.iter().map(|f| f.as_str()));
-
-    let get_task_info = Command::new("timew")
-        .args(args)
-        .output()
-        .expect("Failed to get timew json!");
-
-    let str_json = str::from_utf8(&get_task_info.stdout).unwrap();
-
-    serde_json::from_str::<Vec<structs::TimeWarriorExported>>(str_json)
+) -> Result<Vec<TimeWarriorExported>, FypmError> {
+    backends::time::current().export(filter)
 }
 pub fn get_timew_time(id: &String, action: &TimewAction) -> String {
     let get_task_json = get_timew_json_by_filter(&vec![id.clone()]).unwrap();
@@ -86,16 +71,7 @@ pub fn get_uuids_by_filter(
     filter: &str,
     options: Option<GetJsonByFilterOptions>,
 ) -> Result<Vec<String>, FypmError> {
-    let uuids = String::from_utf8_lossy(
-        &Command::new("task")
-            .args([filter, "_uuids"])
-            .output()
-            .unwrap()
-            .stdout,
-    )
-    .lines()
-    .map(|line| line.to_string())
-    .collect::<Vec<String>>();
+    let uuids = backends::task::current().uuids(filter)?;
 
     if let Some(options) = options {
         if let Some(quantity) = options.quantity {
@@ -115,48 +91,13 @@ pub fn get_uuids_by_filter(
 
     Ok(uuids)
 }
-pub fn get_count_by_filter(filter: &String) -> Result<u32, FypmError> {
-    let mut tasks_count: u32 = 0;
-
-    let tasks_length = String::from_utf8(
-        Command::new("task")
-            .args([filter, &"count".to_string()])
-            .output()
-            .unwrap()
-            .stdout,
-    )
-    .unwrap();
-
-    if tasks_length != "" {
-        tasks_count = tasks_length.trim().parse::<u32>().unwrap();
-    }
-
-    Ok(tasks_count)
+pub fn get_count_by_filter(filter: &str) -> Result<u32, FypmError> {
+    backends::task::current().count(filter)
 }
 pub fn filter_by_modifier(modifier: &String) -> Result<String, FypmError> {
     let cfg_key = format!("report.{modifier}.filter");
 
-    let get_configs = String::from_utf8(
-        Command::new("task")
-            .args(["show", cfg_key.as_str()])
-            .output()
-            .unwrap()
-            .stdout,
-    )
-    .unwrap();
-
-    let lines_with_config = get_configs
-        .split("\n")
-        .filter(|line| line.contains(cfg_key.as_str()))
-        .collect::<Vec<&str>>()
-        .join("\n");
-
-    let config = lines_with_config
-        .replace(cfg_key.as_str(), "")
-        .trim()
-        .to_string();
-
-    Ok(config)
+    backends::task::current().show_config(&cfg_key)
 }
 
 pub fn mother_json_by_sequence_id(seq_id: &String) -> Result<TaskWarriorExported, FypmError> {
@@ -1,4 +1,5 @@
 pub mod get;
 pub mod verify;
 pub mod extract;
-pub mod term;
\ No newline at end of file
+pub mod term;
+pub mod output;
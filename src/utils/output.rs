@@ -0,0 +1,119 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::values::{
+    enums::OutputFormat,
+    err::{FypmError, FypmErrorKind},
+};
+
+fn serialization_error(error: impl std::fmt::Display) -> FypmError {
+    FypmError {
+        message: format!("Failed to serialize the output: {}", error),
+        kind: FypmErrorKind::InvalidInput,
+    }
+}
+
+/// Print records in a machine-readable format. Returns `false` if the format is `Pretty`,
+/// so the caller has to print it by itself.
+pub fn print_records<T: Serialize + Default>(
+    format: &OutputFormat,
+    records: &[T],
+) -> Result<bool, FypmError> {
+    match format {
+        OutputFormat::Pretty => Ok(false),
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(records).map_err(serialization_error)?
+            );
+
+            Ok(true)
+        }
+        OutputFormat::Csv => {
+            print!("{}", to_csv(records)?);
+
+            Ok(true)
+        }
+    }
+}
+/// Same as `print_records`, but JSON is printed as a single object instead of an array.
+pub fn print_record<T: Serialize>(format: &OutputFormat, record: &T) -> Result<bool, FypmError> {
+    match format {
+        OutputFormat::Pretty => Ok(false),
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(record).map_err(serialization_error)?
+            );
+
+            Ok(true)
+        }
+        OutputFormat::Csv => {
+            print!("{}", write_csv(Vec::new(), std::slice::from_ref(record))?);
+
+            Ok(true)
+        }
+    }
+}
+
+fn to_fields<T: Serialize>(record: &T) -> Result<Map<String, Value>, FypmError> {
+    match serde_json::to_value(record).map_err(serialization_error)? {
+        Value::Object(fields) => Ok(fields),
+        _ => Err(serialization_error("a CSV record must be a struct")),
+    }
+}
+fn to_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(string) => string.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(|s| s.to_string())
+                    .unwrap_or(item.to_string())
+            })
+            .collect::<Vec<String>>()
+            .join(","),
+        other => other.to_string(),
+    }
+}
+/// Render the records as CSV. The header has the fields of `T`, so it's printed even without
+/// records, and the fields that only some records have (ex: skipped when empty) are added
+/// at the end. Lists are joined with commas.
+pub fn to_csv<T: Serialize + Default>(records: &[T]) -> Result<String, FypmError> {
+    let columns = to_fields(&T::default())?.keys().cloned().collect();
+
+    write_csv(columns, records)
+}
+fn write_csv<T: Serialize>(mut columns: Vec<String>, records: &[T]) -> Result<String, FypmError> {
+    let records = records
+        .iter()
+        .map(to_fields)
+        .collect::<Result<Vec<Map<String, Value>>, FypmError>>()?;
+
+    for fields in &records {
+        for key in fields.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns).map_err(serialization_error)?;
+
+    for fields in &records {
+        writer
+            .write_record(
+                columns
+                    .iter()
+                    .map(|column| fields.get(column).map(to_cell).unwrap_or_default()),
+            )
+            .map_err(serialization_error)?;
+    }
+
+    let bytes = writer.into_inner().map_err(serialization_error)?;
+
+    String::from_utf8(bytes).map_err(serialization_error)
+}
@@ -50,4 +50,47 @@ pub const OVERLAY_CONFIG_COMMENT: &str =
 ; In this file, you will define values ​​to override any configuration.
 ;
 ; -> Do not use it to override values ​​that you have written in other configuration files,
-;    as this does not make sense and will cause a lot of confusion.";
\ No newline at end of file
+;    as this does not make sense and will cause a lot of confusion.";
+
+pub const RULES_CONFIG_COMMENT: &str =
+"; ----------> Rules config
+; In this file, you will write the invariants of your tasks. They are checked by the fypm hooks
+; when a task is added or modified, and by 'fypm verify rules'.
+;
+; -> Each rule is written as 'rule.<name>.<field>', where the fields are:
+;    when     Conditions that select the tasks of the rule (all of them must match)
+;    require  Conditions that the selected tasks must match
+;    forbid   Conditions that the selected tasks must not match
+;    message  Error shown when the rule is broken
+;    enabled  Set it to 'false' to disable a rule (fypm rules too)
+;    A condition is 'ATTRIBUTE:VALUE', 'ATTRIBUTE' (it must be set) or '+TAG', separated by spaces.
+;
+; -> fypm rules (habit_recur, habit_state, event_worktime, check_state, continuous_state,
+;    continuous_alias and mother_objective) can be changed here too, field by field.
+;
+; ex: rule.project_style.when = TYPE:Objective
+;     rule.project_style.require = project STYLE
+;     rule.project_style.message = Objective tasks must have a project and a STYLE!";
+
+pub const SETTINGS_CONFIG_COMMENT: &str =
+"; ----------> fypm settings
+; In this file, you will write values for fypm itself. They are not sent to taskwarrior.
+;
+; -> control.task: the uuid of the control task, the task of the time without specific use.
+;    It's started when the active task is stopped or done, and it can be used as the alias 't'.
+;    Set it with `fypm control set <filter>`.
+;
+; -> instance.current: the instance whose Taskwarrior and Timewarrior data is used ('default' for
+;    the usual data). Set it with `fypm instance switch <name>`, or use `--instance` once.
+;
+; -> status.sinks: where the status (worktime, active task and score) is written when it changes.
+;    Separated by commas, the sinks are: polybar, waybar, i3blocks, plain and json.
+;    Each sink writes to 'status.<sink>.path' (for polybar, it's a directory).
+;
+; -> worktime.filter.*: the filters used to create a taskwarrior context for each worktime.
+;    The filter of a worktime is '(base or its tasks of today) and status:pending', plus the
+;    AllDay tasks due today. The tasks of today match 'essential' or 'scheduled', and '{due}'
+;    in 'base' is replaced by the 'due' filter.
+;
+; ex: status.sinks = polybar,waybar
+;     status.waybar.path = /tmp/fypm-waybar.json";
@@ -1,20 +1,23 @@
 use super::structs::GetJsonByFilterOptions;
 
-pub const LAST_TASK_PATH: &str = "/tmp/.last_task";
+/// Format of the dates in Taskwarrior exports (ex: `20240110T090000Z`).
+pub const TASKWARRIOR_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
 
-//. DEV: Switch this string for a dynamic system
-pub const CONTROL_TASK: &str = "5c847c7e-c7eb-44f6-ad7e-29cc989c8854";
+/// Env var with the directory of the fypm data of the current instance, set by `InstanceHandler::apply`.
+pub const INSTANCE_PATH_VAR: &str = "FYPM_INSTANCE_PATH";
 
 pub const DEFAULT_GET_JSON_OPTIONS: Option<GetJsonByFilterOptions> = Some(GetJsonByFilterOptions {
     quantity: Some(1),
     aditional_overrides: None,
 });
 
-pub const DEFAULT_CONFIG_FILES: [&str; 6] = [
+pub const DEFAULT_CONFIG_FILES: [&str; 8] = [
     "task.fypm.ini",
     "uda.fypm.ini",
     "report.fypm.ini",
     "urgency.fypm.ini",
     "colors.fypm.ini",
     "overlay.fypm.ini",
+    "rules.fypm.ini",
+    "settings.fypm.ini",
 ];
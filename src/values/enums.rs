@@ -1,4 +1,4 @@
-use clap::{Subcommand, ValueEnum, Parser};
+use clap::{Args, Subcommand, ValueEnum, Parser};
 use clap_complete::ArgValueCompleter;
 use strum::{Display, EnumString};
 
@@ -11,12 +11,23 @@ use crate::func::completion;
 pub struct Cli {
     #[command(subcommand)]
     pub commands: Commands,
+
+    /// Print the Taskwarrior and Timewarrior mutations instead of executing them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Run against the data of this instance, instead of the current one (`default` for the
+    /// usual data)
+    #[arg(long, global = true)]
+    pub instance: Option<String>,
 }
 
 #[derive(Debug, ValueEnum, Clone, PartialEq)]
 pub enum VerifyScripts {
     /// Verify if exists Continuous tasks without aliases
     Aliases,
+    /// Verify if pending tasks break the rules of rules.fypm.ini
+    Rules,
 }
 
 #[derive(Debug, ValueEnum, Clone, PartialEq, strum_macros::Display)]
@@ -80,16 +91,197 @@ pub enum TimewAction {
 
 #[derive(Debug, ValueEnum, Clone, PartialEq)]
 pub enum AliasActions {
+    /// Save an alias to a task: add <name> <filter...>. With only a filter, it works like the
+    /// deprecated ta-alias add
     Add,
+    /// Remove an alias: remove <name>
+    Remove,
+    List,
+    /// Change the name of an alias: rename <name> <new name>
+    Rename,
+    /// Deprecated, use ta-alias change: change <filter> [alias]
     Change,
 }
 
+#[derive(Debug, ValueEnum, Clone, PartialEq)]
+pub enum TaAliasActions {
+    /// Add an ALIAS to a task: add <filter> [alias]
+    Add,
+    /// Change the ALIAS of a task: change <filter> [alias]
+    Change,
+    /// Remove the ALIAS of a task: remove <filter>
+    Remove,
+    /// Ask an ALIAS to every Continuous task without one
+    Bulk,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Colored text, made to be read by humans
+    #[default]
+    Pretty,
+    Json,
+    Csv,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct OutputArgs {
+    /// Print the output as JSON (same as `--format json`)
+    #[arg(long, conflicts_with = "format")]
+    pub json: bool,
+    /// Format of the output
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl OutputArgs {
+    pub fn format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            self.format
+        }
+    }
+}
+
+/// Columns of a worktime. The ones that are missing in `wt-add` are asked interactively.
+#[derive(Args, Debug, Clone, PartialEq, Default)]
+pub struct WorktimeArgs {
+    /// Description of the worktime
+    #[arg(short, long)]
+    pub description: Option<String>,
+    /// Style of the worktime (a value of the STYLE UDA)
+    #[arg(short, long)]
+    pub style: Option<String>,
+    /// Start of the worktime (HH:MM)
+    #[arg(long)]
+    pub start: Option<String>,
+    /// End of the worktime (HH:MM). If it's before the start, the worktime ends on the next day
+    #[arg(long)]
+    pub end: Option<String>,
+    /// Background color of the polybar module (HEX)
+    #[arg(long)]
+    pub background: Option<String>,
+    /// Foreground color of the polybar module (HEX)
+    #[arg(long)]
+    pub foreground: Option<String>,
+    /// Save the worktime even if its window overlaps the one of another worktime
+    #[arg(long)]
+    pub allow_overlap: bool,
+}
+
+/// Columns of an instance. Without paths, `instance add` creates directories in the fypm data.
+#[derive(Args, Debug, Clone, PartialEq, Default)]
+pub struct InstanceArgs {
+    /// Description of the instance
+    #[arg(short, long)]
+    pub description: Option<String>,
+    /// Taskwarrior data directory of the instance (TASKDATA)
+    #[arg(long)]
+    pub taskdata: Option<String>,
+    /// Timewarrior database directory of the instance (TIMEWARRIORDB)
+    #[arg(long)]
+    pub timewarriordb: Option<String>,
+}
+
+/// What `wt-import` does with the worktimes whose names already exist.
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Default)]
+pub enum ImportConflict {
+    /// Import nothing
+    #[default]
+    Abort,
+    /// Keep the existing worktimes
+    Skip,
+    /// Overwrite the existing worktimes and their schedules
+    Replace,
+}
+
+#[derive(Debug, ValueEnum, Clone, PartialEq)]
+pub enum HookEvent {
+    /// Check the TYPE rules of a task that is being added and move the dates of recurring instances
+    OnAdd,
+    /// Check the TYPE rules of a modification and start/stop its Timewarrior interval
+    OnModify,
+}
+
+#[derive(Debug, ValueEnum, Clone, PartialEq)]
+pub enum HooksActions {
+    /// Install (or update) the fypm hooks in the Taskwarrior hooks directory
+    Install,
+    /// Check if the fypm hooks are installed, executable and up to date
+    Status,
+    /// Remove the fypm hooks
+    Uninstall,
+}
+
+#[derive(Debug, ValueEnum, Clone, PartialEq)]
+pub enum InstanceActions {
+    /// Create an instance: add <name>
+    Add,
+    List,
+    /// Remove an instance (its data is kept): remove <name>
+    Remove,
+    /// Change an instance: edit <name>
+    Edit,
+    /// Use an instance from now on (`default` goes back to the usual data): switch <name>
+    Switch,
+}
+
+#[derive(Debug, ValueEnum, Clone, PartialEq)]
+pub enum ControlActions {
+    /// Use the task of the filter as the control task: set <filter>
+    Set,
+    /// Show the control task
+    Show,
+}
+
+#[derive(Debug, ValueEnum, Clone, PartialEq)]
+pub enum WtScheduleActions {
+    /// Assign a worktime to days: wt-schedule add <worktime> <days...>
+    Add,
+    /// Remove the schedule of days: wt-schedule remove [worktime] <days...>
+    Remove,
+    /// Mark dates as holidays, without worktimes: wt-schedule holiday <dates...>
+    Holiday,
+    /// List the schedule
+    List,
+}
+
+/// A day of the worktime schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleDay {
+    Weekday(chrono::Weekday),
+    Date(chrono::NaiveDate),
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Default, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum StatusFormat {
+    /// One line of text
+    #[default]
+    Plain,
+    /// JSON for a waybar custom module
+    Waybar,
+    /// Full text, short text and color lines for i3blocks
+    I3blocks,
+    /// Every value of the status as JSON
+    Json,
+    /// The worktime and its colors, one file for each (written in a directory)
+    Polybar,
+}
+
 #[derive(Debug, ValueEnum, Clone, PartialEq)]
 pub enum FilterActions {
     Add,
     List,
     Remove,
     Edit,
+    /// Show the tasks of a saved filter: run <name> [report] [key=value...]
+    Run,
+    /// Write the filters as TOML: export [path]
+    Export,
+    /// Read filters from a TOML file written by export: import <path>
+    Import,
 }
 
 #[derive(Subcommand, Debug, PartialEq)]
@@ -100,45 +292,181 @@ pub enum Commands {
     //#endregion
     //#region               Systems
     /// Add a worktime
-    WtAdd { worktime_name: String },
+    WtAdd {
+        worktime_name: String,
+        #[command(flatten)]
+        fields: WorktimeArgs,
+    },
+    /// Edit the columns of a worktime
+    WtEdit {
+        worktime_name: String,
+        /// New name of the worktime
+        #[arg(short, long)]
+        name: Option<String>,
+        #[command(flatten)]
+        fields: WorktimeArgs,
+    },
     /// Remove a worktime
     WtRemove { worktime_name: String },
     /// List worktimes
-    WtLs,
-    /// Apply a worktime
-    WtApply { worktime_name: String },
+    WtLs {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Apply a worktime (by default, the one of the current time in the schedule)
+    WtApply { worktime_name: Option<String> },
+    /// Show the worktime of the current time in the schedule
+    WtCurrent {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Compare the worktime windows with the time tracked on their tasks
+    WtReport {
+        /// To specify range, use: -- -<y|m|w> [year|month|week] OR <start_date> - <end_date> (where format is YYYY-MM-DD)
+        date_args: Option<Vec<String>>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Write the worktimes and their schedules as TOML
+    WtExport {
+        /// File to write (by default, the standard output)
+        path: Option<String>,
+    },
+    /// Read worktimes and their schedules from a TOML file written by wt-export
+    WtImport {
+        path: String,
+        /// What to do with the worktimes that already exist
+        #[arg(long, value_enum, default_value_t)]
+        on_conflict: ImportConflict,
+        /// Import the worktimes even if their windows overlap the ones of other worktimes
+        #[arg(long)]
+        allow_overlap: bool,
+    },
+    /// Manage the days of the worktimes
+    WtSchedule {
+        /// The action to be performed
+        action: WtScheduleActions,
+        /// Worktime and days (mon..sun, weekdays, weekend or YYYY-MM-DD)
+        args: Vec<String>,
+        /// Description of the holidays
+        #[arg(short, long)]
+        description: Option<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Apply the worktime of the current time in background, switching it when a window begins
+    WtDaemon {
+        /// Run in the foreground, without pid and log files
+        #[arg(long)]
+        foreground: bool,
+        /// Stop the running daemon
+        #[arg(long)]
+        stop: bool,
+    },
+
+    /// Show the worktime, the active task, its elapsed time and the score of today
+    Status {
+        /// Format of the output
+        #[arg(long, value_enum, default_value_t)]
+        format: StatusFormat,
+        /// Write the status to the sinks of settings.fypm.ini instead of printing it
+        #[arg(long)]
+        write: bool,
+    },
 
     /// Verify tasks for inconsistencies
     Verify { script: VerifyScripts },
 
-    /// Manage tasks aliases
+    /// Run a Taskwarrior hook (the task JSON is read from stdin)
+    Hook { event: HookEvent },
+    /// Manage the control task, started when nothing else is (alias: t)
+    Control {
+        action: ControlActions,
+        /// Filter to the task (max: 1). Only used by set
+        filter: Option<String>,
+    },
+    /// Manage the fypm hooks of Taskwarrior
+    Hooks {
+        action: HooksActions,
+        #[arg(short = 'y', long)]
+        skip_confirmation: bool,
+    },
+
+    /// Manage the special aliases, short names that can be used instead of a filter or timew id
     Alias {
         /// The action to be performed
         action: AliasActions,
-        /// Filter to task to be manipulated (max: 1)
-        filter: String,
+        /// Arguments of the action (ex: `add <name> <filter...>`, `rename <name> <new name>`)
+        args: Vec<String>,
+        /// Description of the alias (add). The task description is used by default
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Output options (only used by `list`)
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
     /// Manage filters
     Filter {
         /// The action to be performed
         action: FilterActions,
+        /// Arguments of the action (ex: `add <name> <filter...>`, `run <name> [report] [key=value...]`)
+        args: Vec<String>,
+        /// Description of the filter (add and edit)
+        #[arg(short, long)]
+        description: Option<String>,
+        /// New name of the filter (edit)
+        #[arg(short, long)]
+        name: Option<String>,
+        /// What import does with the filters that already exist
+        #[arg(long, value_enum, default_value_t)]
+        on_conflict: ImportConflict,
+        /// Output options (only used by `list`)
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
-    /// Manage instances
+    /// Manage instances, separated Taskwarrior and Timewarrior databases (with their own undo
+    /// journal, task history and aliases)
     Instance {
-        action: String,
-        #[arg(long)]
-        actionargs: Vec<String>,
+        /// The action to be performed
+        action: InstanceActions,
+        /// Arguments of the action (ex: `add <name>`, `switch <name>`)
+        args: Vec<String>,
+        /// New name of the instance (edit)
+        #[arg(short, long)]
+        name: Option<String>,
+        #[command(flatten)]
+        fields: InstanceArgs,
+        /// Output options (only used by `list`)
+        #[command(flatten)]
+        output: OutputArgs,
     },
     /// Initialize day by setting first tasks of the day
     InitDay,
+
+    /// Undo the last fypm operations, restoring every task they touched
+    Undo {
+        /// Quantity of operations to undo
+        #[arg(default_value_t = 1)]
+        quantity: usize,
+        #[arg(short = 'y', long)]
+        skip_confirmation: bool,
+    },
+    /// List the last fypm operations that can be undone
+    History {
+        /// Maximum quantity of operations to list
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+    },
     //#endregion
     //#region               Task Subcommands
     /// Show task information
     TaInfo {
         /// Filter to the task (max: 1)
-        filter: String
+        filter: String,
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
     /// Add a task to taskwarrior (taadd)
@@ -209,6 +537,16 @@ pub enum Commands {
     TaStart { filter: String },
     /// Stop a task (tastop)
     TaStop { filter: Option<String> },
+    /// Start the previous task again (same as `ta-start last`)
+    TaSwitch,
+    /// List the previous tasks, with the aliases to start them again (last, last2...)
+    TaRecent {
+        /// Quantity of tasks to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
     /// Mark a task as done (tadone)
     TaDone {
         tasks_to_done: Option<String>,
@@ -259,6 +597,15 @@ pub enum Commands {
         new_time: String,
     },
 
+    /// Manage the ALIAS of Continuous tasks, that can be used by ta-start and ti-track
+    TaAlias {
+        /// The action to be performed
+        action: TaAliasActions,
+        /// Filter to task to be manipulated (max: 1). Not used by bulk
+        filter: Option<String>,
+        /// The new alias. Without it, an alias is asked with suggestions
+        alias: Option<String>,
+    },
     TaProject {
         #[arg(value_enum)]
         action: TaProjectActions,
@@ -272,6 +619,8 @@ pub enum Commands {
         /// Exclude recurring tasks from the count
         #[arg(short, long)]
         no_parents: bool,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     /// List tasks by date in a separate day/week style (tals-date)
     TaLsDate {
@@ -289,6 +638,8 @@ pub enum Commands {
     TaLsScore {
         /// To specify range, use: -- -<y|m|w> [year|month|week] OR <start_date> - <end_date> (where format is YYYY-MM-DD)
         date_args: Option<Vec<String>>,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     //#endregion
     //#region               Timew Subcommands
@@ -327,6 +678,8 @@ pub enum Commands {
         #[arg(default_value_t = String::from("today"))]
         initial_date: String,
         final_date: Option<String>,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     //#endregion
 }
@@ -476,3 +829,76 @@ pub enum FypmUrgency {
     UrgN30,
     UrgN100,
 }
+
+/// Implement serde for UDA enums with their strum names, so unknown values fall back to `Other`.
+macro_rules! uda_enum_serde {
+    ($($name:ident),*) => {
+        $(
+            impl serde::Serialize for $name {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+            impl<'de> serde::Deserialize<'de> for $name {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+
+                    value.parse().map_err(serde::de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+#[derive(EnumString, Display, Clone, PartialEq, Eq, Debug)]
+pub enum TaskType {
+    Habit,
+    Eventual,
+    Objective,
+    Continuous,
+    Event,
+    Check,
+    Goal,
+    #[strum(default)]
+    Other(String),
+}
+#[derive(EnumString, Display, Clone, PartialEq, Eq, Debug)]
+pub enum TaskStyle {
+    Apollonian,
+    Dionysian,
+    Creative,
+    Necessity,
+    Idle,
+    #[strum(default)]
+    Other(String),
+}
+#[derive(EnumString, Display, Clone, PartialEq, Eq, Debug, Default)]
+pub enum TaskState {
+    Info,
+    #[default]
+    Time,
+    #[strum(default)]
+    Other(String),
+}
+#[derive(EnumString, Display, Clone, PartialEq, Eq, Debug)]
+pub enum TaskEffort {
+    Inconsistent,
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+    None,
+    #[strum(default)]
+    Other(String),
+}
+#[derive(EnumString, Display, Clone, PartialEq, Eq, Debug)]
+pub enum TaskQuadrant {
+    One,
+    Two,
+    Three,
+    None,
+    #[strum(default)]
+    Other(String),
+}
+uda_enum_serde!(TaskType, TaskStyle, TaskState, TaskEffort, TaskQuadrant);
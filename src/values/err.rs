@@ -12,10 +12,29 @@ pub enum FypmErrorKind {
     ProblemWithStoredTask,
     InvalidInput,
     InvalidConfig,
+    /// It occours when a backend (ex: the `task` binary) fails to execute an operation
+    BackendError,
 }
 
 #[derive(Debug)]
 pub struct FypmError {
     pub message: String,
     pub kind: FypmErrorKind,
-}
\ No newline at end of file
+}
+
+impl FypmError {
+    /// Wrap an error of the fypm database (ex: `.map_err(FypmError::database)?`).
+    pub fn database(error: diesel::result::Error) -> FypmError {
+        FypmError {
+            message: format!("Failed to access the fypm database: {}", error),
+            kind: FypmErrorKind::BackendError,
+        }
+    }
+}
+
+/// So a transaction can return `FypmError`.
+impl From<diesel::result::Error> for FypmError {
+    fn from(error: diesel::result::Error) -> Self {
+        FypmError::database(error)
+    }
+}
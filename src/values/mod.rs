@@ -2,4 +2,5 @@ pub mod constants;
 pub mod enums;
 pub mod err;
 pub mod structs;
-pub mod comments;
\ No newline at end of file
+pub mod comments;
+pub mod serialization;
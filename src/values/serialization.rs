@@ -0,0 +1,127 @@
+//! Serde helpers for the formats used by Taskwarrior exports and hooks.
+
+//#region           Crates
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{de, Deserialize, Deserializer, Serializer};
+use serde_json::Value;
+
+//#endregion
+//#region           Modules
+use super::constants::TASKWARRIOR_DATE_FORMAT;
+
+//#endregion
+//#region           Implementation
+/// Parse a date as Taskwarrior writes it (`20240110T090000Z`). RFC 3339 and ISO dates are accepted too.
+pub fn parse_taskwarrior_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDateTime::parse_from_str(value, TASKWARRIOR_DATE_FORMAT) {
+        return Some(date.and_utc());
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+/// Dates in the Taskwarrior format.
+pub mod taskwarrior_date {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        date: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&date.format(TASKWARRIOR_DATE_FORMAT))
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+
+        parse_taskwarrior_date(&value)
+            .ok_or_else(|| de::Error::custom(format!("invalid Taskwarrior date: {}", value)))
+    }
+}
+/// Optional dates in the Taskwarrior format. Use it with `#[serde(default)]`.
+pub mod optional_taskwarrior_date {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        date: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => super::taskwarrior_date::serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+    /// Empty strings are read as `None`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            None => Ok(None),
+            Some(value) if value.is_empty() => Ok(None),
+            Some(value) => parse_taskwarrior_date(&value)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("invalid Taskwarrior date: {}", value))),
+        }
+    }
+}
+/// `imask` is a number in exports, but older versions write it as a string.
+pub mod lenient_index {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(index: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        match index {
+            Some(index) => serializer.serialize_u32(*index),
+            None => serializer.serialize_none(),
+        }
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u32>, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Null => Ok(None),
+            Value::Number(number) => number
+                .as_f64()
+                .map(|number| Some(number as u32))
+                .ok_or_else(|| de::Error::custom("invalid index")),
+            Value::String(string) => string
+                .trim()
+                .parse::<f64>()
+                .map(|number| Some(number as u32))
+                .map_err(de::Error::custom),
+            other => Err(de::Error::custom(format!("invalid index: {}", other))),
+        }
+    }
+}
+/// `depends` is an array of UUIDs since Taskwarrior 2.6, and a comma separated string before it.
+pub mod depends {
+    use super::*;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<String>>, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Null => Ok(None),
+            Value::String(string) => Ok(Some(
+                string
+                    .split(',')
+                    .map(|uuid| uuid.trim().to_string())
+                    .filter(|uuid| !uuid.is_empty())
+                    .collect(),
+            )),
+            Value::Array(uuids) => Ok(Some(
+                uuids
+                    .iter()
+                    .filter_map(|uuid| uuid.as_str().map(|uuid| uuid.to_string()))
+                    .collect(),
+            )),
+            other => Err(de::Error::custom(format!("invalid depends: {}", other))),
+        }
+    }
+}
+//#endregion
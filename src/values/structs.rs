@@ -1,7 +1,13 @@
 use std::collections::BTreeMap;
 
-#[derive(Clone, Copy, PartialEq, serde::Deserialize, Debug)]
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use super::enums::{TaskEffort, TaskQuadrant, TaskState, TaskStyle, TaskType};
+use super::serialization::{depends, lenient_index, optional_taskwarrior_date, taskwarrior_date};
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize, Debug, Default)]
 pub enum TaskWarriorStatus {
+    #[default]
     #[serde(rename = "pending")]
     Pending,
     #[serde(rename = "completed")]
@@ -14,45 +20,122 @@ pub enum TaskWarriorStatus {
     Waiting,
 }
 
-#[derive(Clone, serde::Deserialize, Debug)]
+#[derive(Clone, serde::Deserialize, serde::Serialize, Debug, PartialEq)]
 pub struct TaskAnnotation {
-    pub entry: String,
+    #[serde(with = "taskwarrior_date")]
+    pub entry: DateTime<Utc>,
     pub description: String,
 }
-#[derive(Clone, serde::Deserialize, Debug)]
+/// A task as exported by Taskwarrior (or received by a hook).
+///
+/// Fypm UDAs are optional, so tasks created before them can still be read. Attributes that
+/// are not known here are kept in `extra`, so a task can be serialized back without losses.
+#[derive(Clone, serde::Deserialize, serde::Serialize, Debug, Default, PartialEq)]
 pub struct TaskWarriorExported {
+    /// `0` if the task is not pending, or if it was received by a hook.
+    #[serde(default, skip_serializing)]
     pub id: u32,
-    #[serde(rename = "STATE")]
-    pub state: String,
-    #[serde(rename = "STYLE")]
-    pub style: Option<String>,
-    #[serde(rename = "TYPE")]
-    pub r#type: String,
-    #[serde(rename = "WT")]
-    pub wt: String,
+    pub uuid: String,
+    pub description: String,
+    pub status: TaskWarriorStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<TaskAnnotation>>,
+    #[serde(default, skip_serializing)]
+    pub urgency: f64,
+
+    //#region       Dates
+    #[serde(with = "taskwarrior_date")]
+    pub entry: DateTime<Utc>,
+    #[serde(default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+    #[serde(default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Utc>>,
+    #[serde(default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled: Option<DateTime<Utc>>,
+    #[serde(default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait: Option<DateTime<Utc>>,
+    #[serde(default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(rename = "ALARM", default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alarm: Option<DateTime<Utc>>,
+    #[serde(rename = "GOAL", default, with = "optional_taskwarrior_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goal: Option<DateTime<Utc>>,
+    //#endregion
+
+    //#region       Relations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recur: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default, with = "lenient_index")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imask: Option<u32>,
+    #[serde(default, deserialize_with = "depends::deserialize")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends: Option<Vec<String>>,
+    #[serde(rename = "MOTHER")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mother: Option<String>,
+    #[serde(rename = "CHAIN")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
     #[serde(rename = "INFORELAT")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inforelat: Option<String>,
-    #[serde(rename = "ALIAS")]
-    pub alias: Option<String>,
     #[serde(rename = "SEQ_CURRENT")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub seq_current: Option<String>,
     #[serde(rename = "SEQ_NEXT")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub seq_next: Option<String>,
     #[serde(rename = "SEQ_PREVIOUS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub seq_prev: Option<String>,
-    pub description: String,
-    pub due: Option<String>,
-    pub entry: String,
-    pub modified: String,
-    pub parent: Option<String>,
-    pub project: Option<String>,
-    pub status: TaskWarriorStatus,
-    pub uuid: String,
-    pub annotations: Option<Vec<TaskAnnotation>>,
-    pub tags: Option<Vec<String>>,
-    pub urgency: f64,
-    pub effort: Option<String>,
-    pub quadrant: Option<String>,
+    //#endregion
+
+    //#region       Fypm UDAs
+    #[serde(rename = "STATE")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<TaskState>,
+    #[serde(rename = "STYLE")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<TaskStyle>,
+    #[serde(rename = "TYPE")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<TaskType>,
+    #[serde(rename = "WT")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wt: Option<String>,
+    #[serde(rename = "ALIAS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort: Option<TaskEffort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quadrant: Option<TaskQuadrant>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<String>,
+    //#endregion
+
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 #[derive(serde::Deserialize, Debug, Clone )]
 pub struct TimeWarriorExported {
@@ -60,6 +143,153 @@ pub struct TimeWarriorExported {
     pub start: String,
     pub end: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub annotation: Option<String>,
+}
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Default)]
+pub struct TaskInfo {
+    pub id: u32,
+    pub uuid: String,
+    pub description: String,
+    pub project: Option<String>,
+    pub style: Option<TaskStyle>,
+    pub wt: Option<String>,
+    pub r#type: Option<TaskType>,
+    pub quadrant: Option<TaskQuadrant>,
+    pub effort: Option<TaskEffort>,
+    pub tags: Vec<String>,
+}
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct DeletedTasksStatistic {
+    pub archived: usize,
+    pub failed: usize,
+    pub abandoned: usize,
+    pub nocontrol: usize,
+}
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct PendingTasksStatistic {
+    pub all: usize,
+    pub necessity: usize,
+    pub apollonian: usize,
+    pub dionysian: usize,
+    pub habit: usize,
+    pub eventual: usize,
+    pub objective: usize,
+    pub continuous: usize,
+    pub event: usize,
+    pub check: usize,
+    pub mother: usize,
+    pub subtask: usize,
+    pub style_none: usize,
+}
+/// Completion of the tasks due in a day or, when `kind` is "week", in the week that ends on `date`.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Default)]
+pub struct CompletionScore {
+    pub kind: String,
+    pub date: String,
+    pub pending: usize,
+    pub completed: usize,
+    pub deleted: usize,
+    pub total: usize,
+}
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Default)]
+pub struct TimeLogRecord {
+    pub id: i32,
+    pub uuid: String,
+    pub description: String,
+    pub start: String,
+    pub end: Option<String>,
+    pub annotation: Option<String>,
+}
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Default)]
+pub struct RecentTaskRecord {
+    /// How the task can be started again: `last`, `last2`...
+    pub alias: String,
+    pub uuid: String,
+    pub description: String,
+    pub started_at: String,
+}
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Default)]
+pub struct WorktimeScheduleRecord {
+    /// A weekday (`Mon`, `Tue`...) or a date (`YYYY-MM-DD`)
+    pub day: String,
+    /// None in holidays
+    pub worktime: Option<String>,
+    pub description: Option<String>,
+}
+/// The saved filters, as written by `filter export`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct FiltersFile {
+    #[serde(default, rename = "filter")]
+    pub filters: Vec<FilterEntry>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct FilterEntry {
+    pub name: String,
+    pub filter: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+/// The worktimes and their days, as written by `wt-export`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WorktimesFile {
+    #[serde(default, rename = "worktime")]
+    pub worktimes: Vec<WorktimeEntry>,
+    #[serde(default, rename = "holiday", skip_serializing_if = "Vec::is_empty")]
+    pub holidays: Vec<HolidayEntry>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct WorktimeEntry {
+    pub name: String,
+    pub description: String,
+    pub style: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub polybar_background: String,
+    pub polybar_foreground: String,
+    /// Weekdays (`Mon`, `Tue`...) and dates (`YYYY-MM-DD`) of the worktime
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct HolidayEntry {
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+/// A Timewarrior interval, in local time, with the WT of its task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedInterval {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub worktime: Option<String>,
+}
+/// Minutes of a worktime window in a day. `tracked` is the time spent on tasks of the worktime
+/// (or AllDay tasks) and `other` the time spent on tasks of other worktimes.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Default)]
+pub struct WorktimeReportRecord {
+    pub date: String,
+    pub worktime: String,
+    pub planned: i64,
+    pub tracked: i64,
+    pub other: i64,
+    pub untracked: i64,
+}
+/// What the status bars show: the applied worktime, the active task and the score of today.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Default)]
+pub struct StatusInfo {
+    pub worktime: Option<String>,
+    pub worktime_end: Option<String>,
+    pub worktime_background: Option<String>,
+    pub worktime_foreground: Option<String>,
+    pub task: Option<String>,
+    pub task_uuid: Option<String>,
+    /// Minutes since the active task was started
+    pub elapsed_minutes: Option<i64>,
+    /// Completed instances due today
+    pub completed_today: usize,
+    /// Instances due today
+    pub total_today: usize,
 }
 
 pub struct GetJsonByFilterOptions {
@@ -130,4 +360,24 @@ pub enum TaskWarriorUrgencyConfigScope {
 pub struct FypmConfigFile {
     pub name: String,
     pub map: BTreeMap<String, String>,
-}
\ No newline at end of file
+}
+/// A condition of a rule in `rules.fypm.ini`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskRuleCondition {
+    /// `+TAG`: the task has the tag
+    Tag(String),
+    /// `ATTRIBUTE`: the attribute is set
+    Present(String),
+    /// `ATTRIBUTE:VALUE`: the attribute has the value
+    Equals(String, String),
+}
+/// An invariant of the tasks: every task that matches `when` must match all the `require`
+/// conditions and none of the `forbid` conditions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskRule {
+    pub name: String,
+    pub when: Vec<TaskRuleCondition>,
+    pub require: Vec<TaskRuleCondition>,
+    pub forbid: Vec<TaskRuleCondition>,
+    pub message: String,
+}